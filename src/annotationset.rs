@@ -1,34 +1,59 @@
 use std::{
-    collections::{HashMap, hash_map::{IntoValues, Values}},
+    collections::HashMap,
+    slice::{Iter, IterMut},
+    vec::IntoIter,
 };
 use crate::annotation::Ann;
 
 /// A set of annotations with efficient indexing by image id.
 /// 
-/// Annotations cannot be modified in-place. Modification is
-/// achived through a remove-insert operation or by consuming
-/// and mapping.
+/// Annotations are iterated in insertion order, which makes the
+/// output of converters reproducible. Use `AnnSet::sort_by_img_id`
+/// to iterate in image identifier order instead.
+/// 
+/// Annotations are stored in a `Vec` in insertion order along with
+/// the position of each image identifier. Inserting an annotation of
+/// an image already present replaces it in-place. Annotations can be
+/// modified in-place through `AnnSet::anns_mut` as long as their image
+/// identifiers are left unchanged, since those are part of the index.
 #[derive(Debug, Clone)]
 pub struct AnnSet {
-    /// WARNING: do not mutate the annotation labels.
-    pub(crate) items: HashMap<String, Ann>,
+    /// The annotations in iteration order.
+    /// 
+    /// WARNING: do not mutate the annotation image identifiers.
+    items: Vec<Ann>,
+
+    /// The position of each annotation in `items`.
+    index: HashMap<String, usize>,
 }
 
 impl AnnSet {
     /// Creates an empty set of annotations.
     pub fn new() -> AnnSet {
-        AnnSet { items: HashMap::new() }
+        AnnSet { items: Vec::new(), index: HashMap::new() }
     }
 
     /// Creates an empty set of annotations with at least the specified
     /// capacity.
     pub fn with_capacity(capacity: usize) -> AnnSet {
-        AnnSet { items: HashMap::with_capacity(capacity) }
+        AnnSet { 
+            items: Vec::with_capacity(capacity), 
+            index: HashMap::with_capacity(capacity),
+        }
     }
 
     /// Reserves capacity for at least `additional` annotations.
     pub fn reserve(&mut self, additional: usize) {
-        self.items.reserve(additional)
+        self.items.reserve(additional);
+        self.index.reserve(additional);
+    }
+
+    fn reindex(&mut self) {
+        self.index.clear();
+
+        for (i, ann) in self.items.iter().enumerate() {
+            self.index.insert(ann.img_id.clone(), i);
+        }
     }
 }
 
@@ -37,68 +62,200 @@ impl AnnSet {
     /// Inserts an annotation into the annotation set.
     /// 
     /// If an annotation with the same image identifier was
-    /// already present, the old value is returned.
+    /// already present, it is replaced in-place, keeping its 
+    /// position, and the old value is returned.
     pub fn insert(&mut self, ann: Ann) -> Option<Ann> {
-        self.items.insert(ann.img_id.clone(), ann)
+        match self.index.get(&ann.img_id) {
+            Some(&i) => Some(std::mem::replace(&mut self.items[i], ann)),
+            None => {
+                self.index.insert(ann.img_id.clone(), self.items.len());
+                self.items.push(ann);
+                None
+            },
+        }
     }
 
     /// Returns a reference to the annotation corresponding to the
     /// specified image identifier.
     pub fn get(&self, img_id: &str) -> Option<&Ann> {
-        self.items.get(img_id)
+        self.index.get(img_id).map(|&i| &self.items[i])
     }
 
-    /// WARNING: do not mutate the annotation label as it
+    /// WARNING: do not mutate the annotation image identifier as it
     /// breaks the invariant.
     pub(crate) fn get_mut(&mut self, img_id: &str) -> Option<&mut Ann> {
-        self.items.get_mut(img_id)
+        self.index.get(img_id).map(|&i| &mut self.items[i])
+    }
+
+    /// Returns a mutable iterator over the annotations, for operations
+    /// modifying them in-place.
+    /// 
+    /// WARNING: do not mutate the annotation image identifiers as they 
+    /// are part of the index. Image sizes, labels and bounding boxes are
+    /// not and can safely be modified.
+    pub(crate) fn anns_mut(&mut self) -> IterMut<'_, Ann> {
+        self.items.iter_mut()
     }
 
     /// Removes and returns the annotation having the specified image 
     /// identifier from the annotation set.
+    /// 
+    /// This operation preserves the order of the other annotations
+    /// and is `O(n)`.
     pub fn remove(&mut self, img_id: &str) -> Option<Ann> {
-        self.items.remove(img_id)
+        let i = self.index.remove(img_id)?;
+        let ann = self.items.remove(i);
+
+        for ann in &self.items[i..] {
+            *self.index.get_mut(&ann.img_id).expect("Image id must be present.") -= 1;
+        }
+
+        Some(ann)
+    }
+
+    /// Only keeps the annotations for which the predicate returns true,
+    /// preserving their order.
+    pub fn retain<F: FnMut(&Ann) -> bool>(&mut self, f: F) {
+        self.items.retain(f);
+        self.reindex();
     }
 
     /// Returns true if the annotation set contains an annotation with 
     /// the specified image identifier.
     pub fn contains(&self, img_id: &str) -> bool {
-        self.items.contains_key(img_id)
+        self.index.contains_key(img_id)
     }
 
     /// Returns the number of bounding box annotations in the annotation set.
     pub fn len(&self) -> usize {
         self.items.len()
     }
+
+    /// Returns true if the annotation set contains no annotations.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the total number of bounding boxes of all annotations.
+    pub fn nb_bboxes(&self) -> usize {
+        self.items.iter().map(|ann| ann.bboxes.len()).sum()
+    }
+}
+
+impl AnnSet {
+    /// Sorts the annotations by image identifier so that they are 
+    /// iterated and saved in this order.
+    pub fn sort_by_img_id(&mut self) {
+        self.items.sort_by(|a, b| a.img_id.cmp(&b.img_id));
+        self.reindex();
+    }
+}
+
+impl Default for AnnSet {
+    fn default() -> Self {
+        AnnSet::new()
+    }
 }
 
 impl IntoIterator for AnnSet {
     type Item = Ann;
-    type IntoIter = IntoValues<String, Ann>;
+    type IntoIter = IntoIter<Ann>;
 
-    // TODO: O(capacity)
     fn into_iter(self) -> Self::IntoIter {
-        self.items.into_values()
+        self.items.into_iter()
     }
 }
 
 impl<'a> IntoIterator for &'a AnnSet {
     type Item = &'a Ann;
-    type IntoIter = Values<'a, String, Ann>;
+    type IntoIter = Iter<'a, Ann>;
 
-    // TODO: O(capacity)
     fn into_iter(self) -> Self::IntoIter {
-        self.items.values()
+        self.items.iter()
     }
 }
 
 impl AnnSet {
-    // TODO: O(capacity)
+    /// Returns an iterator over the annotations in insertion order.
     pub fn iter(&self) -> <&Self as IntoIterator>::IntoIter {
         self.into_iter()
     }
 }
 
+impl Extend<Ann> for AnnSet {
+    /// Inserts the annotations, replacing existing annotations
+    /// with the same image identifier.
+    fn extend<I: IntoIterator<Item = Ann>>(&mut self, iter: I) {
+        for ann in iter {
+            self.insert(ann);
+        }
+    }
+}
+
+impl FromIterator<Ann> for AnnSet {
+    /// Collects annotations into a set. The last annotation wins when
+    /// several annotations have the same image identifier.
+    fn from_iter<I: IntoIterator<Item = Ann>>(iter: I) -> Self {
+        let mut annset = AnnSet::new();
+        annset.extend(iter);
+        annset
+    }
+}
+
+/// How to resolve conflicts when the same image identifier is present
+/// in both annotation sets of a union.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conflict {
+    /// Keep the annotation of the left set.
+    KeepLeft,
+    /// Keep the annotation of the right set.
+    KeepRight,
+    /// Concatenate the bounding boxes of both annotations. The image size 
+    /// of the left annotation is used if present.
+    Concat,
+}
+
+impl AnnSet {
+    /// Returns the annotations of both sets, resolving annotations
+    /// present in both sets with the given policy.
+    pub fn union(mut self, other: AnnSet, conflict: Conflict) -> AnnSet {
+        self.reserve(other.len());
+
+        for ann in other {
+            match (self.get_mut(&ann.img_id), conflict) {
+                (None, _) | (Some(_), Conflict::KeepRight) => { self.insert(ann); },
+                (Some(_), Conflict::KeepLeft) => (),
+                (Some(left), Conflict::Concat) => {
+                    left.img_size = left.img_size.or(ann.img_size);
+                    left.bboxes.extend(ann.bboxes);
+                },
+            }
+        }
+
+        self
+    }
+
+    /// Returns the annotations whose image identifier is also present
+    /// in `other`.
+    pub fn intersection(mut self, other: &AnnSet) -> AnnSet {
+        self.retain(|ann| other.contains(&ann.img_id));
+        self
+    }
+
+    /// Returns the annotations whose image identifier is not present
+    /// in `other`.
+    pub fn difference(mut self, other: &AnnSet) -> AnnSet {
+        self.retain(|ann| !other.contains(&ann.img_id));
+        self
+    }
+
+    /// Merges ground truths and predictions into a single set, 
+    /// concatenating the bounding boxes of the same image.
+    pub fn merge_predictions(self, dets: AnnSet) -> AnnSet {
+        self.union(dets, Conflict::Concat)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::annotationset::*;
@@ -111,4 +268,62 @@ mod tests {
 
         assert!(anns.len() == 1);
     }
+
+    fn annset(anns: &[(&str, usize)]) -> AnnSet {
+        anns.iter()
+            .map(|&(img_id, nb_boxes)| {
+                let bbox = crate::bbox::BBox::new(img_id, 0.0, 0.0, 1.0, 1.0, None);
+                Ann::new(img_id, None, vec![bbox; nb_boxes])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_insertion_order() {
+        let mut anns = annset(&[("c", 0), ("a", 0), ("b", 0), ("d", 0)]);
+        let ids = |anns: &AnnSet| anns.iter().map(|a| a.img_id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&anns), ["c", "a", "b", "d"]);
+
+        anns.insert(Ann::empty("a"));
+        assert_eq!(ids(&anns), ["c", "a", "b", "d"]);
+
+        anns.remove("a");
+        assert_eq!(ids(&anns), ["c", "b", "d"]);
+        assert!(anns.get("d").is_some());
+        assert!(anns.get("a").is_none());
+
+        anns.sort_by_img_id();
+        assert_eq!(ids(&anns), ["b", "c", "d"]);
+        assert_eq!(anns.get("c").unwrap().img_id, "c");
+    }
+
+    #[test]
+    fn test_union() {
+        let left = annset(&[("a", 1), ("b", 1)]);
+        let right = annset(&[("b", 2), ("c", 1)]);
+
+        let keep_left = left.clone().union(right.clone(), Conflict::KeepLeft);
+        assert_eq!(keep_left.len(), 3);
+        assert_eq!(keep_left.get("b").unwrap().bboxes.len(), 1);
+
+        let keep_right = left.clone().union(right.clone(), Conflict::KeepRight);
+        assert_eq!(keep_right.get("b").unwrap().bboxes.len(), 2);
+
+        let concat = left.union(right, Conflict::Concat);
+        assert_eq!(concat.get("b").unwrap().bboxes.len(), 3);
+    }
+
+    #[test]
+    fn test_intersection_difference() {
+        let left = annset(&[("a", 1), ("b", 1)]);
+        let right = annset(&[("b", 2), ("c", 1)]);
+
+        let inter = left.clone().intersection(&right);
+        assert_eq!(inter.len(), 1);
+        assert_eq!(inter.get("b").unwrap().bboxes.len(), 1);
+
+        let diff = left.difference(&right);
+        assert_eq!(diff.len(), 1);
+        assert!(diff.contains("a"));
+    }
 }
\ No newline at end of file
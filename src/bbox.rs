@@ -1,11 +1,23 @@
+use std::collections::BTreeMap;
+
 use crate::imgsize::ImgSize;
 use crate::coords::{
     Coords,
+    abs_to_rel,
     rel_to_abs,
     ltwh_to_ltrb,
     xywh_to_ltrb,
 };
 
+/// The attribute flagging COCO crowd regions.
+pub const CROWD_ATTR: &str = "iscrowd";
+
+/// The attribute flagging Pascal VOC difficult objects.
+pub const DIFFICULT_ATTR: &str = "difficult";
+
+/// The attribute flagging OpenImages group-of boxes.
+pub const GROUP_OF_ATTR: &str = "IsGroupOf";
+
 /// A rectangular bounding box with a label and an optional 
 /// confidence score.
 /// 
@@ -19,6 +31,13 @@ pub struct BBox {
     xmin: f32, ymin: f32,
     xmax: f32, ymax: f32,
     conf: Option<f32>,
+
+    /// Additional format-specific attributes of the bounding box
+    /// such as occlusion or truncation flags.
+    /// 
+    /// Attributes are kept as raw strings so that they survive a
+    /// parse-save round trip unchanged.
+    pub attributes: BTreeMap<String, String>,
 }
 
 impl BBox {
@@ -49,12 +68,17 @@ impl BBox {
         
         if let Some(conf) = conf {
             assert!(
-                0.0 <= conf && conf <= 1.0, 
+                (0.0..=1.0).contains(&conf), 
                 "confidence score ({}) should be in 0..=1", conf
             );
         }
 
-        Self { label: label.into(), xmin, ymin, xmax, ymax, conf }
+        Self { 
+            label: label.into(), 
+            xmin, ymin, xmax, ymax, 
+            conf, 
+            attributes: BTreeMap::new(),
+        }
     }
 
     /// Creates a bounding box annotation in the given coordinate format.
@@ -131,7 +155,7 @@ impl BBox {
     /// # Panics
     /// The operation panics if the score is not in `0..=1`.
     pub fn set_conf(&mut self, conf: f32) {
-        assert!(0.0 <= conf && conf <= 1.0);
+        assert!((0.0..=1.0).contains(&conf));
         self.conf = Some(conf);
     }
 }
@@ -140,16 +164,16 @@ impl BBox {
 /// 
 /// The available formats are:
 /// * `BBoxFormat::LTRB`: 
-///`(xmin, ymin, xmax, ymax)` where `(xmin, ymin)` is
-/// the top-left corner and `(xmax, ymax)` the bottom-right one.
+///   `(xmin, ymin, xmax, ymax)` where `(xmin, ymin)` is
+///   the top-left corner and `(xmax, ymax)` the bottom-right one.
 /// 
 /// * `BBoxFormat::LTWH`:
-/// `(xmin, ymin, width, height)` where `(xmin, ymin)` is
-/// the top-left corner and `(width, height)` the bounding box size.
+///   `(xmin, ymin, width, height)` where `(xmin, ymin)` is
+///   the top-left corner and `(width, height)` the bounding box size.
 /// 
 /// * `BBoxFormat::XYWH`:
-/// `(xmid, ymid, width, height)` where `(xmid, ymid)` is
-/// bounding box center and `(width, height)` the bounding box size.
+///   `(xmid, ymid, width, height)` where `(xmid, ymid)` is
+///   bounding box center and `(width, height)` the bounding box size.
 #[derive(Clone, Copy)]
 pub enum BBoxFmt {
     /// `(xmin, ymin, xmax, ymax)` where `(xmin, ymin)` is
@@ -199,6 +223,149 @@ impl BBox {
     }
 }
 
+impl BBox {
+    /// Clips the bounding box to the image bounds.
+    pub fn clip(&mut self, img_size: ImgSize) {
+        let (w, h) = (img_size.width as f32, img_size.height as f32);
+
+        self.xmin = self.xmin.clamp(0.0, w);
+        self.ymin = self.ymin.clamp(0.0, h);
+        self.xmax = self.xmax.clamp(0.0, w);
+        self.ymax = self.ymax.clamp(0.0, h);
+    }
+
+    /// Scales the bounding box coordinates, for instance after an
+    /// image resize.
+    /// 
+    /// # Panics
+    /// Will panic if a scale factor is negative.
+    pub fn scale(&mut self, sx: f32, sy: f32) {
+        assert!(sx >= 0.0 && sy >= 0.0, "scale factors should be positive");
+
+        self.xmin *= sx;
+        self.ymin *= sy;
+        self.xmax *= sx;
+        self.ymax *= sy;
+    }
+
+    /// Moves the bounding box by the given offsets.
+    pub fn translate(&mut self, dx: f32, dy: f32) {
+        self.xmin += dx;
+        self.ymin += dy;
+        self.xmax += dx;
+        self.ymax += dy;
+    }
+
+    /// Grows the bounding box by `dx` pixels on the left and the right
+    /// and by `dy` pixels on the top and the bottom.
+    /// 
+    /// Negative values shrink the bounding box, which collapses to its 
+    /// center if it would become inverted.
+    pub fn pad(&mut self, dx: f32, dy: f32) {
+        let (xmid, ymid) = (self.xmid(), self.ymid());
+
+        self.xmin = (self.xmin - dx).min(xmid);
+        self.ymin = (self.ymin - dy).min(ymid);
+        self.xmax = (self.xmax + dx).max(xmid);
+        self.ymax = (self.ymax + dy).max(ymid);
+    }
+
+    /// Grows the bounding box by a ratio of its size on each side, 
+    /// e.g. `0.1` increases the width and the height by 20%.
+    /// 
+    /// See `BBox::pad` for negative values.
+    pub fn pad_ratio(&mut self, ratio: f32) {
+        self.pad(self.width() * ratio, self.height() * ratio);
+    }
+
+    /// Converts from the inclusive pixel convention, where `xmax` and `ymax` 
+    /// are the last pixels of the box as in Pascal VOC, to the exclusive
+    /// convention used by `BBox`, where they are one past the box.
+    pub fn inclusive_to_exclusive(&mut self) {
+        self.xmax += 1.0;
+        self.ymax += 1.0;
+    }
+
+    /// Converts from the exclusive pixel convention to the inclusive
+    /// one, see `BBox::inclusive_to_exclusive`.
+    pub fn exclusive_to_inclusive(&mut self) {
+        self.xmax = (self.xmax - 1.0).max(self.xmin);
+        self.ymax = (self.ymax - 1.0).max(self.ymin);
+    }
+}
+
+impl BBox {
+    /// Returns the intersection of the two bounding boxes, or `None` 
+    /// if they do not overlap. Boxes which only touch do not overlap.
+    /// 
+    /// The result has the label of `self` and no confidence score.
+    pub fn intersection(&self, other: &BBox) -> Option<BBox> {
+        let xmin = self.xmin.max(other.xmin);
+        let ymin = self.ymin.max(other.ymin);
+        let xmax = self.xmax.min(other.xmax);
+        let ymax = self.ymax.min(other.ymax);
+
+        if xmin >= xmax || ymin >= ymax {
+            return None
+        }
+
+        Some(BBox::new(self.label.as_str(), xmin, ymin, xmax, ymax, None))
+    }
+
+    /// Returns the smallest bounding box enclosing both bounding boxes.
+    /// 
+    /// The result has the label of `self` and no confidence score.
+    pub fn union(&self, other: &BBox) -> BBox {
+        BBox::new(
+            self.label.as_str(),
+            self.xmin.min(other.xmin),
+            self.ymin.min(other.ymin),
+            self.xmax.max(other.xmax),
+            self.ymax.max(other.ymax),
+            None,
+        )
+    }
+
+    /// Returns true if `other` is entirely inside the bounding box.
+    pub fn contains(&self, other: &BBox) -> bool {
+        self.xmin <= other.xmin && self.ymin <= other.ymin 
+            && other.xmax <= self.xmax && other.ymax <= self.ymax
+    }
+
+    /// Returns true if the point is inside the bounding box.
+    pub fn contains_point(&self, x: f32, y: f32) -> bool {
+        self.xmin <= x && x <= self.xmax && self.ymin <= y && y <= self.ymax
+    }
+
+    /// Returns true if the bounding box is entirely inside the image.
+    pub fn is_inside(&self, img_size: ImgSize) -> bool {
+        self.xmin >= 0.0 && self.ymin >= 0.0 
+            && self.xmax <= img_size.width as f32 && self.ymax <= img_size.height as f32
+    }
+
+    /// Returns the coordinates in the given format, relative to the
+    /// image size.
+    pub fn coords_rel(&self, fmt: BBoxFmt, img_size: ImgSize) -> Coords {
+        abs_to_rel(self.coords(fmt), img_size)
+    }
+}
+
+impl BBox {
+    /// Returns the value of the attribute with the given name, if any.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    /// Sets the value of the attribute with the given name and
+    /// returns the previous value, if any.
+    pub fn set_attribute<K, V>(&mut self, name: K, value: V) -> Option<String> where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.attributes.insert(name.into(), value.into())
+    }
+}
+
 impl BBox {
     pub fn is_ground_truth(&self) -> bool {
         self.conf.is_none()
@@ -293,6 +460,17 @@ mod tests {
         bbox.set_conf(1.1);
     }
 
+    #[test]
+    fn attributes() {
+        let mut bbox = BBox::new("", 0.0, 0.0, 0.0, 0.0, None);
+        assert!(bbox.attribute("occluded").is_none());
+
+        assert!(bbox.set_attribute("occluded", "1").is_none());
+        assert!(bbox.attribute("occluded") == Some("1"));
+
+        assert!(bbox.set_attribute("occluded", "2") == Some(String::from("1")));
+    }
+
     #[test]
     fn create_rel_ltrb() {
         let bbox = BBox::create_rel("", (0.0, 0.0, 0.75, 1.0), BBoxFmt::LTRB, None, ImgSize::new(100, 200));
@@ -335,6 +513,70 @@ mod tests {
         assert!(bbox.height() == 200.0);
     }
 
+    #[test]
+    fn clip() {
+        let mut bbox = BBox::new("", -10.0, 5.0, 50.0, 150.0, None);
+        bbox.clip(ImgSize::new(40, 100));
+
+        assert!(bbox.ltrb() == (0.0, 5.0, 40.0, 100.0));
+        assert!(bbox.is_inside(ImgSize::new(40, 100)));
+    }
+
+    #[test]
+    fn scale_translate() {
+        let mut bbox = BBox::new("", 10.0, 10.0, 20.0, 30.0, None);
+        bbox.scale(2.0, 0.5);
+        assert!(bbox.ltrb() == (20.0, 5.0, 40.0, 15.0));
+
+        bbox.translate(-20.0, 5.0);
+        assert!(bbox.ltrb() == (0.0, 10.0, 20.0, 20.0));
+    }
+
+    #[test]
+    fn pad() {
+        let mut bbox = BBox::new("", 10.0, 10.0, 20.0, 30.0, None);
+        bbox.pad(5.0, 1.0);
+        assert!(bbox.ltrb() == (5.0, 9.0, 25.0, 31.0));
+
+        bbox.pad(-100.0, -100.0);
+        assert!(bbox.ltrb() == (15.0, 20.0, 15.0, 20.0));
+
+        let mut bbox = BBox::new("", 0.0, 0.0, 10.0, 20.0, None);
+        bbox.pad_ratio(0.5);
+        assert!(bbox.ltrb() == (-5.0, -10.0, 15.0, 30.0));
+    }
+
+    #[test]
+    fn pixel_conventions() {
+        let mut bbox = BBox::new("", 0.0, 0.0, 9.0, 9.0, None);
+        bbox.inclusive_to_exclusive();
+        assert!(bbox.width() == 10.0);
+
+        bbox.exclusive_to_inclusive();
+        assert!(bbox.ltrb() == (0.0, 0.0, 9.0, 9.0));
+    }
+
+    #[test]
+    fn intersection_union() {
+        let b1 = BBox::new("a", 0.0, 0.0, 10.0, 10.0, Some(0.5));
+        let b2 = BBox::new("b", 5.0, 5.0, 20.0, 20.0, None);
+        let b3 = BBox::new("c", 30.0, 30.0, 40.0, 40.0, None);
+
+        let inter = b1.intersection(&b2).unwrap();
+        assert!(inter.ltrb() == (5.0, 5.0, 10.0, 10.0));
+        assert!(inter.label == "a" && inter.is_ground_truth());
+        assert!(b1.intersection(&b3).is_none());
+
+        let touching = BBox::new("d", 10.0, 0.0, 20.0, 10.0, None);
+        assert!(b1.intersection(&touching).is_none());
+
+        let union = b1.union(&b3);
+        assert!(union.ltrb() == (0.0, 0.0, 40.0, 40.0));
+        assert!(union.contains(&b2));
+        assert!(!b1.contains(&b2));
+        assert!(b1.contains_point(10.0, 0.0));
+    }
+
     // #[test]
     // fn iou() {
     //     let bbox1 = BBox::new("", 0.0, 0.0, 10.0, 10.0, None);
@@ -1,12 +1,34 @@
 mod parse;
 mod convert;
 mod eval;
+mod check;
+mod labels;
+mod filters;
+mod split;
+mod nms;
+mod fuse;
+mod compare;
 
 use parse::Parse;
 use convert::Convert;
 use eval::Eval;
+use check::Check;
+use split::Split;
+use nms::Nms;
+use fuse::Fuse;
+use compare::Compare;
+use labels::MapLabels;
 
-use clap::{Parser, Subcommand, ValueEnum};
+use crate::{
+    annotationset::AnnSet,
+    parsers::ParseError,
+    converters::ConvError,
+    path::expand_user,
+};
+
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(version, about)]
@@ -28,16 +50,218 @@ enum Commands {
 
     /// Evaluate bounding box annotations and predictions
     Eval(Eval),
+
+    /// Rename, merge and filter labels
+    MapLabels(MapLabels),
+
+    /// Split annotations into train, validation and test sets or k folds
+    Split(Split),
+
+    /// Check annotations for problems, exits with code 1 on warnings and 2 on errors
+    Check(Check),
+
+    /// Remove overlapping predictions with (Soft-)Non-Maximum Suppression
+    Nms(Nms),
+
+    /// Ensemble the predictions of several models with Weighted Boxes Fusion
+    Fuse(Fuse),
+
+    /// Compare the predictions of two models with paired bootstrap resampling
+    Compare(Compare),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum ReportFmt {
+    Table, Json, Csv
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum SrcAnnFmt {
-    Coco, Cvat, Imagenet, Labelme, OpenImage, Yolo, PascalVoc
+    Coco, Cvat, Imagenet, Labelme, OpenImage, Yolo, PascalVoc, Kitti,
+    LabelStudio, LabelStudioPredictions, Tfrecord, Createml, Supervisely,
+    WiderFace, Via, Cityscapes,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum DstAnnFmt {
-    Coco, Cvat, Imagenet, Labelme, OpenImage, Yolo, PascalVoc, Vit
+    Coco, Cvat, Imagenet, Labelme, OpenImage, Yolo, PascalVoc, Kitti, LabelStudio, Tfrecord, Createml, Supervisely, 
+    WiderFace, Via, Cityscapes, Vit
+}
+
+/// Format-specific options used to parse annotations.
+struct SrcOpts<'a> {
+    imgs_path: Option<PathBuf>,
+    img_ext: &'a str,
+    conf_last: bool,
+    keep_dont_care: bool,
+    label_attr: &'a str,
+}
+
+impl SrcAnnFmt {
+    fn parse(self, path: PathBuf, opts: SrcOpts) -> Result<AnnSet, ParseError> {
+        let SrcOpts { imgs_path, img_ext, conf_last, keep_dont_care, label_attr } = opts;
+
+        match self {
+            SrcAnnFmt::Coco => AnnSet::parse_coco(path),
+            SrcAnnFmt::Cvat => AnnSet::parse_cvat(path),
+            SrcAnnFmt::Imagenet => AnnSet::parse_imagenet(path),
+            SrcAnnFmt::Labelme => AnnSet::parse_labelme(path),
+            SrcAnnFmt::OpenImage => {
+                let imgs_path = imgs_path
+                    .expect("parsing OpenImage requires `imgs_path`");
+
+                AnnSet::parse_openimage(path, imgs_path)
+            },
+            SrcAnnFmt::Yolo => {
+                let imgs_path = imgs_path
+                    .expect("parsing YOLO requires `imgs_path`");
+
+                AnnSet::parse_yolo(path, imgs_path, conf_last, img_ext)
+            },
+            SrcAnnFmt::PascalVoc => AnnSet::parse_pascal_voc(path),
+            SrcAnnFmt::Kitti => AnnSet::parse_kitti(path, imgs_path, img_ext, keep_dont_care),
+            SrcAnnFmt::LabelStudio => AnnSet::parse_labelstudio(path),
+            SrcAnnFmt::LabelStudioPredictions => AnnSet::parse_labelstudio_predictions(path),
+            SrcAnnFmt::Tfrecord => AnnSet::parse_tfrecord(path),
+            SrcAnnFmt::Createml => AnnSet::parse_createml(path, imgs_path),
+            SrcAnnFmt::Supervisely => AnnSet::parse_supervisely(path),
+            SrcAnnFmt::WiderFace => AnnSet::parse_widerface(path, imgs_path),
+            SrcAnnFmt::Via => AnnSet::parse_via(path, imgs_path, label_attr),
+            SrcAnnFmt::Cityscapes => AnnSet::parse_cityscapes(path),
+        }
+    }
+}
+
+/// Format-specific options used to save annotations.
+struct DstOpts<'a> {
+    conf_last: bool,
+    img_prefix: &'a str,
+    imgs_path: Option<PathBuf>,
+    dataset: &'a str,
+    label_attr: &'a str,
+}
+
+impl DstAnnFmt {
+    fn save(self, anns: &AnnSet, path: PathBuf, opts: DstOpts) -> Result<(), ConvError> {
+        let DstOpts { conf_last, img_prefix, imgs_path, dataset, label_attr } = opts;
+
+        match self {
+            DstAnnFmt::Coco => anns.save_coco(path),
+            DstAnnFmt::Cvat => anns.save_cvat(path),
+            DstAnnFmt::Imagenet => anns.save_imagenet(path),
+            DstAnnFmt::Labelme => anns.save_labelme(path),
+            DstAnnFmt::OpenImage => anns.save_openimage(path),
+            DstAnnFmt::Yolo => anns.save_yolo(path, conf_last),
+            DstAnnFmt::PascalVoc => anns.save_pascal_voc(path),
+            DstAnnFmt::Kitti => anns.save_kitti(path),
+            DstAnnFmt::LabelStudio => anns.save_labelstudio(path, img_prefix),
+            DstAnnFmt::Tfrecord => anns.save_tfrecord(path, imgs_path),
+            DstAnnFmt::Createml => anns.save_createml(path),
+            DstAnnFmt::Supervisely => anns.save_supervisely(path, dataset),
+            DstAnnFmt::WiderFace => anns.save_widerface(path),
+            DstAnnFmt::Via => anns.save_via(path, label_attr),
+            DstAnnFmt::Cityscapes => anns.save_cityscapes(path),
+            DstAnnFmt::Vit => todo!("not yet implemented"),
+        }
+    }
+
+    /// The file extension of single-file formats, `None` for formats
+    /// saved as a directory.
+    fn file_ext(self) -> Option<&'static str> {
+        match self {
+            DstAnnFmt::Coco 
+            | DstAnnFmt::LabelStudio 
+            | DstAnnFmt::Createml 
+            | DstAnnFmt::Via => Some("json"),
+            DstAnnFmt::Cvat => Some("xml"),
+            DstAnnFmt::OpenImage => Some("csv"),
+            DstAnnFmt::WiderFace => Some("txt"),
+            DstAnnFmt::Tfrecord => Some("tfrecord"),
+            DstAnnFmt::Imagenet
+            | DstAnnFmt::Labelme
+            | DstAnnFmt::Yolo
+            | DstAnnFmt::PascalVoc
+            | DstAnnFmt::Kitti
+            | DstAnnFmt::Supervisely
+            | DstAnnFmt::Cityscapes
+            | DstAnnFmt::Vit => None,
+        }
+    }
+}
+
+/// Format-specific arguments of commands saving annotations.
+#[derive(Args)]
+struct DstArgs {
+    #[arg(long, help = "Whether or not the confidence of output annotations should be stored in last position (YOLO only)")]
+    dst_conf_last: bool,
+
+    #[arg(long, default_value = "", help = "The prefix of output image URLs (Label Studio only)")]
+    dst_img_prefix: String,
+
+    #[arg(long, help = "Embed the images of this directory in output annotations (TFRecord only)")]
+    dst_imgs_path: Option<String>,
+
+    #[arg(long, default_value = "ds0", help = "The dataset name of output annotations (Supervisely only)")]
+    dst_dataset: String,
+
+    #[arg(long, default_value = "label", help = "The region attribute storing the label of output annotations (VIA only)")]
+    dst_label_attr: String,
+}
+
+impl DstArgs {
+    fn opts(&self) -> DstOpts<'_> {
+        DstOpts {
+            conf_last: self.dst_conf_last,
+            img_prefix: &self.dst_img_prefix,
+            imgs_path: self.dst_imgs_path.as_ref().map(expand_user),
+            dataset: &self.dst_dataset,
+            label_attr: &self.dst_label_attr,
+        }
+    }
+}
+
+/// Arguments of commands operating on a single set of annotations.
+#[derive(Args)]
+struct SrcArgs {
+    #[arg(help = "The format of the annotations")]
+    format: SrcAnnFmt,
+
+    #[arg(help = "The file or directory path of the annotations")]
+    path: String, 
+
+    #[arg(long, help = "The image directory of the annotations")]
+    imgs_path: Option<String>,
+
+    #[arg(long, default_value = "jpg", help = "The image extension (YOLO and KITTI only)")]
+    img_ext: String,
+
+    #[arg(long, help = "Whether or not the confidence is stored in last position (YOLO only)")]
+    conf_last: bool,
+
+    #[arg(long, help = "Whether or not to keep `DontCare` regions (KITTI only)")]
+    keep_dont_care: bool,
+
+    #[arg(long, default_value = "label", help = "The region attribute storing the label (VIA only)")]
+    label_attr: String,
+}
+
+impl SrcArgs {
+    fn imgs_path(&self) -> Option<PathBuf> {
+        self.imgs_path.as_ref().map(expand_user)
+    }
+
+    fn parse(&self) -> AnnSet {
+        let opts = SrcOpts {
+            imgs_path: self.imgs_path(),
+            img_ext: &self.img_ext,
+            conf_last: self.conf_last,
+            keep_dont_care: self.keep_dont_care,
+            label_attr: &self.label_attr,
+        };
+
+        self.format.parse(expand_user(&self.path), opts)
+            .expect("failed to parse the annotations")
+    }
 }
 
 pub fn run() {
@@ -47,5 +271,11 @@ pub fn run() {
         Commands::Parse(parse) => parse.run(),
         Commands::Convert(convert) => convert.run(),
         Commands::Eval(eval) => eval.run(),
+        Commands::MapLabels(map_labels) => map_labels.run(),
+        Commands::Split(split) => split.run(),
+        Commands::Check(check) => check.run(),
+        Commands::Nms(nms) => nms.run(),
+        Commands::Fuse(fuse) => fuse.run(),
+        Commands::Compare(compare) => compare.run(),
     }
 }
\ No newline at end of file
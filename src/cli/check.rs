@@ -0,0 +1,69 @@
+use crate::{
+    path::expand_user,
+    validation::Severity,
+};
+
+use super::SrcArgs;
+
+use std::{
+    collections::HashSet,
+    fs,
+    process,
+};
+
+use clap::Args;
+
+#[derive(Args)]
+pub(super) struct Check {
+    #[command(flatten)]
+    src: SrcArgs,
+
+    #[arg(long, help = "A file listing the allowed labels, one per line")]
+    labels: Option<String>,
+
+    #[arg(long, help = "Check that the images exist and have the annotated size (requires `imgs_path`)")]
+    check_imgs: bool,
+}
+
+impl Check {
+    /// Exits with code 2 if errors were found, 1 if only warnings were
+    /// found and 0 otherwise.
+    pub(super) fn run(self) {
+        let annset = self.src.parse();
+
+        let allowed_labels = self.labels.map(|p| {
+            fs::read_to_string(expand_user(p))
+                .expect("failed to read the label file")
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(String::from)
+                .collect::<HashSet<_>>()
+        });
+
+        let imgs_path = if self.check_imgs {
+            Some(self.src.imgs_path().expect("checking images requires `imgs_path`"))
+        } else { None };
+
+        let issues = annset.validate(imgs_path, allowed_labels.as_ref());
+
+        for issue in &issues {
+            println!("{issue}");
+        }
+
+        let nb_errors = issues.iter()
+            .filter(|i| i.severity() == Severity::Error)
+            .count();
+        let nb_warnings = issues.len() - nb_errors;
+
+        println!("Checked {} annotations: {} errors, {} warnings.", annset.len(), nb_errors, nb_warnings);
+
+        let code = match issues.iter().map(|i| i.severity()).max() {
+            Some(Severity::Error) => 2,
+            Some(Severity::Warning) => 1,
+            None => 0,
+        };
+
+        process::exit(code);
+    }
+}
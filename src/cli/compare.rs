@@ -0,0 +1,127 @@
+use crate::{
+    evaluators::coco::{COCOEval, IOU_THRESHOLDS},
+    path::expand_user,
+};
+
+use super::{labels::EvalLabelArgs, SrcAnnFmt, SrcOpts, ReportFmt, eval::IoUKindArg};
+
+use std::{fs, time::Instant};
+
+use clap::Args;
+
+#[derive(Args)]
+pub(super) struct Compare {
+    #[arg(help = "The format of the ground truth annotations")]
+    gts_fmt: SrcAnnFmt,
+
+    #[arg(help = "The file or directory path of the ground truth annotations")]
+    gts_path: String,
+
+    #[arg(help = "The format of the predicted annotations")]
+    dets_fmt: SrcAnnFmt,
+
+    #[arg(help = "The file or directory path of the predictions of model A")]
+    dets_a_path: String,
+
+    #[arg(help = "The file or directory path of the predictions of model B")]
+    dets_b_path: String,
+
+    #[arg(long, help = "The image directory of the annotations")]
+    imgs_path: Option<String>,
+
+    #[arg(long, default_value = "jpg", help = "The image extension of annotation images (YOLO only)")]
+    img_ext: String,
+
+    #[arg(long, help = "Whether or not the confidence of predicted annotations is stored in last position (YOLO only)")]
+    dets_conf_last: bool,
+
+    #[command(flatten)]
+    labels: EvalLabelArgs,
+
+    #[arg(long, value_enum, default_value = "iou", help = "The overlap measure used to match predictions with ground truths")]
+    iou_kind: IoUKindArg,
+
+    #[arg(long, value_delimiter = ',', help = "The comma-separated IoU thresholds to average the AP over, defaults to 0.50:0.95")]
+    iou_thresholds: Vec<f32>,
+
+    #[arg(long, default_value_t = 1000, help = "The number of bootstrap samples")]
+    samples: usize,
+
+    #[arg(long, default_value_t = 0.95, help = "The confidence level of the intervals")]
+    confidence: f32,
+
+    #[arg(long, default_value_t = 0, help = "The seed of the image resampling")]
+    seed: u64,
+
+    #[arg(long, value_enum, default_value = "table", help = "The format of the comparison report")]
+    report_fmt: ReportFmt,
+
+    #[arg(long, help = "The file to write the comparison report to instead of the standard output")]
+    report_path: Option<String>,
+}
+
+impl Compare {
+    pub(super) fn run(self) {
+        let imgs_path = self.imgs_path.map(expand_user);
+
+        let opts = |conf_last, keep_dont_care| SrcOpts {
+            imgs_path: imgs_path.clone(),
+            img_ext: &self.img_ext,
+            conf_last,
+            keep_dont_care,
+            label_attr: "label",
+        };
+
+        let mut gts = self.gts_fmt.parse(expand_user(self.gts_path), opts(false, true))
+            .expect("failed to parse the ground truth annotations");
+
+        let mut dets_a = self.dets_fmt.parse(expand_user(self.dets_a_path), opts(self.dets_conf_last, false))
+            .expect("failed to parse the predictions of model A");
+
+        let mut dets_b = self.dets_fmt.parse(expand_user(self.dets_b_path), opts(self.dets_conf_last, false))
+            .expect("failed to parse the predictions of model B");
+
+        self.labels.apply_gts(&mut gts);
+        self.labels.apply_dets(&mut dets_a);
+        self.labels.apply_dets(&mut dets_b);
+
+        let threshs = if self.iou_thresholds.is_empty() {
+            IOU_THRESHOLDS.to_vec()
+        } else {
+            self.iou_thresholds
+        };
+
+        let time = Instant::now();
+
+        let eval_a = COCOEval::new(&gts, &dets_a)
+            .with_iou_kind(self.iou_kind.into())
+            .with_ignore_labels(self.labels.ignore_labels.iter().cloned());
+
+        let eval_b = COCOEval::new(&gts, &dets_b)
+            .with_iou_kind(self.iou_kind.into())
+            .with_ignore_labels(self.labels.ignore_labels);
+
+        let comparison = eval_a.compare(&eval_b, &threshs, self.samples, self.confidence, self.seed);
+
+        // Keep the standard output parsable for machine-readable reports.
+        let eval_msg = format!("Compared {} images in {:#?}.", comparison.nb_imgs, time.elapsed());
+
+        match (self.report_fmt, &self.report_path) {
+            (ReportFmt::Table, None) => println!("{eval_msg}\n"),
+            _ => eprintln!("{eval_msg}"),
+        }
+
+        let report = match self.report_fmt {
+            ReportFmt::Table => comparison.to_string(),
+            ReportFmt::Json => serde_json::to_string_pretty(&comparison)
+                .expect("failed to serialize the comparison"),
+            ReportFmt::Csv => comparison.to_csv(),
+        };
+
+        match self.report_path {
+            Some(report_path) => fs::write(expand_user(report_path), report)
+                .expect("failed to write the comparison report"),
+            None => print!("{report}"),
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use crate::{annotationset::AnnSet, path::expand_user};
+use crate::path::expand_user;
 
-use super::{SrcAnnFmt, DstAnnFmt};
+use super::{SrcAnnFmt, SrcOpts, DstAnnFmt, DstArgs, labels::LabelArgs, filters::FilterArgs};
 
 use clap::Args;
 
@@ -21,57 +21,56 @@ pub(super) struct Convert {
     #[arg(long, help = "The image directory of the input annotations")]
     imgs_path: Option<String>,
 
-    #[arg(long, default_value = "jpg", help = "The image extension of input annotation images (YOLO only)")]
+    #[arg(long, default_value = "jpg", help = "The image extension of input annotation images (YOLO and KITTI only)")]
     src_img_ext: String,
 
     #[arg(long, help = "Whether or not the confidence of input annotations is stored in last position (YOLO only)")]
     src_conf_last: bool,
 
-    #[arg(long, help = "Whether or not the confidence of output annotations should be stored in last position (YOLO only)")]
-    dst_conf_last: bool,
+    #[arg(long, help = "Whether or not to keep `DontCare` regions of input annotations (KITTI only)")]
+    src_keep_dont_care: bool,
+
+    #[arg(long, default_value = "label", help = "The region attribute storing the label of input annotations (VIA only)")]
+    src_label_attr: String,
+
+    #[command(flatten)]
+    dst: DstArgs,
+
+    #[arg(long, help = "Save the annotations sorted by image identifier instead of in input order")]
+    sort: bool,
+
+    #[command(flatten)]
+    labels: LabelArgs,
+
+    #[command(flatten)]
+    filters: FilterArgs,
 }
 
 impl Convert {
     pub(super) fn run(self) {
         let path = expand_user(self.src_path);
 
-        let anns = match self.src_fmt {
-            SrcAnnFmt::Coco => AnnSet::parse_coco(path),
-            SrcAnnFmt::Cvat => AnnSet::parse_cvat(path),
-            SrcAnnFmt::Imagenet => AnnSet::parse_imagenet(path),
-            SrcAnnFmt::Labelme => AnnSet::parse_labelme(path),
-            SrcAnnFmt::OpenImage => {
-                let imgs_path = self.imgs_path
-                    .expect("parsing OpenImage requires `imgs_path`");
-                let imgs_path = expand_user(imgs_path);
-
-                AnnSet::parse_openimage(path, imgs_path)
-            },
-            SrcAnnFmt::Yolo => {
-                let imgs_path = self.imgs_path
-                    .expect("parsing OpenImage requires `imgs_path`");
-                let imgs_path = expand_user(imgs_path);
-
-                AnnSet::parse_yolo(path, imgs_path, self.src_conf_last, &self.src_img_ext)
-            },
-            SrcAnnFmt::PascalVoc => {
-                AnnSet::parse_pascal_voc(path)
-            },
+        let opts = SrcOpts {
+            imgs_path: self.imgs_path.map(expand_user),
+            img_ext: &self.src_img_ext,
+            conf_last: self.src_conf_last,
+            keep_dont_care: self.src_keep_dont_care,
+            label_attr: &self.src_label_attr,
+        };
+
+        let mut anns = self.src_fmt.parse(path, opts)
+            .expect("failed to parse the annotations");
+
+        self.labels.apply(&mut anns);
+        self.filters.apply(&mut anns);
+
+        if self.sort {
+            anns.sort_by_img_id();
         }
-        .expect("failed to parse the annotations");
 
         let save_path = expand_user(self.dst_path);
 
-        match self.dst_fmt {
-            DstAnnFmt::Coco => anns.save_coco(save_path),
-            DstAnnFmt::Cvat => anns.save_cvat(save_path),
-            DstAnnFmt::Imagenet => anns.save_imagenet(save_path),
-            DstAnnFmt::Labelme => anns.save_labelme(save_path),
-            DstAnnFmt::OpenImage => anns.save_openimage(save_path),
-            DstAnnFmt::Yolo => anns.save_yolo(save_path, self.dst_conf_last),
-            DstAnnFmt::PascalVoc => anns.save_pascal_voc(save_path),
-            DstAnnFmt::Vit => todo!("not yet implemented"),
-        }
+        self.dst_fmt.save(&anns, save_path, self.dst.opts())
         .expect("failed to save the annotations");
     }
 }
\ No newline at end of file
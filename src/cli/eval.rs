@@ -1,8 +1,64 @@
-// use crate::path::expand_user;
+use crate::{
+    evaluation::IoUKind,
+    evaluators::{
+        coco::{COCOEval, AREA_ALL, MAX_DETS},
+        params::{AreaRange, EvalParams},
+        cache::EvalCache,
+        openimages::VerifiedLabels,
+    },
+    path::expand_user,
+};
 
-use super::SrcAnnFmt; 
+use super::{labels::EvalLabelArgs, SrcAnnFmt, SrcOpts, ReportFmt};
 
-use clap::Args;
+use std::{fs, path::Path, time::Instant};
+
+use clap::{Args, ValueEnum};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub(super) enum IoUKindArg {
+    Iou, Giou, Diou, Ciou, Ioa
+}
+
+impl From<IoUKindArg> for IoUKind {
+    fn from(arg: IoUKindArg) -> Self {
+        match arg {
+            IoUKindArg::Iou => IoUKind::IoU,
+            IoUKindArg::Giou => IoUKind::GIoU,
+            IoUKindArg::Diou => IoUKind::DIoU,
+            IoUKindArg::Ciou => IoUKind::CIoU,
+            IoUKindArg::Ioa => IoUKind::IoA,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum ExportFmt {
+    Json, Csv
+}
+
+/// Parses a `NAME=MIN:MAX` area range.
+fn parse_area_range(arg: &str) -> Result<(String, f32, f32), String> {
+    let err = || format!("expected NAME=MIN:MAX, got `{arg}`");
+
+    let (name, range) = arg.split_once('=').ok_or_else(err)?;
+    let (min, max) = range.split_once(':').ok_or_else(err)?;
+
+    Ok((
+        name.to_string(), 
+        min.trim().parse().map_err(|_| err())?, 
+        max.trim().parse().map_err(|_| err())?,
+    ))
+}
+
+/// Parses a `LABEL=THRESHOLD` IoU threshold.
+fn parse_label_iou(arg: &str) -> Result<(String, f32), String> {
+    let err = || format!("expected LABEL=THRESHOLD, got `{arg}`");
+
+    let (label, thresh) = arg.split_once('=').ok_or_else(err)?;
+
+    Ok((label.to_string(), thresh.trim().parse().map_err(|_| err())?))
+}
 
 #[derive(Args)]
 pub(super) struct Eval {
@@ -32,10 +88,352 @@ pub(super) struct Eval {
 
     #[arg(long, help = "Whether or not the confidence of predicted annotations is stored in last position (YOLO only)")]
     dets_conf_last: bool,
+
+    #[arg(long, value_enum, default_value = "iou", help = "The overlap measure used to match predictions with ground truths")]
+    iou_kind: IoUKindArg,
+
+    #[command(flatten)]
+    labels: EvalLabelArgs,
+
+    #[arg(long, value_enum, default_value = "table", help = "The format of the evaluation report")]
+    report_fmt: ReportFmt,
+
+    #[arg(long, help = "The file to write the evaluation report to instead of the standard output")]
+    report_path: Option<String>,
+
+    #[arg(long, default_value_t = 0.5, help = "The IoU threshold of precision-recall curves and operating points")]
+    pr_iou: f32,
+
+    #[arg(long, help = "Report the F1-optimal operating points and the precision, recall and F1 score at this confidence threshold")]
+    conf: Option<f32>,
+
+    #[arg(long, help = "The file to write the precision-recall curves to")]
+    pr_path: Option<String>,
+
+    #[arg(long, value_enum, default_value = "csv", help = "The format of the precision-recall curves")]
+    pr_fmt: ExportFmt,
+
+    #[arg(long, value_enum, help = "Output the confusion matrix in this format, at the `--conf` threshold or 0")]
+    confusion: Option<ReportFmt>,
+
+    #[arg(long, default_value_t = 0.5, help = "The IoU threshold of the confusion matrix")]
+    confusion_iou: f32,
+
+    #[arg(long, help = "The file to write the confusion matrix to instead of the standard output")]
+    confusion_path: Option<String>,
+
+    #[arg(long, default_value_t = 0.5, help = "The IoU threshold of the per-image breakdown, at the `--conf` threshold or 0")]
+    images_iou: f32,
+
+    #[arg(long, help = "The file to write the per-image true positives, false positives and false negatives to")]
+    images_path: Option<String>,
+
+    #[arg(long, value_enum, default_value = "csv", help = "The format of the per-image breakdown, only JSON includes the matching details")]
+    images_fmt: ExportFmt,
+
+    #[arg(long, help = "The CSV file to write the per-image matching details to")]
+    matches_path: Option<String>,
+
+    #[arg(long, help = "Report the 10 images with the most errors, at the `--images-iou` threshold")]
+    worst_images: bool,
+
+    #[arg(long, value_enum, help = "Output the TIDE error decomposition in this format")]
+    tide: Option<ReportFmt>,
+
+    #[arg(long, default_value_t = 0.5, help = "The IoU threshold of true positives in the TIDE decomposition")]
+    tide_iou: f32,
+
+    #[arg(long, default_value_t = 0.1, help = "The IoU threshold of background errors in the TIDE decomposition")]
+    tide_bg_iou: f32,
+
+    #[arg(long, help = "The file to write the TIDE decomposition to instead of the standard output")]
+    tide_path: Option<String>,
+
+    #[arg(long, value_enum, help = "Output the OpenImages challenge metric in this format")]
+    openimages: Option<ReportFmt>,
+
+    #[arg(long, default_value_t = 0.5, help = "The IoU threshold of the OpenImages challenge metric")]
+    openimages_iou: f32,
+
+    #[arg(long, help = "An OpenImages image-level labels CSV file, predictions are only evaluated on images where their label is verified or annotated")]
+    verified_labels: Option<String>,
+
+    #[arg(long, help = "The file to write the OpenImages challenge metric to instead of the standard output")]
+    openimages_path: Option<String>,
+
+    #[arg(long, help = "A file caching the per-image matching, only images whose annotations changed are matched again")]
+    cache: Option<String>,
+
+    #[arg(long, help = "A JSON file of custom evaluation parameters, overridden by the parameter options")]
+    params_file: Option<String>,
+
+    #[arg(long, value_delimiter = ',', help = "The comma-separated IoU thresholds to average metrics over")]
+    iou_thresholds: Vec<f32>,
+
+    #[arg(long, value_parser = parse_label_iou, help = "Evaluate a label at a single IoU threshold, as LABEL=THRESHOLD (repeatable)")]
+    label_iou: Vec<(String, f32)>,
+
+    #[arg(long, value_parser = parse_area_range, help = "A named range of pixel areas, as NAME=MIN:MAX (repeatable)")]
+    area_range: Vec<(String, f32, f32)>,
+
+    #[arg(long, value_parser = parse_area_range, help = "A named range of fractions of the image area, as NAME=MIN:MAX (repeatable)")]
+    rel_area_range: Vec<(String, f32, f32)>,
+
+    #[arg(long, value_delimiter = ',', help = "The comma-separated maximum numbers of detections per image and label")]
+    max_dets: Vec<usize>,
 }
 
 impl Eval {
+    /// The custom evaluation parameters, `None` if none is given.
+    fn params(&self) -> Option<EvalParams> {
+        let is_custom = self.params_file.is_some()
+            || !self.iou_thresholds.is_empty()
+            || !self.label_iou.is_empty()
+            || !self.area_range.is_empty()
+            || !self.rel_area_range.is_empty()
+            || !self.max_dets.is_empty();
+
+        if !is_custom {
+            return None
+        }
+
+        let mut params = match &self.params_file {
+            Some(path) => EvalParams::from_file(expand_user(path))
+                .expect("failed to read the evaluation parameters"),
+            None => EvalParams::default(),
+        };
+
+        if !self.iou_thresholds.is_empty() {
+            params.iou_thresholds.clone_from(&self.iou_thresholds);
+        }
+
+        params.label_iou_thresholds.extend(self.label_iou.iter().cloned());
+
+        if !self.area_range.is_empty() || !self.rel_area_range.is_empty() {
+            let absolute = self.area_range.iter()
+                .map(|(name, min, max)| AreaRange::new(name, *min, *max));
+            let relative = self.rel_area_range.iter()
+                .map(|(name, min, max)| AreaRange::relative(name, *min, *max));
+
+            params.area_ranges = absolute.chain(relative).collect();
+        }
+
+        if !self.max_dets.is_empty() {
+            params.max_dets.clone_from(&self.max_dets);
+        }
+
+        assert!(params.is_valid(), "invalid evaluation parameters");
+
+        Some(params)
+    }
+
     pub(super) fn run(self) {
-        todo!("not implemented yet")
+        let params = self.params();
+
+        let imgs_path = self.imgs_path.map(expand_user);
+
+        let gts_opts = SrcOpts {
+            imgs_path: imgs_path.clone(),
+            img_ext: &self.gts_img_ext,
+            conf_last: self.gts_conf_last,
+            keep_dont_care: true,
+            label_attr: "label",
+        };
+
+        let dets_opts = SrcOpts {
+            imgs_path,
+            img_ext: &self.dets_img_ext,
+            conf_last: self.dets_conf_last,
+            keep_dont_care: false,
+            label_attr: "label",
+        };
+
+        let mut gts = self.gts_fmt.parse(expand_user(self.gts_path), gts_opts)
+            .expect("failed to parse the ground truth annotations");
+
+        let mut dets = self.dets_fmt.parse(expand_user(self.dets_path), dets_opts)
+            .expect("failed to parse the predicted annotations");
+
+        let hierarchy = self.labels.apply_gts(&mut gts);
+        self.labels.apply_dets(&mut dets);
+
+        let time = Instant::now();
+
+        let evaluator = COCOEval::new(&gts, &dets)
+            .with_iou_kind(self.iou_kind.into())
+            .with_ignore_labels(self.labels.ignore_labels);
+
+        let curves = (self.conf.is_some() || self.pr_path.is_some()).then(|| {
+            let curves = evaluator.pr_curves(self.pr_iou, MAX_DETS[MAX_DETS.len() - 1], AREA_ALL);
+
+            match self.conf {
+                Some(conf) => curves.with_conf(conf),
+                None => curves,
+            }
+        });
+
+        let breakdown = (self.worst_images || self.images_path.is_some() || self.matches_path.is_some())
+            .then(|| evaluator.img_breakdown(self.images_iou, self.conf.unwrap_or(0.0)));
+
+        // Operating points and worst images are only part of table reports.
+        let mut extras = String::new();
+
+        if let (Some(_), Some(curves)) = (self.conf, &curves) {
+            extras += &format!("\n{curves}");
+        }
+
+        if let (true, Some(breakdown)) = (self.worst_images, &breakdown) {
+            extras += &format!("\n{breakdown}");
+        }
+
+        let cache_path = self.cache.map(expand_user);
+
+        // An unreadable cache is only a missed opportunity to skip work.
+        let mut cache = cache_path.as_ref().map(|path| match Path::new(path).exists() {
+            true => EvalCache::from_file(path).unwrap_or_else(|_| {
+                eprintln!("Ignoring the invalid evaluation cache {path:?}.");
+                EvalCache::new()
+            }),
+            false => EvalCache::new(),
+        });
+
+        let report = match params {
+            Some(params) => {
+                let custom = match &mut cache {
+                    Some(cache) => evaluator.eval_params_cached(&params, cache),
+                    None => evaluator.eval_params(&params),
+                };
+
+                match self.report_fmt {
+                    ReportFmt::Table => format!("{custom}{extras}"),
+                    ReportFmt::Json => serde_json::to_string_pretty(&custom)
+                        .expect("failed to serialize the evaluation"),
+                    ReportFmt::Csv => custom.to_csv(),
+                }
+            },
+            None => {
+                let summary = match &mut cache {
+                    Some(cache) => evaluator.eval_all_cached(cache),
+                    None => evaluator.eval_all(),
+                };
+
+                match self.report_fmt {
+                    ReportFmt::Table => format!("{summary}{extras}"),
+                    ReportFmt::Json => serde_json::to_string_pretty(&summary)
+                        .expect("failed to serialize the evaluation"),
+                    ReportFmt::Csv => summary.to_csv(),
+                }
+            },
+        };
+
+        // Keep the standard output parsable for machine-readable reports.
+        let mut eval_msg = format!("Evaluated {} images in {:#?}.", gts.len(), time.elapsed());
+
+        if let (Some(mut cache), Some(path)) = (cache, cache_path) {
+            eval_msg += &format!(" Reused {} of {} cached image matchings.", cache.hits(), cache.hits() + cache.misses());
+
+            cache.prune();
+            cache.save(path).expect("failed to write the evaluation cache");
+        }
+
+        match (self.report_fmt, &self.report_path) {
+            (ReportFmt::Table, None) => println!("{eval_msg}\n"),
+            _ => eprintln!("{eval_msg}"),
+        }
+
+        match self.report_path {
+            Some(report_path) => fs::write(expand_user(report_path), report)
+                .expect("failed to write the evaluation report"),
+            None => print!("{report}"),
+        }
+
+        if let (Some(pr_path), Some(curves)) = (self.pr_path, &curves) {
+            let curves = match self.pr_fmt {
+                ExportFmt::Json => serde_json::to_string_pretty(&curves)
+                    .expect("failed to serialize the precision-recall curves"),
+                ExportFmt::Csv => curves.to_csv(),
+            };
+
+            fs::write(expand_user(pr_path), curves)
+                .expect("failed to write the precision-recall curves");
+        }
+
+        if let (Some(images_path), Some(breakdown)) = (self.images_path, &breakdown) {
+            let images = match self.images_fmt {
+                ExportFmt::Json => serde_json::to_string_pretty(&breakdown)
+                    .expect("failed to serialize the per-image breakdown"),
+                ExportFmt::Csv => breakdown.to_csv(),
+            };
+
+            fs::write(expand_user(images_path), images)
+                .expect("failed to write the per-image breakdown");
+        }
+
+        if let (Some(matches_path), Some(breakdown)) = (self.matches_path, &breakdown) {
+            fs::write(expand_user(matches_path), breakdown.matches_to_csv())
+                .expect("failed to write the matching details");
+        }
+
+        if let Some(fmt) = self.confusion {
+            let matrix = evaluator.confusion_matrix(self.confusion_iou, self.conf.unwrap_or(0.0));
+
+            let report = match fmt {
+                ReportFmt::Table => matrix.to_string(),
+                ReportFmt::Json => serde_json::to_string_pretty(&matrix)
+                    .expect("failed to serialize the confusion matrix"),
+                ReportFmt::Csv => matrix.to_csv(),
+            };
+
+            match self.confusion_path {
+                Some(path) => fs::write(expand_user(path), report)
+                    .expect("failed to write the confusion matrix"),
+                None => print!("\n{report}"),
+            }
+        }
+
+        if let Some(fmt) = self.tide {
+            let tide = evaluator.tide(self.tide_iou, self.tide_bg_iou);
+
+            let report = match fmt {
+                ReportFmt::Table => tide.to_string(),
+                ReportFmt::Json => serde_json::to_string_pretty(&tide)
+                    .expect("failed to serialize the TIDE decomposition"),
+                ReportFmt::Csv => tide.to_csv(),
+            };
+
+            match self.tide_path {
+                Some(path) => fs::write(expand_user(path), report)
+                    .expect("failed to write the TIDE decomposition"),
+                None => print!("\n{report}"),
+            }
+        }
+
+        if let Some(fmt) = self.openimages {
+            let verified = self.verified_labels.map(|path| {
+                let mut verified = VerifiedLabels::from_openimages(expand_user(path))
+                    .expect("failed to read the verified labels");
+
+                if let Some(hierarchy) = &hierarchy {
+                    verified.expand_labels(hierarchy);
+                }
+
+                verified
+            });
+
+            let oid = evaluator.openimages(verified.as_ref(), self.openimages_iou);
+
+            let report = match fmt {
+                ReportFmt::Table => oid.to_string(),
+                ReportFmt::Json => serde_json::to_string_pretty(&oid)
+                    .expect("failed to serialize the OpenImages metric"),
+                ReportFmt::Csv => oid.to_csv(),
+            };
+
+            match self.openimages_path {
+                Some(path) => fs::write(expand_user(path), report)
+                    .expect("failed to write the OpenImages metric"),
+                None => print!("\n{report}"),
+            }
+        }
     }
 }
\ No newline at end of file
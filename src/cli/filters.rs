@@ -0,0 +1,92 @@
+use crate::annotationset::AnnSet;
+
+use clap::{Args, ValueEnum};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum KindArg {
+    Detections, GroundTruths
+}
+
+/// Bounding box filters, applied in the order: kind, confidence, area,
+/// relative area, size, aspect ratio, top-k.
+#[derive(Args)]
+pub(super) struct FilterArgs {
+    #[arg(long, value_enum, help = "Only keep the predictions or the ground truths")]
+    only: Option<KindArg>,
+
+    #[arg(long, help = "Drop the predictions with a confidence below this threshold")]
+    min_conf: Option<f32>,
+
+    #[arg(long, default_value_t = 0.0, help = "The minimum box area in pixels")]
+    min_area: f32,
+
+    #[arg(long, default_value_t = f32::INFINITY, help = "The maximum box area in pixels")]
+    max_area: f32,
+
+    #[arg(long, default_value_t = 0.0, help = "The minimum box area relative to the image area")]
+    min_rel_area: f32,
+
+    #[arg(long, default_value_t = f32::INFINITY, help = "The maximum box area relative to the image area")]
+    max_rel_area: f32,
+
+    #[arg(long, default_value_t = 0.0, help = "The minimum box width in pixels")]
+    min_width: f32,
+
+    #[arg(long, default_value_t = f32::INFINITY, help = "The maximum box width in pixels")]
+    max_width: f32,
+
+    #[arg(long, default_value_t = 0.0, help = "The minimum box height in pixels")]
+    min_height: f32,
+
+    #[arg(long, default_value_t = f32::INFINITY, help = "The maximum box height in pixels")]
+    max_height: f32,
+
+    #[arg(long, default_value_t = 0.0, help = "The minimum box width over height ratio")]
+    min_aspect_ratio: f32,
+
+    #[arg(long, default_value_t = f32::INFINITY, help = "The maximum box width over height ratio")]
+    max_aspect_ratio: f32,
+
+    #[arg(long, help = "Only keep the k most confident predictions of each image")]
+    top_k: Option<usize>,
+
+    #[arg(long, help = "Apply `--top-k` to each label separately")]
+    top_k_per_label: bool,
+}
+
+impl FilterArgs {
+    pub(super) fn apply(&self, annset: &mut AnnSet) {
+        match self.only {
+            Some(KindArg::Detections) => annset.drop_ground_truths(),
+            Some(KindArg::GroundTruths) => annset.drop_detections(),
+            None => (),
+        }
+
+        if let Some(min_conf) = self.min_conf {
+            annset.filter_conf(min_conf);
+        }
+
+        if (self.min_area, self.max_area) != (0.0, f32::INFINITY) {
+            annset.filter_area((self.min_area, self.max_area));
+        }
+
+        if (self.min_rel_area, self.max_rel_area) != (0.0, f32::INFINITY) {
+            annset.filter_rel_area((self.min_rel_area, self.max_rel_area));
+        }
+
+        let (width, height) = ((self.min_width, self.max_width), (self.min_height, self.max_height));
+        if (width, height) != ((0.0, f32::INFINITY), (0.0, f32::INFINITY)) {
+            annset.filter_size(width, height);
+        }
+
+        if (self.min_aspect_ratio, self.max_aspect_ratio) != (0.0, f32::INFINITY) {
+            annset.filter_aspect_ratio((self.min_aspect_ratio, self.max_aspect_ratio));
+        }
+
+        match (self.top_k, self.top_k_per_label) {
+            (Some(k), false) => annset.keep_top_k(k),
+            (Some(k), true) => annset.keep_top_k_per_label(k),
+            (None, _) => (),
+        }
+    }
+}
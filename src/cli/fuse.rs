@@ -0,0 +1,84 @@
+use crate::{
+    annotationset::AnnSet,
+    path::expand_user,
+};
+
+use super::{SrcAnnFmt, SrcOpts, DstAnnFmt, DstArgs};
+
+use clap::Args;
+
+#[derive(Args)]
+pub(super) struct Fuse {
+    #[arg(help = "The format of the input predictions")]
+    src_fmt: SrcAnnFmt,
+
+    #[arg(help = "The format of the output annotations")]
+    dst_fmt: DstAnnFmt,
+
+    #[arg(help = "The file or directory path of the output annotations")]
+    dst_path: String,
+
+    #[arg(num_args = 2.., required = true, help = "The file or directory paths of the predictions of each model")]
+    src_paths: Vec<String>,
+
+    #[arg(long, help = "The image directory of the input predictions")]
+    imgs_path: Option<String>,
+
+    #[arg(long, default_value = "jpg", help = "The image extension of input predictions (YOLO and KITTI only)")]
+    src_img_ext: String,
+
+    #[arg(long, help = "Whether or not the confidence of input predictions is stored in last position (YOLO only)")]
+    src_conf_last: bool,
+
+    #[arg(long, value_delimiter = ',', help = "The comma-separated weight of each model, defaults to equal weights")]
+    weights: Vec<f32>,
+
+    #[arg(long, default_value_t = 0.55, help = "The IoU above which predictions are fused")]
+    iou_thresh: f32,
+
+    #[arg(long, default_value_t = 0.0, help = "The confidence below which predictions are discarded")]
+    skip_thresh: f32,
+
+    #[command(flatten)]
+    dst: DstArgs,
+}
+
+impl Fuse {
+    pub(super) fn run(self) {
+        assert!(self.weights.iter().all(|&w| w >= 0.0), "weights should not be negative");
+
+        let sets: Vec<AnnSet> = self.src_paths.iter()
+            .map(|path| {
+                let opts = SrcOpts {
+                    imgs_path: self.imgs_path.as_ref().map(expand_user),
+                    img_ext: &self.src_img_ext,
+                    conf_last: self.src_conf_last,
+                    keep_dont_care: false,
+                    label_attr: "label",
+                };
+
+                self.src_fmt.parse(expand_user(path), opts)
+                    .expect("failed to parse the predictions")
+            })
+            .collect();
+
+        let weights = if self.weights.is_empty() { 
+            vec![1.0; sets.len()] 
+        } else { 
+            assert_eq!(self.weights.len(), sets.len(), "there should be one weight per model");
+            self.weights
+        };
+
+        let sets: Vec<&AnnSet> = sets.iter().collect();
+        let fused = AnnSet::weighted_boxes_fusion(&sets, &weights, self.iou_thresh, self.skip_thresh);
+
+        println!("Fused {} predictions of {} models into {} boxes.", 
+            sets.iter().map(|s| s.nb_bboxes()).sum::<usize>(), 
+            sets.len(), 
+            fused.nb_bboxes(),
+        );
+
+        self.dst_fmt.save(&fused, expand_user(self.dst_path), self.dst.opts())
+            .expect("failed to save the annotations");
+    }
+}
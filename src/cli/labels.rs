@@ -0,0 +1,145 @@
+use crate::{
+    annotationset::AnnSet,
+    labels::{LabelCase, LabelHierarchy, LabelMap, read_label_map},
+    path::expand_user,
+};
+
+use super::{SrcArgs, DstAnnFmt, DstArgs};
+
+use std::collections::HashSet;
+
+use clap::{Args, ValueEnum};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum CaseArg {
+    Lower, Upper
+}
+
+/// Label operations, applied in the order: case normalisation, 
+/// renaming, dropping, keeping.
+#[derive(Args)]
+pub(super) struct LabelArgs {
+    #[arg(long, value_enum, help = "Convert labels to lower or upper case")]
+    label_case: Option<CaseArg>,
+
+    #[arg(long, help = "A CSV file mapping old labels to new labels, one `old,new` pair per line")]
+    label_map: Option<String>,
+
+    #[arg(long, value_name = "OLD=NEW", help = "Rename or merge a label, can be repeated")]
+    rename: Vec<String>,
+
+    #[arg(long, value_name = "LABEL", help = "Drop the boxes with this label, can be repeated")]
+    drop_label: Vec<String>,
+
+    #[arg(long, value_name = "LABEL", help = "Only keep the boxes with this label, can be repeated")]
+    keep_label: Vec<String>,
+}
+
+impl LabelArgs {
+    pub(super) fn apply(&self, annset: &mut AnnSet) {
+        if let Some(case) = self.label_case {
+            annset.normalize_label_case(match case {
+                CaseArg::Lower => LabelCase::Lower,
+                CaseArg::Upper => LabelCase::Upper,
+            });
+        }
+
+        let mut mapping = match &self.label_map {
+            Some(p) => read_label_map(expand_user(p))
+                .expect("failed to read the label map"),
+            None => LabelMap::new(),
+        };
+
+        for rename in &self.rename {
+            let (old, new) = rename.split_once('=')
+                .expect("label renames should be formatted as `old=new`");
+
+            mapping.insert(String::from(old), String::from(new));
+        }
+
+        if !mapping.is_empty() {
+            annset.map_labels(&mapping);
+        }
+
+        if !self.drop_label.is_empty() {
+            annset.drop_labels(&self.drop_label.iter().cloned().collect::<HashSet<_>>());
+        }
+
+        if !self.keep_label.is_empty() {
+            annset.keep_labels(&self.keep_label.iter().cloned().collect::<HashSet<_>>());
+        }
+    }
+}
+
+#[derive(Args)]
+pub(super) struct MapLabels {
+    #[command(flatten)]
+    src: SrcArgs,
+
+    #[arg(help = "The format of the output annotations")]
+    dst_fmt: DstAnnFmt,
+
+    #[arg(help = "The file or directory path of the output annotations")]
+    dst_path: String,
+
+    #[command(flatten)]
+    labels: LabelArgs,
+
+    #[command(flatten)]
+    dst: DstArgs,
+}
+
+impl MapLabels {
+    pub(super) fn run(self) {
+        let mut annset = self.src.parse();
+        let nb_boxes = annset.nb_bboxes();
+
+        self.labels.apply(&mut annset);
+
+        println!("Kept {} of {} boxes.", annset.nb_bboxes(), nb_boxes);
+
+        self.dst_fmt.save(&annset, expand_user(self.dst_path), self.dst.opts())
+            .expect("failed to save the annotations");
+    }
+}
+
+/// Label handling of evaluations. Ground truths are expanded to the 
+/// ancestors of their labels in the hierarchy, predictions are left 
+/// unchanged so that a prediction of a parent label matches its children.
+#[derive(Args)]
+pub(super) struct EvalLabelArgs {
+    #[arg(long, value_delimiter = ',', default_value = "DontCare", help = "The comma-separated labels of ground truth ignore regions")]
+    pub(super) ignore_labels: Vec<String>,
+
+    #[arg(long, conflicts_with = "hierarchy", help = "Evaluate regardless of labels (class-agnostic), ignore labels excepted")]
+    agnostic: bool,
+
+    #[arg(long, help = "An OpenImages class hierarchy JSON file to expand ground truth labels to their ancestors")]
+    hierarchy: Option<String>,
+}
+
+impl EvalLabelArgs {
+    /// Returns the label hierarchy, if any, to expand other labels with.
+    pub(super) fn apply_gts(&self, gts: &mut AnnSet) -> Option<LabelHierarchy> {
+        if self.agnostic {
+            gts.make_agnostic(&self.ignore_labels.iter().cloned().collect());
+        }
+
+        let hierarchy = self.hierarchy.as_ref().map(|path| {
+            LabelHierarchy::from_openimages(expand_user(path))
+                .expect("failed to read the label hierarchy")
+        });
+
+        if let Some(hierarchy) = &hierarchy {
+            gts.expand_labels(hierarchy);
+        }
+
+        hierarchy
+    }
+
+    pub(super) fn apply_dets(&self, dets: &mut AnnSet) {
+        if self.agnostic {
+            dets.make_agnostic(&self.ignore_labels.iter().cloned().collect());
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{
+    path::expand_user,
+    postprocess::SoftNmsDecay,
+};
+
+use super::{SrcArgs, DstAnnFmt, DstArgs};
+
+use clap::{Args, ValueEnum};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum DecayArg {
+    Linear, Gaussian
+}
+
+#[derive(Args)]
+pub(super) struct Nms {
+    #[command(flatten)]
+    src: SrcArgs,
+
+    #[arg(help = "The format of the output annotations")]
+    dst_fmt: DstAnnFmt,
+
+    #[arg(help = "The file or directory path of the output annotations")]
+    dst_path: String,
+
+    #[arg(long, default_value_t = 0.5, help = "The IoU above which detections are suppressed")]
+    iou_thresh: f32,
+
+    #[arg(long, help = "Let detections with different labels suppress each other")]
+    agnostic: bool,
+
+    #[arg(long, value_enum, help = "Decay the confidence of overlapping detections instead of removing them (Soft-NMS)")]
+    soft: Option<DecayArg>,
+
+    #[arg(long, default_value_t = 0.5, help = "The sigma of the gaussian decay")]
+    sigma: f32,
+
+    #[arg(long, default_value_t = 0.001, help = "The confidence below which decayed detections are removed")]
+    score_thresh: f32,
+
+    #[command(flatten)]
+    dst: DstArgs,
+}
+
+impl Nms {
+    pub(super) fn run(self) {
+        let mut annset = self.src.parse();
+        let nb_boxes = annset.nb_bboxes();
+
+        match self.soft {
+            None => annset.nms(self.iou_thresh, self.agnostic),
+            Some(decay) => {
+                let decay = match decay {
+                    DecayArg::Linear => SoftNmsDecay::Linear,
+                    DecayArg::Gaussian => SoftNmsDecay::Gaussian { sigma: self.sigma },
+                };
+
+                annset.soft_nms(decay, self.iou_thresh, self.score_thresh, self.agnostic);
+            },
+        }
+
+        println!("Kept {} of {} boxes.", annset.nb_bboxes(), nb_boxes);
+
+        self.dst_fmt.save(&annset, expand_user(self.dst_path), self.dst.opts())
+            .expect("failed to save the annotations");
+    }
+}
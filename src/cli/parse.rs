@@ -1,84 +1,50 @@
-use crate::{annotationset::AnnSet, path::expand_user};
+use crate::path::expand_user;
 
-use super::SrcAnnFmt;
+use super::{SrcArgs, ReportFmt};
 
-use std::time::Instant;
+use std::{fs, time::Instant};
 
 use clap::Args;
 
 #[derive(Args)]
 pub(super) struct Parse {
-    #[arg(help = "The format of the annotations")]
-    format: SrcAnnFmt,
+    #[command(flatten)]
+    src: SrcArgs,
 
-    #[arg(help = "The file or directory path of the annotations")]
-    path: String, 
+    #[arg(long, value_enum, default_value = "table", help = "The format of the statistics report")]
+    report_fmt: ReportFmt,
 
-    #[arg(long, help = "The image directory of the annotations")]
-    imgs_path: Option<String>,
-
-    #[arg(long, default_value = "jpg", help = "The image extension (YOLO only)")]
-    img_ext: String,
-
-    #[arg(long, help = "Whether or not the confidence is stored in last position (YOLO only)")]
-    conf_last: bool,
+    #[arg(long, help = "The file to write the statistics report to instead of the standard output")]
+    report_path: Option<String>,
 }
 
 impl Parse {
     pub(super) fn run(self) {
-        let path = expand_user(self.path);
-
         let time = Instant::now();
 
-        let annset = match self.format {
-            SrcAnnFmt::Coco => {
-                AnnSet::parse_coco(path)
-                    .expect("failed to parse")
-            }, 
-            
-            SrcAnnFmt::Cvat => {
-                AnnSet::parse_cvat(path)
-                    .expect("failed to parse")
-            },
-
-            SrcAnnFmt::OpenImage => {
-                let imgs_path = self.imgs_path
-                    .expect("parsing OpenImage requires `imgs_path`");
-                let imgs_path = expand_user(imgs_path);
-
-                AnnSet::parse_openimage(path, imgs_path)
-                    .expect("failed to parse")
-            },
-
-            SrcAnnFmt::Labelme => {
-                AnnSet::parse_labelme(path)
-                    .expect("failed to parse")
-            },
+        let annset = self.src.parse();
 
-            SrcAnnFmt::PascalVoc => {
-                AnnSet::parse_pascal_voc(path)
-                    .expect("failed to parse")
-            },
+        // Keep the standard output parsable for machine-readable reports.
+        let parsed_msg = format!("Parsed {} annotations in {:#?}.", annset.len(), time.elapsed());
 
-            SrcAnnFmt::Imagenet => {
-                AnnSet::parse_imagenet(path)
-                    .expect("failed to parse")
-            }
+        match (self.report_fmt, &self.report_path) {
+            (ReportFmt::Table, None) => println!("{parsed_msg}\n"),
+            _ => eprintln!("{parsed_msg}"),
+        }
 
-            SrcAnnFmt::Yolo => {
-                let imgs_path = self.imgs_path
-                    .expect("parsing OpenImage requires `imgs_path`");
-                let imgs_path = expand_user(imgs_path);
+        let stats = annset.stats();
 
-                AnnSet::parse_yolo(
-                    path, 
-                    imgs_path, 
-                    self.conf_last, 
-                    &self.img_ext,
-                ).expect("failed to parse the annotations")
-            }
+        let report = match self.report_fmt {
+            ReportFmt::Table => stats.to_string(),
+            ReportFmt::Json => serde_json::to_string_pretty(&stats)
+                .expect("failed to serialize the statistics"),
+            ReportFmt::Csv => stats.to_csv(),
         };
 
-        println!("Parsed {} annotations in {:#?}.", annset.len(), time.elapsed());
+        match self.report_path {
+            Some(report_path) => fs::write(expand_user(report_path), report)
+                .expect("failed to write the statistics report"),
+            None => print!("{report}"),
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,91 @@
+use crate::{
+    annotationset::AnnSet,
+    path::expand_user,
+};
+
+use super::{SrcArgs, DstAnnFmt, DstArgs};
+
+use std::{fs, path::Path};
+
+use clap::Args;
+
+/// Parses a `NAME=RATIO` split.
+fn parse_split(arg: &str) -> Result<(String, f32), String> {
+    let err = || format!("expected NAME=RATIO, got `{arg}`");
+
+    let (name, ratio) = arg.split_once('=').ok_or_else(err)?;
+
+    Ok((name.to_string(), ratio.trim().parse().map_err(|_| err())?))
+}
+
+#[derive(Args)]
+pub(super) struct Split {
+    #[command(flatten)]
+    src: SrcArgs,
+
+    #[arg(help = "The format of the output annotations")]
+    dst_fmt: DstAnnFmt,
+
+    #[arg(help = "The directory where to save the splits")]
+    dst_path: String,
+
+    #[arg(
+        long, 
+        value_name = "NAME=RATIO", 
+        value_parser = parse_split,
+        default_values = ["train=0.8", "val=0.2"], 
+        help = "A split name and its ratio, can be repeated"
+    )]
+    split: Vec<(String, f32)>,
+
+    #[arg(long, help = "Generate k (train, val) folds instead of the named splits")]
+    kfold: Option<usize>,
+
+    #[arg(long, default_value_t = 0, help = "The seed of the random partitioning")]
+    seed: u64,
+
+    #[arg(long, help = "Preserve the label distribution in each split")]
+    stratify: bool,
+
+    #[command(flatten)]
+    dst: DstArgs,
+}
+
+impl Split {
+    /// Saves the split to `<dir>/<name>.<ext>` for single-file formats,
+    /// else to the `<dir>/<name>` directory.
+    fn save(&self, annset: &AnnSet, dir: &Path, name: &str) {
+        let path = match self.dst_fmt.file_ext() {
+            Some(ext) => dir.join(format!("{name}.{ext}")),
+            None => dir.join(name),
+        };
+
+        let parent = if self.dst_fmt.file_ext().is_some() { dir } else { &path };
+        fs::create_dir_all(parent).expect("failed to create the output directory");
+
+        self.dst_fmt.save(annset, path, self.dst.opts())
+            .expect("failed to save the annotations");
+    }
+
+    pub(super) fn run(self) {
+        let annset = self.src.parse();
+        let dst_path = expand_user(&self.dst_path);
+
+        if let Some(k) = self.kfold {
+            for (i, (train, val)) in annset.kfold(k, self.seed, self.stratify).iter().enumerate() {
+                let fold_path = dst_path.join(format!("fold_{i}"));
+                self.save(train, &fold_path, "train");
+                self.save(val, &fold_path, "val");
+
+                println!("Fold {i}: {} train and {} val annotations.", train.len(), val.len());
+            }
+
+            return
+        }
+
+        for (name, split) in annset.split(&self.split, self.seed, self.stratify) {
+            self.save(&split, &dst_path, &name);
+            println!("Split {name}: {} annotations.", split.len());
+        }
+    }
+}
@@ -1,10 +1,18 @@
+pub mod cityscapes;
 pub mod coco;
+pub mod createml;
 pub mod cvat;
 pub mod folder;
 pub mod imagenet;
+pub mod kitti;
 pub mod labelme;
+pub mod labelstudio;
 pub mod openimage;
+pub mod supervisely;
+pub mod tfrecord;
 pub mod txt;
+pub mod via;
+pub mod widerface;
 pub mod yolo;
 
 #[derive(Debug)]
@@ -0,0 +1,86 @@
+use crate::{
+    bbox::BBox,
+    annotation::Ann,
+    annotationset::AnnSet,
+    converters::ConvError,
+    serde_records::cityscapes::*,
+};
+
+use std::{path::Path, fs};
+
+use serde_json::to_string;
+
+impl TryFrom<&BBox> for CSObj {
+    type Error = ConvError;
+
+    fn try_from(bbox: &BBox) -> Result<Self, Self::Error> {
+        let (l, t, w, h) = bbox.ltwh();
+
+        let instance_id = bbox.attribute("instance_id")
+            .map(|id| id.parse::<i64>().map_err(|_| ConvError {}))
+            .transpose()?;
+
+        let bbox_vis = bbox.attribute("bbox_vis")
+            .map(|v| {
+                v.split_whitespace()
+                    .map(|c| c.parse::<f32>().map_err(|_| ConvError {}))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?;
+
+        Ok(CSObj { 
+            label: bbox.label.clone(),
+            instance_id,
+            bbox: vec![l, t, w, h],
+            bbox_vis,
+        })
+    }
+}
+
+impl TryFrom<&Ann> for CSAnn {
+    type Error = ConvError;
+
+    fn try_from(ann: &Ann) -> Result<Self, Self::Error> {
+        let img_size = ann.img_size.ok_or(ConvError {})?;
+
+        let objects = ann.bboxes.iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(CSAnn { img_width: img_size.width, img_height: img_size.height, objects })
+    }
+}
+
+impl Ann {
+    /// Serialize the annotation to a String of Cityscapes bounding box format.
+    pub fn to_cityscapes(&self) -> Result<String, ConvError> {
+        let ann: CSAnn = self.try_into()?;
+        to_string(&ann).map_err(|_| ConvError {})
+    }
+
+    /// Save the annotation to Cityscapes bounding box format.
+    /// 
+    /// The file is named after the image, e.g. `aachen_000000_000019_gtBbox.json`
+    /// for the image `aachen_000000_000019_leftImg8bit.png`.
+    pub fn save_cityscapes<P: AsRef<Path>>(&self, path: P) -> Result<(), ConvError> {
+        let stem = match self.img_id.strip_suffix(IMG_SUFFIX) {
+            Some(stem) => stem,
+            None => Path::new(&self.img_id)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .ok_or(ConvError {})?,
+        };
+
+        let path = path.as_ref().join(format!("{stem}{ANN_INFIX}.json"));
+        let contents = self.to_cityscapes()?;
+
+        fs::write(path, contents).map_err(|_| ConvError {})
+    }
+}
+
+impl AnnSet {
+    /// Save the annotations to Cityscapes bounding box format.
+    pub fn save_cityscapes<P: AsRef<Path>>(&self, path: P) -> Result<(), ConvError> {
+        self.save_all(|ann| ann.save_cityscapes(&path))
+    }
+}
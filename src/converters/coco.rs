@@ -3,6 +3,7 @@ use crate::{
     annotationset::AnnSet,
     converters::ConvError,
     serde_records::coco::*,
+    bbox::CROWD_ATTR,
 };
 
 use std::{
@@ -68,9 +69,10 @@ impl AnnSet {
 
                 let ann = COCOAnn {
                     cat_id: to_cat_id[bbox.label.as_str()],
-                    img_id: img_id,
+                    img_id,
                     bbox: coords, 
-                    conf: bbox.conf()
+                    conf: bbox.conf(),
+                    iscrowd: bbox.attribute(CROWD_ATTR).and_then(|v| v.parse().ok()),
                 };
 
                 anns.push(ann);
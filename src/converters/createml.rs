@@ -0,0 +1,57 @@
+use crate::{
+    bbox::BBox,
+    annotation::Ann,
+    annotationset::AnnSet,
+    converters::ConvError,
+    serde_records::createml::*,
+};
+
+use std::{
+    path::Path,
+    fs,
+    ffi::OsStr,
+};
+
+use serde_json::to_string;
+
+impl From<&BBox> for CMLObj {
+    fn from(bbox: &BBox) -> Self {
+        let (x, y, width, height) = bbox.xywh();
+
+        CMLObj {
+            label: bbox.label.clone(),
+            coordinates: CMLCoords { x, y, width, height },
+            confidence: bbox.conf(),
+        }
+    }
+}
+
+impl From<&Ann> for CMLAnn {
+    fn from(ann: &Ann) -> Self {
+        let annotations = ann.bboxes.iter()
+            .map(Into::into)
+            .collect();
+
+        CMLAnn { image: ann.img_id.clone(), annotations }
+    }
+}
+
+impl AnnSet {
+    /// Save the annotations to CreateML JSON format.
+    pub fn save_createml<P: AsRef<Path>>(&self, path: P) -> Result<(), ConvError> {
+        if let Some(e) = path.as_ref().extension() {
+            if e != OsStr::new("json") {
+                return Err(ConvError {})
+            }
+        }
+
+        let cml_anns = self.iter()
+            .map(Into::into)
+            .collect::<Vec<CMLAnn>>();
+
+        let contents = to_string(&cml_anns)
+            .map_err(|_| ConvError {})?;
+
+        fs::write(path, contents).map_err(|_| ConvError {})
+    }
+}
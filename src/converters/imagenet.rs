@@ -1,5 +1,5 @@
 use crate::{
-    bbox::BBox,
+    bbox::{BBox, DIFFICULT_ATTR},
     annotation::Ann,
     annotationset::AnnSet,
     converters::ConvError,
@@ -15,7 +15,9 @@ impl From<&BBox> for InetObj {
         let (xmin, ymin, xmax, ymax) = bbox.ltrb();
         let bndbox = INetBndBox { xmin, ymin, xmax, ymax };
 
-        InetObj { name: bbox.label.clone(), bndbox }
+        let difficult = bbox.attribute(DIFFICULT_ATTR).and_then(|v| v.parse().ok());
+
+        InetObj { name: bbox.label.clone(), difficult, bndbox }
     }
 }
 
@@ -0,0 +1,84 @@
+use crate::{
+    bbox::BBox,
+    annotation::Ann,
+    annotationset::AnnSet,
+    converters::ConvError,
+    serde_records::kitti::*,
+};
+
+use std::{path::Path, fs};
+
+use itertools::Itertools;
+
+impl BBox {
+    fn to_kitti(&self) -> Result<String, ConvError> {
+        // KITTI is whitespace-separated, a label with spaces
+        // would not be parsable.
+        if self.label.is_empty() || self.label.contains(char::is_whitespace) {
+            return Err(ConvError {})
+        }
+
+        let (xmin, ymin, xmax, ymax) = self.ltrb();
+        let coords = [xmin, ymin, xmax, ymax].map(|c| format!("{c:.2}"));
+
+        let pre = PRE_BOX_ATTRS.iter()
+            .zip(PRE_BOX_DEFAULTS)
+            .map(|(name, default)| self.attribute(name).unwrap_or(default));
+
+        let post = POST_BOX_ATTRS.iter()
+            .zip(POST_BOX_DEFAULTS)
+            .map(|(name, default)| self.attribute(name).unwrap_or(default));
+
+        let mut line = std::iter::once(self.label.as_str())
+            .chain(pre)
+            .chain(coords.iter().map(String::as_str))
+            .chain(post)
+            .join(" ");
+
+        if let Some(conf) = self.conf() {
+            // The raw score is written back unless the confidence was 
+            // modified since parsing.
+            let score = self.attribute(SCORE_ATTR)
+                .filter(|s| s.parse().ok().map(score_to_conf) == Some(conf));
+
+            match score {
+                Some(score) => line.push_str(&format!(" {score}")),
+                None => line.push_str(&format!(" {conf}")),
+            }
+        }
+
+        Ok(line)
+    }
+}
+
+impl Ann {
+    /// Serialize the annotation to a String of KITTI label format.
+    /// 
+    /// Missing KITTI fields are filled with the values KITTI uses
+    /// for `DontCare` regions.
+    pub fn to_kitti(&self) -> Result<String, ConvError> {
+        let lines = self.bboxes.iter()
+            .map(BBox::to_kitti)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(lines.join("\n"))
+    }
+
+    /// Save the annotation to KITTI label format.
+    pub fn save_kitti<P: AsRef<Path>>(&self, path: P) -> Result<(), ConvError> {
+        let mut path = path.as_ref().to_path_buf();
+        path.push(&self.img_id);
+        path.set_extension("txt");
+
+        let contents = self.to_kitti()?;
+
+        fs::write(path, contents).map_err(|_| ConvError {})
+    }
+}
+
+impl AnnSet {
+    /// Save the annotations to KITTI label format.
+    pub fn save_kitti<P: AsRef<Path>>(&self, path: P) -> Result<(), ConvError> {
+        self.save_all(|ann| ann.save_kitti(&path))
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{
+    coords::abs_to_rel,
+    imgsize::ImgSize,
+    bbox::{BBox, BBoxFmt},
+    annotation::Ann,
+    annotationset::AnnSet,
+    converters::ConvError,
+    serde_records::labelstudio::*,
+};
+
+use std::{
+    path::Path,
+    fs,
+    ffi::OsStr,
+};
+
+use serde_json::{Map, Value, to_string, to_value};
+
+fn to_ls_result(bbox: &BBox, img_size: ImgSize) -> Result<LSResult, ConvError> {
+    let (x, y, width, height) = abs_to_rel(bbox.coords(BBoxFmt::LTWH), img_size);
+
+    let value = LSValue {
+        x: x * 100.0,
+        y: y * 100.0,
+        width: width * 100.0,
+        height: height * 100.0,
+        rotation: 0.0,
+        labels: vec![bbox.label.clone()],
+    };
+
+    Ok(LSResult {
+        kind: String::from(RECT_LABELS),
+        original_width: Some(img_size.width),
+        original_height: Some(img_size.height),
+        from_name: String::from("label"),
+        to_name: String::from("image"),
+        score: bbox.conf(),
+        value: to_value(value).map_err(|_| ConvError {})?,
+    })
+}
+
+fn to_ls_task(ann: &Ann, img_prefix: &str) -> Result<LSTask, ConvError> {
+    let img_size = ann.img_size.ok_or(ConvError {})?;
+
+    let mut data = Map::new();
+    data.insert(String::from("image"), Value::String(format!("{img_prefix}{}", ann.img_id)));
+
+    let mut gts = vec![];
+    let mut dets = vec![];
+
+    for bbox in &ann.bboxes {
+        let result = to_ls_result(bbox, img_size)?;
+
+        if bbox.is_detection() {
+            dets.push(result);
+        } else {
+            gts.push(result);
+        }
+    }
+
+    let annotations = if gts.is_empty() { vec![] } else {
+        vec![LSAnnotation { was_cancelled: false, result: gts }]
+    };
+
+    let predictions = if dets.is_empty() { vec![] } else {
+        vec![LSPrediction { model_version: None, score: None, result: dets }]
+    };
+
+    Ok(LSTask { data, annotations, predictions })
+}
+
+impl AnnSet {
+    /// Save the annotations to Label Studio JSON format.
+    /// 
+    /// Ground truths are written as task annotations and detections as
+    /// task predictions, so the file can be imported as pre-annotations.
+    /// The task image URL is the image identifier prefixed by `img_prefix`,
+    /// for instance `/data/local-files/?d=images/`.
+    pub fn save_labelstudio<P: AsRef<Path>>(&self, path: P, img_prefix: &str) -> Result<(), ConvError> {
+        if let Some(e) = path.as_ref().extension() {
+            if e != OsStr::new("json") {
+                return Err(ConvError {})
+            }
+        }
+
+        let tasks = self.iter()
+            .map(|ann| to_ls_task(ann, img_prefix))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let contents = to_string(&tasks)
+            .map_err(|_| ConvError {})?;
+
+        fs::write(path, contents).map_err(|_| ConvError {})
+    }
+}
@@ -37,12 +37,20 @@ impl AnnSet {
 
                 let conf = bbox.conf();
 
-                let line = OALine::new(
+                let mut line = OALine::new(
                     img_id.as_str(), label.as_str(), 
                     xmin, ymin, xmax, ymax,
                     conf
                 );
 
+                for (name, value) in &bbox.attributes {
+                    match value.as_str() {
+                        "1" => line.set_flag(name, true),
+                        "0" => line.set_flag(name, false),
+                        _ => {},
+                    }
+                }
+
                 writer.serialize(line)  
                     .map_err(|_| ConvError {})?;
             }
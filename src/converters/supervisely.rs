@@ -0,0 +1,106 @@
+use crate::{
+    bbox::BBox,
+    annotation::Ann,
+    annotationset::AnnSet,
+    converters::ConvError,
+    serde_records::supervisely::*,
+};
+
+use std::{
+    collections::BTreeSet,
+    path::Path,
+    fs,
+};
+
+use serde_json::{json, to_string};
+
+/// Class colors, cycled through when there are more classes.
+const PALETTE: [&str; 8] = [
+    "#E6194B", "#3CB44B", "#FFE119", "#4363D8",
+    "#F58231", "#911EB4", "#42D4F4", "#F032E6",
+];
+
+impl From<&BBox> for SlyObj {
+    fn from(bbox: &BBox) -> Self {
+        let (xmin, ymin, xmax, ymax) = bbox.ltrb();
+
+        let tags = bbox.conf()
+            .map(|c| vec![json!({ "name": "confidence", "value": c })])
+            .unwrap_or_default();
+
+        SlyObj {
+            class_title: bbox.label.clone(),
+            geometry_type: String::from(RECTANGLE),
+            points: SlyPoints { 
+                exterior: vec![vec![xmin, ymin], vec![xmax, ymax]], 
+                interior: vec![],
+            },
+            tags,
+        }
+    }
+}
+
+impl TryFrom<&Ann> for SlyAnn {
+    type Error = ConvError;
+
+    fn try_from(ann: &Ann) -> Result<Self, Self::Error> {
+        let img_size = ann.img_size.ok_or(ConvError {})?;
+        let size = SlySize { width: img_size.width, height: img_size.height };
+
+        let objects = ann.bboxes.iter()
+            .map(Into::into)
+            .collect();
+
+        Ok(SlyAnn { size, objects, tags: vec![] })
+    }
+}
+
+impl Ann {
+    /// Serialize the annotation to a String of Supervisely annotation format.
+    pub fn to_supervisely(&self) -> Result<String, ConvError> {
+        let ann: SlyAnn = self.try_into()?;
+        to_string(&ann).map_err(|_| ConvError {})
+    }
+
+    /// Save the annotation to Supervisely annotation format.
+    /// 
+    /// The file is named after the image identifier, e.g. `image.jpg.json`.
+    pub fn save_supervisely<P: AsRef<Path>>(&self, path: P) -> Result<(), ConvError> {
+        let path = path.as_ref().join(format!("{}.json", self.img_id));
+        let contents = self.to_supervisely()?;
+
+        fs::write(path, contents).map_err(|_| ConvError {})
+    }
+}
+
+impl AnnSet {
+    /// Save the annotations to a Supervisely project.
+    /// 
+    /// The class definitions are written to `meta.json` and the
+    /// annotations to the `ann` directory of the dataset `dataset`.
+    pub fn save_supervisely<P: AsRef<Path>>(&self, path: P, dataset: &str) -> Result<(), ConvError> {
+        let path = path.as_ref();
+
+        let labels = self.iter()
+            .flat_map(|ann| ann.bboxes.iter().map(|b| b.label.as_str()))
+            .collect::<BTreeSet<_>>();
+
+        let classes = labels.into_iter()
+            .zip(PALETTE.iter().cycle())
+            .map(|(label, color)| SlyClass {
+                title: String::from(label),
+                shape: String::from(RECTANGLE),
+                color: String::from(*color),
+            })
+            .collect();
+
+        let meta = SlyMeta { classes, tags: vec![] };
+        let contents = to_string(&meta).map_err(|_| ConvError {})?;
+
+        let ann_dir = path.join(dataset).join("ann");
+        fs::create_dir_all(&ann_dir).map_err(|_| ConvError {})?;
+        fs::write(path.join("meta.json"), contents).map_err(|_| ConvError {})?;
+
+        self.save_all(|ann| ann.save_supervisely(&ann_dir))
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{
+    coords::abs_to_rel,
+    annotation::Ann,
+    annotationset::AnnSet,
+    converters::ConvError,
+    serde_records::tfrecord::*,
+};
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+    ffi::OsStr,
+};
+
+fn to_example(
+    ann: &Ann,
+    to_class_id: &HashMap<&str, i64>,
+    imgs_path: Option<&Path>,
+) -> Result<TFExample, ConvError> {
+    let img_size = ann.img_size.ok_or(ConvError {})?;
+    let filename = ann.img_id.as_bytes().to_vec();
+
+    let mut example = TFExample::default();
+
+    example.insert("image/filename", TFFeature::Bytes(vec![filename.clone()]));
+    example.insert("image/source_id", TFFeature::Bytes(vec![filename]));
+    example.insert("image/width", TFFeature::Int64(vec![img_size.width as i64]));
+    example.insert("image/height", TFFeature::Int64(vec![img_size.height as i64]));
+
+    if let Some(imgs_path) = imgs_path {
+        let img_path = imgs_path.join(&ann.img_id);
+        let encoded = fs::read(&img_path).map_err(|_| ConvError {})?;
+
+        let format = img_path.extension()
+            .and_then(OsStr::to_str)
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+
+        // TensorFlow expects `jpeg` rather than `jpg`.
+        let format = if format == "jpg" { String::from("jpeg") } else { format };
+
+        example.insert("image/encoded", TFFeature::Bytes(vec![encoded]));
+        example.insert("image/format", TFFeature::Bytes(vec![format.into_bytes()]));
+    }
+
+    let nb_boxes = ann.bboxes.len();
+    let mut xmins = Vec::with_capacity(nb_boxes);
+    let mut ymins = Vec::with_capacity(nb_boxes);
+    let mut xmaxs = Vec::with_capacity(nb_boxes);
+    let mut ymaxs = Vec::with_capacity(nb_boxes);
+    let mut texts = Vec::with_capacity(nb_boxes);
+    let mut ids = Vec::with_capacity(nb_boxes);
+    let mut scores = Vec::with_capacity(nb_boxes);
+
+    for bbox in &ann.bboxes {
+        let (xmin, ymin, xmax, ymax) = abs_to_rel(bbox.ltrb(), img_size);
+
+        xmins.push(xmin);
+        ymins.push(ymin);
+        xmaxs.push(xmax);
+        ymaxs.push(ymax);
+        texts.push(bbox.label.as_bytes().to_vec());
+        ids.push(to_class_id[bbox.label.as_str()]);
+
+        if let Some(conf) = bbox.conf() {
+            scores.push(conf);
+        }
+    }
+
+    example.insert("image/object/bbox/xmin", TFFeature::Float(xmins));
+    example.insert("image/object/bbox/ymin", TFFeature::Float(ymins));
+    example.insert("image/object/bbox/xmax", TFFeature::Float(xmaxs));
+    example.insert("image/object/bbox/ymax", TFFeature::Float(ymaxs));
+    example.insert("image/object/class/text", TFFeature::Bytes(texts));
+    example.insert("image/object/class/label", TFFeature::Int64(ids));
+
+    // Scores are only meaningful if every box is a detection.
+    if !scores.is_empty() {
+        if scores.len() != nb_boxes {
+            return Err(ConvError {})
+        }
+
+        example.insert("image/object/score", TFFeature::Float(scores));
+    }
+
+    Ok(example)
+}
+
+impl AnnSet {
+    /// Save the annotations to a TFRecord file of `tf.train.Example`
+    /// using the TensorFlow Object Detection API feature layout.
+    /// 
+    /// Class ids start at 1 (0 is reserved for the background) and are
+    /// assigned in lexicographic order of the labels. Image bytes are
+    /// embedded only if `imgs_path` is provided.
+    pub fn save_tfrecord<P1: AsRef<Path>, P2: AsRef<Path>>(
+        &self,
+        path: P1,
+        imgs_path: Option<P2>,
+    ) -> Result<(), ConvError> {
+        let labels = self.iter()
+            .flat_map(|ann| ann.bboxes.iter().map(|b| b.label.as_str()))
+            .collect::<BTreeSet<_>>();
+
+        let to_class_id = labels.into_iter()
+            .zip(1..)
+            .collect::<HashMap<_, _>>();
+
+        let file = File::create(path).map_err(|_| ConvError {})?;
+        let mut writer = BufWriter::new(file);
+
+        for ann in self {
+            let example = to_example(ann, &to_class_id, imgs_path.as_ref().map(AsRef::as_ref))?;
+
+            write_record(&mut writer, &example.encode())
+                .map_err(|_| ConvError {})?;
+        }
+
+        writer.flush().map_err(|_| ConvError {})
+    }
+}
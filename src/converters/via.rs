@@ -0,0 +1,68 @@
+use crate::{
+    bbox::BBox,
+    annotation::Ann,
+    annotationset::AnnSet,
+    converters::ConvError,
+    serde_records::via::*,
+};
+
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    fs,
+    ffi::OsStr,
+};
+
+use serde_json::{to_string, to_value, Value};
+
+fn to_via_region(bbox: &BBox, label_attr: &str) -> Result<VIARegion, ConvError> {
+    let (x, y, width, height) = bbox.ltwh();
+    let rect = VIARect { name: String::from(RECT), x, y, width, height };
+
+    let mut region_attributes = bbox.attributes.iter()
+        .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+        .collect::<BTreeMap<_, _>>();
+
+    region_attributes.insert(String::from(label_attr), Value::String(bbox.label.clone()));
+
+    Ok(VIARegion {
+        shape_attributes: to_value(rect).map_err(|_| ConvError {})?,
+        region_attributes,
+    })
+}
+
+fn to_via_img(ann: &Ann, label_attr: &str) -> Result<VIAImg, ConvError> {
+    let regions = ann.bboxes.iter()
+        .map(|b| to_via_region(b, label_attr))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(VIAImg {
+        filename: ann.img_id.clone(),
+        size: -1,
+        regions,
+        file_attributes: BTreeMap::new(),
+    })
+}
+
+impl AnnSet {
+    /// Save the annotations to VGG Image Annotator (VIA) annotation format.
+    /// 
+    /// The file can be imported in VIA with "Import annotations (from json)".
+    /// Labels are written to the region attribute `label_attr`.
+    pub fn save_via<P: AsRef<Path>>(&self, path: P, label_attr: &str) -> Result<(), ConvError> {
+        if let Some(e) = path.as_ref().extension() {
+            if e != OsStr::new("json") {
+                return Err(ConvError {})
+            }
+        }
+
+        // VIA keys images by file name and file size.
+        let imgs = self.iter()
+            .map(|ann| Ok((format!("{}-1", ann.img_id), to_via_img(ann, label_attr)?)))
+            .collect::<Result<BTreeMap<_, _>, ConvError>>()?;
+
+        let contents = to_string(&imgs).map_err(|_| ConvError {})?;
+
+        fs::write(path, contents).map_err(|_| ConvError {})
+    }
+}
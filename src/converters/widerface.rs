@@ -0,0 +1,51 @@
+use crate::{
+    bbox::BBox,
+    annotationset::AnnSet,
+    converters::ConvError,
+    serde_records::widerface::*,
+};
+
+use std::{
+    fs,
+    fmt::Write,
+    path::Path,
+};
+
+impl BBox {
+    fn to_widerface(&self) -> String {
+        let (x, y, w, h) = self.ltwh();
+
+        match self.conf() {
+            Some(conf) => format!("{x} {y} {w} {h} {conf}"),
+            None => {
+                let flags = ATTRS.map(|name| self.attribute(name).unwrap_or("0"));
+                format!("{x} {y} {w} {h} {}", flags.join(" "))
+            },
+        }
+    }
+}
+
+impl AnnSet {
+    /// Save the annotations to a WIDER FACE annotation file.
+    /// 
+    /// All bounding boxes are considered faces regardless of their label.
+    /// Missing flags of ground truths are set to `0`.
+    pub fn save_widerface<P: AsRef<Path>>(&self, path: P) -> Result<(), ConvError> {
+        let mut contents = String::new();
+
+        for ann in self {
+            let nb_bboxes = ann.bboxes.len();
+            writeln!(contents, "{}\n{nb_bboxes}", ann.img_id).map_err(|_| ConvError {})?;
+
+            if nb_bboxes == 0 {
+                contents.push_str("0 0 0 0 0 0 0 0 0 0\n");
+            }
+
+            for bbox in &ann.bboxes {
+                writeln!(contents, "{}", bbox.to_widerface()).map_err(|_| ConvError {})?;
+            }
+        }
+
+        fs::write(path, contents).map_err(|_| ConvError {})
+    }
+}
@@ -1,27 +1,263 @@
-use crate::bbox::BBox;
+use crate::{
+    bbox::BBox,
+    coords::Coords,
+};
+
+use std::f32::consts::PI;
+
+/// The measure of overlap between two bounding boxes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IoUKind {
+    /// Intersection over Union.
+    #[default]
+    IoU,
+
+    /// Generalized IoU, penalised by the area of the enclosing box not 
+    /// covered by the two bounding boxes. Lies in `[-1, 1]`.
+    GIoU,
+
+    /// Distance IoU, penalised by the distance between the bounding box 
+    /// centers. Lies in `[-1, 1]`.
+    DIoU,
+
+    /// Complete IoU, the Distance IoU further penalised by the aspect 
+    /// ratio mismatch. Lies in `[-1, 1]`.
+    CIoU,
+
+    /// Intersection over the area of the first bounding box, used to 
+    /// match detections with crowd and ignore regions.
+    IoA,
+}
+
+impl IoUKind {
+    /// Computes the overlap of two bounding boxes. 
+    /// 
+    /// This is not symmetric for `IoUKind::IoA`.
+    pub fn compute(self, lhs: &BBox, rhs: &BBox) -> f32 {
+        self.compute_ltrb(lhs.ltrb(), rhs.ltrb())
+    }
+
+    fn compute_ltrb(self, lhs: Coords, rhs: Coords) -> f32 {
+        match self {
+            IoUKind::IoU => iou_ltrb(lhs, rhs),
+            IoUKind::GIoU => giou_ltrb(lhs, rhs),
+            IoUKind::DIoU => diou_ltrb(lhs, rhs),
+            IoUKind::CIoU => ciou_ltrb(lhs, rhs),
+            IoUKind::IoA => ioa_ltrb(lhs, rhs),
+        }
+    }
+}
 
 /// Compute the Intersection over Union (IoU) of 
 /// two bounding boxes.
 pub fn iou(lhs: &BBox, rhs: &BBox) -> f32 {
-    let xmin = lhs.xmin().max(rhs.xmin());
-    let ymin = lhs.ymin().max(rhs.ymin());
-    let xmax = lhs.xmax().min(rhs.xmax());
-    let ymax = lhs.ymax().min(rhs.ymax());
+    iou_ltrb(lhs.ltrb(), rhs.ltrb())
+}
+
+/// Compute the Generalized Intersection over Union (GIoU)
+/// of two bounding boxes.
+pub fn giou(lhs: &BBox, rhs: &BBox) -> f32 {
+    giou_ltrb(lhs.ltrb(), rhs.ltrb())
+}
+
+/// Compute the Distance Intersection over Union (DIoU)
+/// of two bounding boxes.
+pub fn diou(lhs: &BBox, rhs: &BBox) -> f32 {
+    diou_ltrb(lhs.ltrb(), rhs.ltrb())
+}
+
+/// Compute the Complete Intersection over Union (CIoU)
+/// of two bounding boxes.
+pub fn ciou(lhs: &BBox, rhs: &BBox) -> f32 {
+    ciou_ltrb(lhs.ltrb(), rhs.ltrb())
+}
+
+/// Compute the Intersection over the Area (IoA) of the
+/// first bounding box.
+pub fn ioa(lhs: &BBox, rhs: &BBox) -> f32 {
+    ioa_ltrb(lhs.ltrb(), rhs.ltrb())
+}
+
+fn area((l, t, r, b): Coords) -> f32 {
+    (r - l) * (b - t)
+}
+
+/// The intersection area, zero if the bounding boxes do not intersect.
+fn intersection(lhs: Coords, rhs: Coords) -> f32 {
+    let xmin = lhs.0.max(rhs.0);
+    let ymin = lhs.1.max(rhs.1);
+    let xmax = lhs.2.min(rhs.2);
+    let ymax = lhs.3.min(rhs.3);
 
     // The bounding boxes do not intersect.
     if xmax <= xmin || ymax <= ymin {
         return 0.0
     }
 
-    let inter = (xmax - xmin) * (ymax - ymin);
+    (xmax - xmin) * (ymax - ymin)
+}
+
+/// The smallest box enclosing both bounding boxes.
+fn enclosing(lhs: Coords, rhs: Coords) -> Coords {
+    (lhs.0.min(rhs.0), lhs.1.min(rhs.1), lhs.2.max(rhs.2), lhs.3.max(rhs.3))
+}
 
-    let uni = lhs.area() + rhs.area() - inter;
+fn iou_ltrb(lhs: Coords, rhs: Coords) -> f32 {
+    let inter = intersection(lhs, rhs);
+
+    if inter == 0.0 {
+        return 0.0
+    }
+
+    let uni = area(lhs) + area(rhs) - inter;
     
     // `uni` is never equal to zero since it implies that
     // one of the bounding box has an area of zero and this
     // implies in turns that xmin == xmax and ymin == ymax, thus
     // the function would have returned early.
-    return inter / uni
+    inter / uni
+}
+
+fn giou_ltrb(lhs: Coords, rhs: Coords) -> f32 {
+    let inter = intersection(lhs, rhs);
+    let uni = area(lhs) + area(rhs) - inter;
+    let hull = area(enclosing(lhs, rhs));
+
+    // Both bounding boxes are degenerate and aligned.
+    if hull <= 0.0 {
+        return 0.0
+    }
+
+    let iou = if inter == 0.0 { 0.0 } else { inter / uni };
+
+    iou - (hull - uni) / hull
+}
+
+/// The squared center distance over the squared enclosing box diagonal.
+fn distance_penalty(lhs: Coords, rhs: Coords) -> f32 {
+    let (l, t, r, b) = enclosing(lhs, rhs);
+    let diag = (r - l).powi(2) + (b - t).powi(2);
+
+    if diag <= 0.0 {
+        return 0.0
+    }
+
+    let dx = (lhs.0 + lhs.2 - rhs.0 - rhs.2) / 2.0;
+    let dy = (lhs.1 + lhs.3 - rhs.1 - rhs.3) / 2.0;
+
+    (dx.powi(2) + dy.powi(2)) / diag
+}
+
+fn diou_ltrb(lhs: Coords, rhs: Coords) -> f32 {
+    iou_ltrb(lhs, rhs) - distance_penalty(lhs, rhs)
+}
+
+fn ciou_ltrb(lhs: Coords, rhs: Coords) -> f32 {
+    let iou = iou_ltrb(lhs, rhs);
+
+    // `atan2` gracefully handles bounding boxes with a null height.
+    let atan_lhs = (lhs.2 - lhs.0).atan2(lhs.3 - lhs.1);
+    let atan_rhs = (rhs.2 - rhs.0).atan2(rhs.3 - rhs.1);
+    let v = 4.0 / (PI * PI) * (atan_lhs - atan_rhs).powi(2);
+
+    let alpha = if v == 0.0 { 0.0 } else { v / (1.0 - iou + v) };
+
+    iou - distance_penalty(lhs, rhs) - alpha * v
+}
+
+fn ioa_ltrb(lhs: Coords, rhs: Coords) -> f32 {
+    let area = area(lhs);
+
+    if area <= 0.0 {
+        return 0.0
+    }
+
+    intersection(lhs, rhs) / area
+}
+
+/// A dense matrix of pairwise overlaps, rows being indexed by the
+/// first slice of bounding boxes and columns by the second one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IoUMatrix {
+    nb_rows: usize,
+    nb_cols: usize,
+    values: Vec<f32>,
+}
+
+impl IoUMatrix {
+    pub fn nb_rows(&self) -> usize { self.nb_rows }
+
+    pub fn nb_cols(&self) -> usize { self.nb_cols }
+
+    /// The overlap of the `row`-th bounding box of the first slice with
+    /// the `col`-th bounding box of the second slice.
+    /// 
+    /// # Panics
+    /// Will panic if the indices are out of bounds.
+    pub fn get(&self, row: usize, col: usize) -> f32 {
+        assert!(row < self.nb_rows, "row index out of bounds");
+        assert!(col < self.nb_cols, "column index out of bounds");
+        self.values[row * self.nb_cols + col]
+    }
+
+    /// The overlaps of the `row`-th bounding box of the first slice
+    /// with every bounding box of the second slice.
+    /// 
+    /// # Panics
+    /// Will panic if the index is out of bounds.
+    pub fn row(&self, row: usize) -> &[f32] {
+        assert!(row < self.nb_rows, "row index out of bounds");
+        &self.values[row * self.nb_cols..(row + 1) * self.nb_cols]
+    }
+
+    pub(crate) fn set(&mut self, row: usize, col: usize, value: f32) {
+        assert!(row < self.nb_rows, "row index out of bounds");
+        assert!(col < self.nb_cols, "column index out of bounds");
+        self.values[row * self.nb_cols + col] = value;
+    }
+}
+
+/// Computes the overlap of every pair of bounding boxes of the two slices.
+pub fn iou_matrix(lhs: &[BBox], rhs: &[BBox], kind: IoUKind) -> IoUMatrix {
+    iou_matrix_ltrb(
+        lhs.iter().map(BBox::ltrb).collect(), 
+        rhs.iter().map(BBox::ltrb).collect(),
+        kind,
+    )
+}
+
+/// Same as `iou_matrix` for slices of references, which avoids cloning
+/// bounding boxes grouped by label.
+pub(crate) fn iou_matrix_ref(lhs: &[&BBox], rhs: &[&BBox], kind: IoUKind) -> IoUMatrix {
+    iou_matrix_ltrb(
+        lhs.iter().map(|b| b.ltrb()).collect(), 
+        rhs.iter().map(|b| b.ltrb()).collect(),
+        kind,
+    )
+}
+
+// Coordinates are gathered upfront and the overlap function is chosen
+// once, so that each inner loop is monomorphised and can be inlined.
+fn iou_matrix_ltrb(lhs: Vec<Coords>, rhs: Vec<Coords>, kind: IoUKind) -> IoUMatrix {
+    fn fill<F: Fn(Coords, Coords) -> f32>(lhs: &[Coords], rhs: &[Coords], f: F) -> Vec<f32> {
+        let mut values = Vec::with_capacity(lhs.len() * rhs.len());
+
+        for &l in lhs {
+            values.extend(rhs.iter().map(|&r| f(l, r)));
+        }
+
+        values
+    }
+
+    let values = match kind {
+        IoUKind::IoU => fill(&lhs, &rhs, iou_ltrb),
+        IoUKind::GIoU => fill(&lhs, &rhs, giou_ltrb),
+        IoUKind::DIoU => fill(&lhs, &rhs, diou_ltrb),
+        IoUKind::CIoU => fill(&lhs, &rhs, ciou_ltrb),
+        IoUKind::IoA => fill(&lhs, &rhs, ioa_ltrb),
+    };
+
+    IoUMatrix { nb_rows: lhs.len(), nb_cols: rhs.len(), values }
 }
 
 #[cfg(test)]
@@ -99,5 +335,74 @@ mod tests {
         let b2 = BBox::new("", 0.0, 10.0, 10.0, 40.0, None);
 
         assert_eq!(iou(&b1, &b2), 0.5);
-    }    
-}
\ No newline at end of file
+    }
+
+    #[test]
+    fn test_giou() {
+        let b1 = BBox::new("", 0.0, 0.0, 10.0, 10.0, None);
+        let b2 = BBox::new("", 20.0, 0.0, 30.0, 10.0, None);
+
+        assert_eq!(giou(&b1, &b1), 1.0);
+        assert_eq!(giou(&b1, &b2), -1.0 / 3.0);
+    }
+
+    #[test]
+    fn test_diou_ciou() {
+        let b1 = BBox::new("", 0.0, 0.0, 10.0, 10.0, None);
+        let b2 = BBox::new("", 2.0, 2.0, 8.0, 8.0, None);
+        let b3 = BBox::new("", 10.0, 0.0, 20.0, 10.0, None);
+
+        // Same center and aspect ratio, thus no penalty.
+        assert_eq!(diou(&b1, &b2), iou(&b1, &b2));
+        assert_eq!(ciou(&b1, &b2), iou(&b1, &b2));
+
+        assert_eq!(diou(&b1, &b3), -0.2);
+
+        let b4 = BBox::new("", 0.0, 0.0, 10.0, 20.0, None);
+        assert!(ciou(&b1, &b4) < diou(&b1, &b4));
+    }
+
+    #[test]
+    fn test_ioa() {
+        let b1 = BBox::new("", 0.0, 0.0, 10.0, 10.0, None);
+        let b2 = BBox::new("", 5.0, 0.0, 25.0, 10.0, None);
+
+        assert_eq!(ioa(&b1, &b2), 0.5);
+        assert_eq!(ioa(&b2, &b1), 0.25);
+    }
+
+    #[test]
+    fn test_iou_matrix() {
+        let lhs = [
+            BBox::new("", 0.0, 0.0, 10.0, 30.0, None),
+            BBox::new("", 5.0, 5.0, 10.0, 10.0, None),
+        ];
+        let rhs = [
+            BBox::new("", 0.0, 10.0, 10.0, 40.0, None),
+            BBox::new("", 0.0, 0.0, 10.0, 10.0, None),
+            BBox::new("", 50.0, 50.0, 60.0, 60.0, None),
+        ];
+
+        for kind in [IoUKind::IoU, IoUKind::GIoU, IoUKind::CIoU, IoUKind::IoA] {
+            let matrix = iou_matrix(&lhs, &rhs, kind);
+            assert_eq!((matrix.nb_rows(), matrix.nb_cols()), (2, 3));
+
+            for (i, l) in lhs.iter().enumerate() {
+                for (j, r) in rhs.iter().enumerate() {
+                    assert_eq!(matrix.get(i, j), kind.compute(l, r));
+                }
+            }
+        }
+
+        assert_eq!(iou_matrix(&lhs, &[], IoUKind::IoU).row(1), &[] as &[f32]);
+    }
+
+    #[test]
+    #[should_panic(expected = "row index out of bounds")]
+    fn test_iou_matrix_row_out_of_bounds() {
+        let lhs = [BBox::new("", 0.0, 0.0, 10.0, 10.0, None)];
+        let rhs = [BBox::new("", 0.0, 0.0, 10.0, 10.0, None)];
+
+        iou_matrix(&lhs, &rhs, IoUKind::IoU).get(1, 0);
+    }
+}
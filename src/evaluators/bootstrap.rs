@@ -0,0 +1,488 @@
+use super::coco::{COCOEval, AREA_ALL, MAX_DETS, average_precision, fmt_metric};
+
+use crate::rng::Rng;
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt::{self, Display},
+};
+
+use serde::Serialize;
+
+/// A point estimate with a bootstrap percentile confidence interval.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Interval {
+    /// The value on the original images.
+    pub value: f32,
+    pub low: f32,
+    pub high: f32,
+}
+
+/// Bootstrap confidence intervals of the Average Precision.
+#[derive(Debug, Clone, Serialize)]
+pub struct BootstrapEval {
+    /// The mean Average Precision over labels with ground truths.
+    pub map: Option<Interval>,
+
+    /// `None` for labels without ground truth.
+    pub labels: BTreeMap<String, Option<Interval>>,
+}
+
+/// The comparison of the Average Precision of two labels or models.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diff {
+    /// The Average Precision of B minus the one of A.
+    pub diff: Interval,
+
+    /// The fraction of bootstrap samples where B has a higher Average
+    /// Precision than A, ties counting for one half.
+    pub prob_b_better: f32,
+}
+
+/// The paired bootstrap comparison of two models, A and B, evaluated on
+/// the same ground truths.
+#[derive(Debug, Clone, Serialize)]
+pub struct Comparison {
+    pub nb_imgs: usize,
+    pub nb_samples: usize,
+    pub confidence: f32,
+    pub a: BootstrapEval,
+    pub b: BootstrapEval,
+
+    /// `None` if either model has no Average Precision.
+    pub map: Option<Diff>,
+
+    pub labels: BTreeMap<String, Option<Diff>>,
+}
+
+/// The ranked detections of one image and one label at every IoU threshold.
+struct ImgStats {
+    nb_gts: usize,
+    dets: Vec<Vec<(f32, bool)>>,
+}
+
+/// For each label, the statistics of each image, `None` if the label
+/// is absent from the image.
+type Stats<'a> = BTreeMap<&'a str, Vec<Option<ImgStats>>>;
+
+/// The Average Precision of each label on the original images followed
+/// by each bootstrap sample.
+type Draws<'a> = Vec<BTreeMap<&'a str, Option<f32>>>;
+
+impl COCOEval<'_> {
+    /// Computes bootstrap confidence intervals of the Average Precision,
+    /// averaged over the IoU thresholds, by resampling images with
+    /// replacement `nb_samples` times.
+    ///
+    /// # Panics
+    /// Will panic if a threshold is not in `[0, 1]`, if there is no
+    /// sample or if the confidence level is not in `(0, 1)`.
+    pub fn bootstrap(&self, threshs: &[f32], nb_samples: usize, confidence: f32, seed: u64) -> BootstrapEval {
+        Self::validate_bootstrap(threshs, nb_samples, confidence);
+
+        let img_ids: Vec<&str> = self.img_ids().collect();
+        let samples = resample(img_ids.len(), nb_samples, seed);
+        let draws = draws(&self.img_stats(&img_ids, threshs), &samples);
+
+        BootstrapEval::new(&draws, confidence)
+    }
+
+    /// Compares with the detections of `other`, which should have the same
+    /// ground truths, drawing the same images for both at each bootstrap
+    /// sample.
+    ///
+    /// # Panics
+    /// Will panic if a threshold is not in `[0, 1]`, if there is no
+    /// sample or if the confidence level is not in `(0, 1)`.
+    pub fn compare(
+        &self,
+        other: &COCOEval,
+        threshs: &[f32],
+        nb_samples: usize,
+        confidence: f32,
+        seed: u64,
+    ) -> Comparison {
+        Self::validate_bootstrap(threshs, nb_samples, confidence);
+
+        let mut seen = HashSet::new();
+        let img_ids: Vec<&str> = self.img_ids()
+            .chain(other.img_ids())
+            .filter(|&img_id| seen.insert(img_id))
+            .collect();
+
+        let samples = resample(img_ids.len(), nb_samples, seed);
+        let draws_a = draws(&self.img_stats(&img_ids, threshs), &samples);
+        let draws_b = draws(&other.img_stats(&img_ids, threshs), &samples);
+
+        let labels: BTreeSet<&str> = draws_a[0].keys().chain(draws_b[0].keys()).copied().collect();
+
+        let label_diffs = labels.into_iter()
+            .map(|label| {
+                let ap = |draws: &Draws, i: usize| draws[i].get(label).copied().flatten();
+                let values = (0..draws_a.len()).map(|i| (ap(&draws_a, i), ap(&draws_b, i)));
+
+                (label.to_string(), Diff::new(values, confidence))
+            })
+            .collect();
+
+        let values = draws_a.iter().zip(&draws_b).map(|(a, b)| (mean(a), mean(b)));
+
+        Comparison {
+            nb_imgs: img_ids.len(),
+            nb_samples,
+            confidence,
+            a: BootstrapEval::new(&draws_a, confidence),
+            b: BootstrapEval::new(&draws_b, confidence),
+            map: Diff::new(values, confidence),
+            labels: label_diffs,
+        }
+    }
+
+    fn validate_bootstrap(threshs: &[f32], nb_samples: usize, confidence: f32) {
+        assert!(
+            threshs.iter().all(|&t| Self::validate_args(t, AREA_ALL))
+                && !threshs.is_empty()
+                && nb_samples > 0
+                && 0.0 < confidence && confidence < 1.0,
+            "invalid evaluation parameters"
+        );
+    }
+
+    /// Matches the bounding boxes once, the bootstrap samples only reuse
+    /// the ranked detections of each image.
+    fn img_stats<'a>(&'a self, img_ids: &[&str], threshs: &[f32]) -> Stats<'a> {
+        let max_det = MAX_DETS[MAX_DETS.len() - 1];
+        let boxes = self.group(max_det);
+        let evals = self.match_all(&boxes, threshs, AREA_ALL);
+
+        let positions: HashMap<&str, usize> = img_ids.iter()
+            .enumerate()
+            .map(|(i, &img_id)| (img_id, i))
+            .collect();
+
+        boxes.iter()
+            .map(|(&label, imgs)| {
+                let mut stats: Vec<Option<ImgStats>> = (0..img_ids.len()).map(|_| None).collect();
+
+                for (img, eval) in imgs.iter().zip(&evals[label]) {
+                    let dets = (0..threshs.len())
+                        .map(|t| Self::ranked_dets(std::slice::from_ref(eval), t, max_det))
+                        .collect();
+
+                    stats[positions[img.img_id]] = Some(ImgStats { nb_gts: eval.nb_gts, dets });
+                }
+
+                (label, stats)
+            })
+            .collect()
+    }
+}
+
+/// Draws the number of occurrences of each image in each sample.
+fn resample(nb_imgs: usize, nb_samples: usize, seed: u64) -> Vec<Vec<usize>> {
+    let mut rng = Rng::new(seed);
+
+    (0..nb_samples)
+        .map(|_| {
+            let mut counts = vec![0; nb_imgs];
+            for _ in 0..nb_imgs {
+                counts[rng.below(nb_imgs)] += 1;
+            }
+            counts
+        })
+        .collect()
+}
+
+/// Computes the Average Precision of each label on the original images
+/// followed by each sample.
+fn draws<'a>(stats: &Stats<'a>, samples: &[Vec<usize>]) -> Draws<'a> {
+    let original = stats.values()
+        .next()
+        .map_or(vec![], |imgs| vec![1; imgs.len()]);
+
+    std::iter::once(&original)
+        .chain(samples)
+        .map(|counts| {
+            stats.iter()
+                .map(|(&label, imgs)| (label, label_ap(imgs, counts)))
+                .collect()
+        })
+        .collect()
+}
+
+/// The Average Precision averaged over IoU thresholds of one label when
+/// each image is drawn `counts` times, `None` without ground truth.
+fn label_ap(imgs: &[Option<ImgStats>], counts: &[usize]) -> Option<f32> {
+    let drawn = || imgs.iter()
+        .zip(counts)
+        .filter_map(|(img, &count)| Some((img.as_ref()?, count)));
+
+    let nb_gts: usize = drawn().map(|(img, count)| img.nb_gts * count).sum();
+    let nb_threshs = drawn().next()?.0.dets.len();
+
+    if nb_gts == 0 {
+        return None
+    }
+
+    let ap = (0..nb_threshs)
+        .map(|t| {
+            let mut dets: Vec<(f32, bool)> = drawn()
+                .flat_map(|(img, count)| std::iter::repeat_n(&img.dets[t], count).flatten().copied())
+                .collect();
+
+            dets.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+            average_precision(&dets, nb_gts).0
+        })
+        .sum::<f32>();
+
+    Some(ap / nb_threshs as f32)
+}
+
+/// The mean Average Precision over labels with ground truths.
+fn mean(aps: &BTreeMap<&str, Option<f32>>) -> Option<f32> {
+    let aps: Vec<f32> = aps.values().flatten().copied().collect();
+
+    (!aps.is_empty()).then(|| aps.iter().sum::<f32>() / aps.len() as f32)
+}
+
+impl Interval {
+    /// The percentile interval of the samples around `value`.
+    fn new(value: f32, mut samples: Vec<f32>, confidence: f32) -> Self {
+        if samples.is_empty() {
+            return Interval { value, low: value, high: value }
+        }
+
+        samples.sort_by(f32::total_cmp);
+
+        let alpha = (1.0 - confidence) / 2.0;
+        let quantile = |p: f32| samples[(p * (samples.len() - 1) as f32).round() as usize];
+
+        Interval { value, low: quantile(alpha), high: quantile(1.0 - alpha) }
+    }
+}
+
+impl BootstrapEval {
+    fn new(draws: &Draws, confidence: f32) -> Self {
+        let interval = |values: Vec<Option<f32>>| {
+            let samples = values[1..].iter().flatten().copied().collect();
+            Some(Interval::new(values[0]?, samples, confidence))
+        };
+
+        let labels = draws[0].keys()
+            .map(|&label| {
+                let values = draws.iter().map(|aps| aps[label]).collect();
+                (label.to_string(), interval(values))
+            })
+            .collect();
+
+        BootstrapEval {
+            map: interval(draws.iter().map(mean).collect()),
+            labels,
+        }
+    }
+}
+
+impl Diff {
+    /// From the values of A and B on the original images followed by each
+    /// sample. Samples where either value is undefined are skipped.
+    fn new<I: Iterator<Item = (Option<f32>, Option<f32>)>>(mut values: I, confidence: f32) -> Option<Self> {
+        let (a, b) = values.next()?;
+        let value = b? - a?;
+
+        let samples: Vec<(f32, f32)> = values
+            .filter_map(|(a, b)| Some((a?, b?)))
+            .collect();
+
+        let wins: f32 = samples.iter()
+            .map(|(a, b)| if b > a { 1.0 } else if b == a { 0.5 } else { 0.0 })
+            .sum();
+
+        let prob_b_better = if samples.is_empty() { 0.5 } else { wins / samples.len() as f32 };
+        let diffs = samples.iter().map(|(a, b)| b - a).collect();
+
+        Some(Diff { diff: Interval::new(value, diffs, confidence), prob_b_better })
+    }
+}
+
+impl Comparison {
+    /// The mean Average Precision followed by the labels, the mean having
+    /// an empty label.
+    fn rows(&self) -> impl Iterator<Item = (&str, Option<&Interval>, Option<&Interval>, Option<&Diff>)> {
+        let map = ("", self.a.map.as_ref(), self.b.map.as_ref(), self.map.as_ref());
+
+        let labels = self.labels.iter().map(|(label, diff)| {
+            let a = self.a.labels.get(label).and_then(Option::as_ref);
+            let b = self.b.labels.get(label).and_then(Option::as_ref);
+
+            (label.as_str(), a, b, diff.as_ref())
+        });
+
+        std::iter::once(map).chain(labels)
+    }
+
+    /// Exports the comparison as CSV with one row per label. The first
+    /// row, with an empty label, holds the mean Average Precision.
+    /// Undefined values are left empty.
+    pub fn to_csv(&self) -> String {
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        writer.write_record([
+            "label", "ap_a", "ap_a_low", "ap_a_high", "ap_b", "ap_b_low", "ap_b_high",
+            "diff", "diff_low", "diff_high", "prob_b_better",
+        ]).expect("writing to memory should not fail");
+
+        let fmt = |v: Option<f32>| v.map(|v| v.to_string()).unwrap_or_default();
+        let fmt_interval = |i: Option<&Interval>| [
+            fmt(i.map(|i| i.value)), fmt(i.map(|i| i.low)), fmt(i.map(|i| i.high)),
+        ];
+
+        for (label, a, b, diff) in self.rows() {
+            let record = std::iter::once(label.to_string())
+                .chain(fmt_interval(a))
+                .chain(fmt_interval(b))
+                .chain(fmt_interval(diff.map(|d| &d.diff)))
+                .chain([fmt(diff.map(|d| d.prob_b_better))]);
+
+            writer.write_record(record)
+                .expect("writing to memory should not fail");
+        }
+
+        let bytes = writer.into_inner()
+            .expect("writing to memory should not fail");
+
+        String::from_utf8(bytes).expect("CSV should be valid UTF-8")
+    }
+}
+
+fn fmt_interval(interval: Option<&Interval>) -> String {
+    interval.map_or(String::from("-"), |i| {
+        format!("{} [{}, {}]", fmt_metric(Some(i.value)), fmt_metric(Some(i.low)), fmt_metric(Some(i.high)))
+    })
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f, "Paired bootstrap over {} images, {} samples, {}% confidence intervals",
+            self.nb_imgs, self.nb_samples, self.confidence * 100.0,
+        )?;
+
+        writeln!(
+            f, "\n{:<24} {:>22} {:>22} {:>25} {:>10}",
+            "Label", "AP (A)", "AP (B)", "B - A", "P(B > A)",
+        )?;
+
+        for (label, a, b, diff) in self.rows() {
+            let label = if label.is_empty() { "mAP" } else { label };
+
+            writeln!(
+                f, "{:<24} {:>22} {:>22} {:>25} {:>10}",
+                label,
+                fmt_interval(a),
+                fmt_interval(b),
+                fmt_interval(diff.map(|d| &d.diff)),
+                fmt_metric(diff.map(|d| d.prob_b_better)),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{annotation::Ann, annotationset::AnnSet, bbox::BBox, evaluators::bootstrap::*};
+
+    fn cats(x: impl Fn(usize) -> f32, conf: Option<f32>) -> AnnSet {
+        AnnSet::from_iter((0..20).map(|i| {
+            let x = x(i);
+            Ann::new(format!("{i}"), None, vec![BBox::new("cat", x, 0.0, x + 10.0, 10.0, conf)])
+        }))
+    }
+
+    /// A finds every cat, B misses half of them.
+    fn models() -> (AnnSet, AnnSet, AnnSet) {
+        let gts = cats(|_| 0.0, None);
+        let dets_a = cats(|_| 0.0, Some(0.9));
+        let dets_b = cats(|i| if i % 2 == 0 { 0.0 } else { 50.0 }, Some(0.9));
+
+        (gts, dets_a, dets_b)
+    }
+
+    #[test]
+    fn intervals() {
+        let (gts, dets_a, dets_b) = models();
+
+        let eval = COCOEval::new(&gts, &dets_a).bootstrap(&[0.5], 200, 0.9, 0);
+        let map = eval.map.unwrap();
+        assert_eq!((map.value, map.low, map.high), (1.0, 1.0, 1.0));
+        assert_eq!(eval.labels["cat"], Some(map));
+
+        let eval = COCOEval::new(&gts, &dets_b).bootstrap(&[0.5], 200, 0.9, 0);
+        let map = eval.map.unwrap();
+        assert!(map.low <= map.value && map.value <= map.high);
+        assert!(map.low < map.high);
+    }
+
+    #[test]
+    fn paired_comparison() {
+        let (gts, dets_a, dets_b) = models();
+        let a = COCOEval::new(&gts, &dets_a);
+        let b = COCOEval::new(&gts, &dets_b);
+
+        let cmp = a.compare(&b, &[0.5], 200, 0.9, 0);
+        assert_eq!(cmp.nb_imgs, 20);
+
+        let diff = cmp.map.as_ref().unwrap();
+        assert!(diff.diff.value < -0.4);
+        assert!(diff.diff.low <= diff.diff.value && diff.diff.value <= diff.diff.high);
+        assert_eq!(diff.prob_b_better, 0.0);
+        assert_eq!(cmp.labels["cat"].as_ref().unwrap().diff, diff.diff);
+
+        // Swapping the models flips the difference.
+        let swapped = b.compare(&a, &[0.5], 200, 0.9, 0);
+        let swapped = swapped.map.unwrap();
+        assert_eq!(swapped.diff.value, -diff.diff.value);
+        assert_eq!(swapped.prob_b_better, 1.0);
+
+        // A model without predictions loses every sample.
+        let no_dets = AnnSet::new();
+        let diff = a.compare(&COCOEval::new(&gts, &no_dets), &[0.5], 50, 0.9, 0).map.unwrap();
+        assert_eq!((diff.diff.value, diff.diff.low, diff.diff.high), (-1.0, -1.0, -1.0));
+        assert_eq!(diff.prob_b_better, 0.0);
+    }
+
+    #[test]
+    fn undefined_map() {
+        let empty = AnnSet::new();
+        let eval = COCOEval::new(&empty, &empty);
+        assert_eq!(eval.bootstrap(&[0.5], 50, 0.9, 0).map, None);
+
+        // Undefined values are left empty in the mean row.
+        let cmp = eval.compare(&eval, &[0.5], 50, 0.9, 0);
+        assert_eq!(cmp.to_csv().lines().nth(1), Some(",,,,,,,,,,"));
+    }
+
+    #[test]
+    fn seeded() {
+        let (gts, dets_a, dets_b) = models();
+        let a = COCOEval::new(&gts, &dets_a);
+        let b = COCOEval::new(&gts, &dets_b);
+
+        let cmp = a.compare(&b, &[0.5], 200, 0.9, 0);
+        let again = a.compare(&b, &[0.5], 200, 0.9, 0);
+        assert_eq!(again.map.unwrap().diff, cmp.map.unwrap().diff);
+
+        let eval = b.bootstrap(&[0.5], 200, 0.9, 1);
+        let again = b.bootstrap(&[0.5], 200, 0.9, 1);
+        assert_eq!(again.map, eval.map);
+    }
+
+    #[test]
+    #[should_panic]
+    fn no_samples() {
+        let (gts, dets_a, _) = models();
+        COCOEval::new(&gts, &dets_a).bootstrap(&[0.5], 0, 0.9, 0);
+    }
+}
@@ -0,0 +1,549 @@
+use super::{
+    coco::{COCOEval, COCOSummary, ImgEval, IOU_THRESHOLDS, MAX_DETS},
+    params::{AreaRange, EvalParams, ParamsEval},
+};
+
+use crate::{
+    bbox::BBox,
+    converters::ConvError,
+    parsers::ParseError,
+};
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, hash_map::Entry},
+    fs,
+    path::Path,
+};
+
+/// The first bytes of cache files.
+const MAGIC: &[u8; 8] = b"GLBXEVAL";
+
+/// The version of the cache file format and of the cache keys, to bump
+/// whenever either the matching or the file layout changes.
+const VERSION: u32 = 1;
+
+/// Marks an unmatched detection in cache files.
+const NO_MATCH: u32 = u32::MAX;
+
+/// The 64-bit FNV-1a hash, stable across runs and platforms unlike the
+/// hasher of the standard library.
+#[derive(Debug, Clone)]
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(0xcbf29ce484222325)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.bytes(&value.to_bits().to_le_bytes());
+    }
+
+    fn str(&mut self, value: &str) {
+        self.u64(value.len() as u64);
+        self.bytes(value.as_bytes());
+    }
+
+    fn bbox(&mut self, bbox: &BBox) {
+        let (xmin, ymin, xmax, ymax) = bbox.ltrb();
+
+        self.str(&bbox.label);
+        [xmin, ymin, xmax, ymax].into_iter().for_each(|c| self.f32(c));
+        self.f32(bbox.conf().unwrap_or(-1.0));
+
+        self.u64(bbox.attributes.len() as u64);
+        for (name, value) in &bbox.attributes {
+            self.str(name);
+            self.str(value);
+        }
+    }
+}
+
+/// Per-image matching results, reused across evaluations of images whose
+/// ground truths and detections did not change.
+///
+/// Entries are keyed by a hash of the image bounding boxes and of the
+/// evaluation parameters.
+#[derive(Debug, Clone, Default)]
+pub struct EvalCache {
+    entries: HashMap<u64, Vec<(String, ImgEval)>>,
+    used: HashSet<u64>,
+    hits: usize,
+    misses: usize,
+}
+
+impl EvalCache {
+    pub fn new() -> Self {
+        EvalCache::default()
+    }
+
+    /// The number of cached image matchings.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The number of image matchings reused since the cache was created.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// The number of image matchings computed since the cache was created.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Drops the entries unused since the cache was created, such as those
+    /// of images whose predictions changed.
+    pub fn prune(&mut self) {
+        self.entries.retain(|key, _| self.used.contains(key));
+    }
+
+    /// Reads a cache saved with `EvalCache::save`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ParseError> {
+        let bytes = fs::read(path).map_err(|_| ParseError {})?;
+
+        EvalCache::from_bytes(&bytes)
+    }
+
+    /// Saves the cache to a compact binary file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConvError> {
+        fs::write(path, self.to_bytes()).map_err(|_| ConvError {})
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        let push_u32 = |bytes: &mut Vec<u8>, value: usize| bytes.extend((value as u32).to_le_bytes());
+
+        push_u32(&mut bytes, VERSION as usize);
+        bytes.extend((self.entries.len() as u64).to_le_bytes());
+
+        // Sorted for identical files from identical caches.
+        let mut keys: Vec<u64> = self.entries.keys().copied().collect();
+        keys.sort_unstable();
+
+        for key in keys {
+            let labels = &self.entries[&key];
+
+            bytes.extend(key.to_le_bytes());
+            push_u32(&mut bytes, labels.len());
+
+            for (label, eval) in labels {
+                push_u32(&mut bytes, label.len());
+                bytes.extend(label.as_bytes());
+
+                push_u32(&mut bytes, eval.matches.len());
+                push_u32(&mut bytes, eval.scores.len());
+                push_u32(&mut bytes, eval.gt_ignored.len());
+                push_u32(&mut bytes, eval.nb_gts);
+
+                eval.scores.iter().for_each(|s| bytes.extend(s.to_le_bytes()));
+
+                for &m in eval.matches.iter().flatten() {
+                    push_u32(&mut bytes, m.unwrap_or(NO_MATCH as usize));
+                }
+
+                bytes.extend(eval.ignored.iter().flatten().map(|&i| i as u8));
+                bytes.extend(eval.gt_ignored.iter().map(|&i| i as u8));
+            }
+        }
+
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        let mut reader = Reader { bytes };
+
+        if reader.take(MAGIC.len())? != MAGIC || reader.u32()? != VERSION as usize {
+            return Err(ParseError {})
+        }
+
+        let nb_entries = reader.u64()?;
+        let mut entries = HashMap::new();
+
+        for _ in 0..nb_entries {
+            let key = reader.u64()?;
+            let nb_labels = reader.u32()?;
+            let mut labels = vec![];
+
+            for _ in 0..nb_labels {
+                let len = reader.u32()?;
+                let label = String::from_utf8(reader.take(len)?.to_vec())
+                    .map_err(|_| ParseError {})?;
+
+                let (nb_threshs, nb_dets) = (reader.u32()?, reader.u32()?);
+                let (nb_all_gts, nb_gts) = (reader.u32()?, reader.u32()?);
+
+                let scores = (0..nb_dets)
+                    .map(|_| reader.f32())
+                    .collect::<Result<_, _>>()?;
+
+                let matches = (0..nb_threshs)
+                    .map(|_| (0..nb_dets)
+                        .map(|_| {
+                            let m = reader.u32()?;
+                            match m == NO_MATCH as usize {
+                                true => Ok(None),
+                                false if m < nb_all_gts => Ok(Some(m)),
+                                false => Err(ParseError {}),
+                            }
+                        })
+                        .collect())
+                    .collect::<Result<_, _>>()?;
+
+                let ignored = (0..nb_threshs)
+                    .map(|_| reader.flags(nb_dets))
+                    .collect::<Result<_, _>>()?;
+
+                let gt_ignored = reader.flags(nb_all_gts)?;
+
+                labels.push((label, ImgEval { scores, matches, ignored, gt_ignored, nb_gts }));
+            }
+
+            entries.insert(key, labels);
+        }
+
+        if !reader.bytes.is_empty() {
+            return Err(ParseError {})
+        }
+
+        Ok(EvalCache { entries, ..EvalCache::default() })
+    }
+}
+
+/// Reads little-endian values from a byte slice.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ParseError> {
+        if len > self.bytes.len() {
+            return Err(ParseError {})
+        }
+
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<usize, ParseError> {
+        let bytes = self.take(4)?.try_into().map_err(|_| ParseError {})?;
+        Ok(u32::from_le_bytes(bytes) as usize)
+    }
+
+    fn u64(&mut self) -> Result<u64, ParseError> {
+        let bytes = self.take(8)?.try_into().map_err(|_| ParseError {})?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn f32(&mut self) -> Result<f32, ParseError> {
+        let bytes = self.take(4)?.try_into().map_err(|_| ParseError {})?;
+        Ok(f32::from_le_bytes(bytes))
+    }
+
+    fn flags(&mut self, len: usize) -> Result<Vec<bool>, ParseError> {
+        self.take(len)?.iter()
+            .map(|&b| match b {
+                0 => Ok(false),
+                1 => Ok(true),
+                _ => Err(ParseError {}),
+            })
+            .collect()
+    }
+}
+
+impl COCOEval<'_> {
+    /// Same as `COCOEval::eval_all` but only matches the images absent
+    /// from the cache, adding them to it.
+    pub fn eval_all_cached(&self, cache: &mut EvalCache) -> COCOSummary {
+        let max_det = MAX_DETS[MAX_DETS.len() - 1];
+
+        Self::summarize(|range| self.match_cached(&IOU_THRESHOLDS, &BTreeMap::new(), range, max_det, cache))
+    }
+
+    /// Same as `COCOEval::eval_params` but only matches the images absent
+    /// from the cache, adding them to it.
+    ///
+    /// # Panics
+    /// Will panic if the parameters are invalid.
+    pub fn eval_params_cached(&self, params: &EvalParams, cache: &mut EvalCache) -> ParamsEval {
+        assert!(params.is_valid(), "invalid evaluation parameters");
+
+        Self::summarize_params(params, |range| {
+            self.match_cached(&params.iou_thresholds, &params.label_iou_thresholds, range, params.max_det(), cache)
+        })
+    }
+
+    /// Same as `COCOEval::match_range` on the groups of `COCOEval::group`,
+    /// reusing the cached images.
+    fn match_cached(
+        &self,
+        threshs: &[f32],
+        label_threshs: &BTreeMap<String, f32>,
+        range: &AreaRange,
+        max_det: usize,
+        cache: &mut EvalCache,
+    ) -> BTreeMap<String, Vec<ImgEval>> {
+        let params = self.params_hash(threshs, label_threshs, range, max_det);
+        let mut evals = BTreeMap::<String, Vec<ImgEval>>::new();
+
+        for img_id in self.img_ids() {
+            let key = self.img_hash(params.clone(), img_id);
+            cache.used.insert(key);
+
+            let labels = match cache.entries.entry(key) {
+                Entry::Occupied(entry) => {
+                    cache.hits += 1;
+                    entry.into_mut()
+                },
+                Entry::Vacant(entry) => {
+                    cache.misses += 1;
+
+                    let labels = self.group_img(img_id, max_det).into_iter()
+                        .map(|(label, img)| {
+                            let label_threshs = label_threshs.get(label)
+                                .map(|&t| vec![t; threshs.len()]);
+                            let threshs = label_threshs.as_deref().unwrap_or(threshs);
+
+                            (label.to_string(), Self::match_img(&img, threshs, range))
+                        })
+                        .collect();
+
+                    entry.insert(labels)
+                },
+            };
+
+            for (label, eval) in labels.iter() {
+                evals.entry(label.clone()).or_default().push(eval.clone());
+            }
+        }
+
+        evals
+    }
+
+    fn params_hash(
+        &self,
+        threshs: &[f32],
+        label_threshs: &BTreeMap<String, f32>,
+        range: &AreaRange,
+        max_det: usize,
+    ) -> Fnv {
+        let mut hash = Fnv::new();
+
+        hash.u64(VERSION as u64);
+        hash.u64(self.iou_kind as u64);
+
+        let mut ignore_labels: Vec<&String> = self.ignore_labels.iter().collect();
+        ignore_labels.sort_unstable();
+        hash.u64(ignore_labels.len() as u64);
+        ignore_labels.into_iter().for_each(|l| hash.str(l));
+
+        hash.u64(threshs.len() as u64);
+        threshs.iter().for_each(|&t| hash.f32(t));
+
+        hash.u64(label_threshs.len() as u64);
+        for (label, &thresh) in label_threshs {
+            hash.str(label);
+            hash.f32(thresh);
+        }
+
+        hash.f32(range.min);
+        hash.f32(range.max);
+        hash.u64(range.relative as u64);
+        hash.u64(max_det as u64);
+
+        hash
+    }
+
+    fn img_hash(&self, mut hash: Fnv, img_id: &str) -> u64 {
+        hash.str(img_id);
+
+        for anns in [self.gts, self.dets] {
+            match anns.get(img_id) {
+                Some(ann) => {
+                    let (width, height) = ann.img_size.map_or((0, 0), |s| (s.width, s.height));
+                    hash.u64(ann.img_size.is_some() as u64);
+                    hash.u64(width as u64);
+                    hash.u64(height as u64);
+
+                    hash.u64(ann.bboxes.len() as u64);
+                    ann.bboxes.iter().for_each(|b| hash.bbox(b));
+                },
+                None => hash.u64(u64::MAX),
+            }
+        }
+
+        hash.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{annotation::Ann, annotationset::AnnSet, bbox::BBox, evaluators::cache::*, path::test_dir};
+
+    fn cats(conf: Option<f32>) -> AnnSet {
+        AnnSet::from_iter((0..4).map(|i| {
+            Ann::new(format!("{i}"), None, vec![BBox::new("cat", 0.0, 0.0, 10.0, 10.0, conf)])
+        }))
+    }
+
+    /// A cache of the 4 images of `cats` for each of the 4 area ranges.
+    fn filled() -> EvalCache {
+        let mut cache = EvalCache::new();
+        COCOEval::new(&cats(None), &cats(Some(0.9))).eval_all_cached(&mut cache);
+        cache
+    }
+
+    #[test]
+    fn misses_then_hits() {
+        let (gts, dets) = (cats(None), cats(Some(0.9)));
+        let evaluator = COCOEval::new(&gts, &dets);
+        let mut cache = EvalCache::new();
+
+        let summary = evaluator.eval_all_cached(&mut cache);
+        assert_eq!(summary.ap.ap, Some(1.0));
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (0, 4 * 4, 4 * 4));
+
+        let summary = evaluator.eval_all_cached(&mut cache);
+        assert_eq!(summary.ap.ap, Some(1.0));
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (4 * 4, 4 * 4, 4 * 4));
+
+        // Images without predictions are cached too.
+        let no_dets = AnnSet::new();
+        let summary = COCOEval::new(&gts, &no_dets).eval_all_cached(&mut cache);
+        assert_eq!(summary.ap.ap, Some(0.0));
+        assert_eq!(cache.len(), 2 * 4 * 4);
+    }
+
+    #[test]
+    fn changed_image() {
+        let gts = cats(None);
+        let mut dets = cats(Some(0.9));
+        // Read back so that no entry is marked as used yet.
+        let mut cache = EvalCache::from_bytes(&filled().to_bytes()).unwrap();
+
+        // Only the changed image is matched again.
+        dets.get_mut("2").unwrap().bboxes[0] = BBox::new("cat", 50.0, 50.0, 60.0, 60.0, Some(0.9));
+
+        let evaluator = COCOEval::new(&gts, &dets);
+        let cached = evaluator.eval_all_cached(&mut cache);
+        let expected = evaluator.eval_all();
+
+        assert_eq!((cache.hits(), cache.misses()), (3 * 4, 4));
+        assert_eq!(cached.ap.ap, expected.ap.ap);
+        assert_eq!(cached.ar_1.ar, expected.ar_1.ar);
+
+        // The entries of the previous predictions of the image are dropped.
+        assert_eq!(cache.len(), 5 * 4);
+        cache.prune();
+        assert_eq!(cache.len(), 4 * 4);
+    }
+
+    #[test]
+    fn custom_params() {
+        let (gts, dets) = (cats(None), cats(Some(0.9)));
+        let evaluator = COCOEval::new(&gts, &dets);
+        let params = EvalParams { iou_thresholds: vec![0.5], ..EvalParams::default() };
+        let mut cache = filled();
+        let nb_entries = cache.len();
+
+        let cached = evaluator.eval_params_cached(&params, &mut cache);
+        let expected = evaluator.eval_params(&params);
+
+        // The parameters are part of the keys.
+        assert!(cache.len() > nb_entries);
+        assert_eq!(cache.hits(), 0);
+
+        for (cached, expected) in cached.results.iter().zip(&expected.results) {
+            assert_eq!(cached.result.ap, expected.result.ap);
+            assert_eq!(cached.result.ar, expected.result.ar);
+        }
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let cache = filled();
+        let bytes = cache.to_bytes();
+        let mut read = EvalCache::from_bytes(&bytes).unwrap();
+
+        assert_eq!(read.len(), cache.len());
+        assert_eq!((read.hits(), read.misses()), (0, 0));
+        assert_eq!(read.to_bytes(), bytes);
+
+        COCOEval::new(&cats(None), &cats(Some(0.9))).eval_all_cached(&mut read);
+        assert_eq!((read.hits(), read.misses()), (4 * 4, 0));
+
+        // An empty cache is only a header.
+        let bytes = EvalCache::new().to_bytes();
+        assert_eq!(bytes.len(), MAGIC.len() + 4 + 8);
+        assert!(EvalCache::from_bytes(&bytes).unwrap().is_empty());
+    }
+
+    #[test]
+    fn file_round_trip() {
+        let dir = test_dir("cache");
+        let path = dir.join("eval.cache");
+
+        let cache = filled();
+        cache.save(&path).unwrap();
+
+        let read = EvalCache::from_file(&path).unwrap();
+        assert_eq!(read.to_bytes(), cache.to_bytes());
+
+        assert!(EvalCache::from_file(dir.join("missing.cache")).is_err());
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = filled().to_bytes();
+
+        assert!(EvalCache::from_bytes(b"").is_err());
+        assert!(EvalCache::from_bytes(MAGIC).is_err());
+
+        for len in [MAGIC.len() + 4, bytes.len() / 2, bytes.len() - 1] {
+            assert!(EvalCache::from_bytes(&bytes[..len]).is_err());
+        }
+
+        let dir = test_dir("cache-truncated");
+        let path = dir.join("eval.cache");
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(EvalCache::from_file(&path).is_err());
+    }
+
+    #[test]
+    fn corrupted() {
+        let bytes = filled().to_bytes();
+
+        let mut magic = bytes.clone();
+        magic[0] = b'X';
+        assert!(EvalCache::from_bytes(&magic).is_err());
+
+        let mut version = bytes.clone();
+        version[MAGIC.len()] += 1;
+        assert!(EvalCache::from_bytes(&version).is_err());
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(EvalCache::from_bytes(&trailing).is_err());
+
+        // The last byte is an ignored flag of a ground truth.
+        let mut flag = bytes;
+        *flag.last_mut().unwrap() = 2;
+        assert!(EvalCache::from_bytes(&flag).is_err());
+    }
+}
@@ -0,0 +1,848 @@
+use super::params::AreaRange;
+
+use crate::{
+    annotationset::AnnSet,
+    bbox::{BBox, CROWD_ATTR, DIFFICULT_ATTR},
+    evaluation::{IoUKind, IoUMatrix, ioa, iou_matrix_ref},
+    imgsize::ImgSize,
+};
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::{self, Display},
+};
+
+use serde::Serialize;
+
+/// The COCO area range of all objects.
+pub const AREA_ALL: (f32, f32) = (0.0, 1e10);
+
+/// The COCO area range of small objects.
+pub const AREA_SMALL: (f32, f32) = (0.0, 32.0 * 32.0);
+
+/// The COCO area range of medium objects.
+pub const AREA_MEDIUM: (f32, f32) = (32.0 * 32.0, 96.0 * 96.0);
+
+/// The COCO area range of large objects.
+pub const AREA_LARGE: (f32, f32) = (96.0 * 96.0, 1e10);
+
+/// The COCO IoU thresholds, from 0.5 to 0.95 with a step of 0.05.
+pub const IOU_THRESHOLDS: [f32; 10] = [0.5, 0.55, 0.6, 0.65, 0.7, 0.75, 0.8, 0.85, 0.9, 0.95];
+
+/// The COCO maximum numbers of detections per image.
+pub const MAX_DETS: [usize; 3] = [1, 10, 100];
+
+/// The number of recall thresholds used to interpolate precision.
+const NB_REC_THRESHOLDS: usize = 101;
+
+/// Evaluates detections against ground truths following the COCO
+/// evaluation protocol.
+/// 
+/// Bounding boxes without confidence in `dets` are considered to have
+/// a confidence of one.
+/// 
+/// Ground truths flagged with the `iscrowd` or `difficult` attribute and 
+/// ground truths with an ignore label are ignore regions, following the 
+/// COCO crowd semantics: they are matched by the fraction of the detection
+/// they cover (IoA), possibly by several detections, and neither count as
+/// missed nor make the detections matching them false positives. Ignore
+/// labels apply to detections of every label while flagged ground truths
+/// only apply to their own label.
+pub struct COCOEval<'e> {
+    pub(super) gts: &'e AnnSet,
+    pub(super) dets: &'e AnnSet,
+    pub(super) iou_kind: IoUKind,
+    pub(super) ignore_labels: HashSet<String>,
+}
+
+impl<'e> COCOEval<'e> {
+    pub fn new(gts: &'e AnnSet, dets: &'e AnnSet) -> COCOEval<'e> {
+        COCOEval { gts, dets, iou_kind: IoUKind::default(), ignore_labels: HashSet::new() }
+    }
+
+    /// Sets the overlap measure used to match detections with ground truths.
+    pub fn with_iou_kind(mut self, iou_kind: IoUKind) -> Self {
+        self.iou_kind = iou_kind;
+        self
+    }
+
+    /// Sets the labels of ground truth ignore regions, such as `DontCare`.
+    /// Detections with these labels are discarded.
+    pub fn with_ignore_labels<I, S>(mut self, labels: I) -> Self where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.ignore_labels = labels.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Whether the ground truth is an ignore region.
+    pub fn is_ignore_region(&self, gt: &BBox) -> bool {
+        let is_set = |attr| matches!(gt.attribute(attr), Some("1" | "true"));
+
+        self.ignore_labels.contains(&gt.label) || is_set(CROWD_ATTR) || is_set(DIFFICULT_ATTR)
+    }
+
+    /// Whether the ignore region `region` covers the detection `det`, 
+    /// given an IoA threshold.
+    pub(super) fn covers(&self, region: &BBox, det: &BBox, thresh: f32) -> bool {
+        let same_label = self.ignore_labels.contains(&region.label) || region.label == det.label;
+
+        same_label && ioa(det, region) >= thresh
+    }
+}
+
+/// The evaluation results of one label.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct LabelEval {
+    /// The Average Precision, `None` if the label has no ground truth.
+    pub ap: Option<f32>,
+
+    /// The Average Recall, `None` if the label has no ground truth.
+    pub ar: Option<f32>,
+
+    /// The number of ground truths in the area range.
+    pub nb_gts: usize,
+}
+
+/// The evaluation results for a given set of parameters.
+#[derive(Debug, Clone, Serialize)]
+pub struct EvalResult {
+    /// The mean Average Precision over labels with ground truths.
+    pub ap: Option<f32>,
+
+    /// The mean Average Recall over labels with ground truths.
+    pub ar: Option<f32>,
+
+    pub labels: BTreeMap<String, LabelEval>,
+}
+
+impl EvalResult {
+    fn new(labels: BTreeMap<String, LabelEval>) -> Self {
+        let mean = |values: Vec<f32>| {
+            (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+        };
+
+        EvalResult { 
+            ap: mean(labels.values().filter_map(|l| l.ap).collect()), 
+            ar: mean(labels.values().filter_map(|l| l.ar).collect()), 
+            labels,
+        }
+    }
+}
+
+/// The twelve standard COCO metrics.
+#[derive(Debug, Clone, Serialize)]
+pub struct COCOSummary {
+    pub ap: EvalResult,
+    pub ap_50: EvalResult,
+    pub ap_75: EvalResult,
+    pub ap_small: EvalResult,
+    pub ap_medium: EvalResult,
+    pub ap_large: EvalResult,
+    pub ar_1: EvalResult,
+    pub ar_10: EvalResult,
+    pub ar_100: EvalResult,
+    pub ar_small: EvalResult,
+    pub ar_medium: EvalResult,
+    pub ar_large: EvalResult,
+}
+
+/// The bounding boxes of one image and one label, ready to be matched.
+pub(super) struct ImgBoxes<'a> {
+    pub(super) img_id: &'a str,
+
+    /// The image size of the ground truths, else of the detections.
+    pub(super) img_size: Option<ImgSize>,
+
+    /// The ground truths of the label followed by the ignore regions of
+    /// every label.
+    pub(super) gts: Vec<&'a BBox>,
+
+    /// The indices of the ground truths in their annotation.
+    pub(super) gt_idxs: Vec<usize>,
+
+    /// Whether each ground truth is an ignore region.
+    pub(super) crowd: Vec<bool>,
+
+    /// Sorted by decreasing confidence.
+    pub(super) dets: Vec<&'a BBox>,
+
+    /// The indices of the detections in their annotation.
+    pub(super) det_idxs: Vec<usize>,
+
+    /// The overlaps of detections (rows) with ground truths (columns), the
+    /// IoA for ignore regions.
+    pub(super) ious: IoUMatrix,
+}
+
+/// The matching of the bounding boxes of one image and one label.
+#[derive(Debug, Clone)]
+pub(super) struct ImgEval {
+    /// The detection confidences, in decreasing order.
+    pub(super) scores: Vec<f32>,
+
+    /// For each IoU threshold, the ground truth matched by each detection.
+    pub(super) matches: Vec<Vec<Option<usize>>>,
+
+    /// For each IoU threshold, whether each detection is ignored.
+    pub(super) ignored: Vec<Vec<bool>>,
+
+    /// Whether each ground truth is ignored.
+    pub(super) gt_ignored: Vec<bool>,
+
+    /// The number of ground truths which are not ignored.
+    pub(super) nb_gts: usize,
+}
+
+impl COCOEval<'_> {
+    pub(super) fn validate_args(thresh: f32, sz_range: (f32, f32)) -> bool {
+        let (low, high) = sz_range;
+        
+        (0.0..=1.0).contains(&thresh) && 0.0 <= low && low <= high
+    }
+
+    /// Evaluates at a single IoU threshold, keeping at most `ndets` 
+    /// detections per image and label and only objects with an area 
+    /// in `sz_range`.
+    /// 
+    /// # Panics
+    /// Will panic if the threshold is not in `[0, 1]` or if the size
+    /// range is invalid.
+    pub fn eval(&self, thresh: f32, ndets: usize, sz_range: (f32, f32)) -> EvalResult {
+        self.eval_thresholds(&[thresh], ndets, sz_range)
+    }
+
+    /// Same as `COCOEval::eval` but averaged over several IoU thresholds.
+    pub fn eval_thresholds(&self, threshs: &[f32], ndets: usize, sz_range: (f32, f32)) -> EvalResult {
+        assert!(
+            threshs.iter().all(|&t| Self::validate_args(t, sz_range)), 
+            "invalid evaluation parameters"
+        );
+
+        let boxes = self.group(ndets);
+        let evals = self.match_all(&boxes, threshs, sz_range);
+
+        Self::metrics(&evals, 0..threshs.len(), ndets)
+    }
+
+    /// Computes the twelve standard COCO metrics.
+    pub fn eval_all(&self) -> COCOSummary {
+        let boxes = self.group(MAX_DETS[MAX_DETS.len() - 1]);
+
+        Self::summarize(|range| Self::match_range(&boxes, &IOU_THRESHOLDS, &BTreeMap::new(), range))
+    }
+
+    /// Computes the twelve standard COCO metrics from the matching of each
+    /// area range at the COCO IoU thresholds.
+    pub(super) fn summarize<K, F>(mut match_range: F) -> COCOSummary where
+        K: AsRef<str> + Ord,
+        F: FnMut(&AreaRange) -> BTreeMap<K, Vec<ImgEval>>,
+    {
+        let max_det = MAX_DETS[MAX_DETS.len() - 1];
+        let mut match_area = |(min, max)| match_range(&AreaRange::new("", min, max));
+
+        let all = match_area(AREA_ALL);
+        let small = match_area(AREA_SMALL);
+        let medium = match_area(AREA_MEDIUM);
+        let large = match_area(AREA_LARGE);
+
+        let thresh_idx = |t: f32| IOU_THRESHOLDS.iter().position(|&x| x == t).unwrap();
+        let (idx_50, idx_75) = (thresh_idx(0.5), thresh_idx(0.75));
+        let every = 0..IOU_THRESHOLDS.len();
+
+        COCOSummary {
+            ap: Self::metrics(&all, every.clone(), max_det),
+            ap_50: Self::metrics(&all, idx_50..idx_50 + 1, max_det),
+            ap_75: Self::metrics(&all, idx_75..idx_75 + 1, max_det),
+            ap_small: Self::metrics(&small, every.clone(), max_det),
+            ap_medium: Self::metrics(&medium, every.clone(), max_det),
+            ap_large: Self::metrics(&large, every.clone(), max_det),
+            ar_1: Self::metrics(&all, every.clone(), MAX_DETS[0]),
+            ar_10: Self::metrics(&all, every.clone(), MAX_DETS[1]),
+            ar_100: Self::metrics(&all, every.clone(), max_det),
+            ar_small: Self::metrics(&small, every.clone(), max_det),
+            ar_medium: Self::metrics(&medium, every.clone(), max_det),
+            ar_large: Self::metrics(&large, every, max_det),
+        }
+    }
+
+    /// The evaluated images, in the ground truth order followed by images
+    /// only present in the detections.
+    pub(super) fn img_ids(&self) -> impl Iterator<Item = &str> {
+        self.gts.iter()
+            .map(|ann| ann.img_id.as_str())
+            .chain(self.dets.iter()
+                .map(|ann| ann.img_id.as_str())
+                .filter(|img_id| !self.gts.contains(img_id)))
+    }
+
+    /// Groups the bounding boxes by label and image, keeping the `max_det` 
+    /// most confident detections of each group.
+    pub(super) fn group(&self, max_det: usize) -> BTreeMap<&str, Vec<ImgBoxes<'_>>> {
+        let mut groups = BTreeMap::<&str, Vec<ImgBoxes>>::new();
+
+        for img_id in self.img_ids() {
+            for (label, img) in self.group_img(img_id, max_det) {
+                groups.entry(label).or_default().push(img);
+            }
+        }
+
+        groups
+    }
+
+    /// Groups the bounding boxes of one image by label, in no particular 
+    /// order.
+    pub(super) fn group_img<'a>(&'a self, img_id: &'a str, max_det: usize) -> Vec<(&'a str, ImgBoxes<'a>)> {
+        let img_size = self.gts.get(img_id).and_then(|a| a.img_size)
+            .or_else(|| self.dets.get(img_id).and_then(|a| a.img_size));
+
+        type Indexed<'a> = Vec<(usize, &'a BBox)>;
+        let mut by_label = HashMap::<&str, (Indexed, Indexed)>::new();
+
+        let mut regions: Indexed = vec![];
+
+        for (i, bbox) in self.gts.get(img_id).iter().flat_map(|ann| ann.bboxes.iter().enumerate()) {
+            if self.ignore_labels.contains(&bbox.label) {
+                regions.push((i, bbox));
+            } else {
+                by_label.entry(bbox.label.as_str()).or_default().0.push((i, bbox));
+            }
+        }
+
+        for (i, bbox) in self.dets.get(img_id).iter().flat_map(|ann| ann.bboxes.iter().enumerate()) {
+            if !self.ignore_labels.contains(&bbox.label) {
+                by_label.entry(bbox.label.as_str()).or_default().1.push((i, bbox));
+            }
+        }
+
+        by_label.into_iter()
+            .map(|(label, (mut gts, mut dets))| {
+                gts.extend_from_slice(&regions);
+
+                // Stable sort to keep the detection order for equal confidences.
+                dets.sort_by(|a, b| Self::score(b.1).total_cmp(&Self::score(a.1)));
+                dets.truncate(max_det);
+
+                let (gt_idxs, gts): (Vec<usize>, Vec<&BBox>) = gts.into_iter().unzip();
+                let (det_idxs, dets): (Vec<usize>, Vec<&BBox>) = dets.into_iter().unzip();
+                let crowd: Vec<bool> = gts.iter().map(|gt| self.is_ignore_region(gt)).collect();
+
+                let mut ious = iou_matrix_ref(&dets, &gts, self.iou_kind);
+
+                for g in (0..gts.len()).filter(|&g| crowd[g]) {
+                    for (d, det) in dets.iter().enumerate() {
+                        ious.set(d, g, ioa(det, gts[g]));
+                    }
+                }
+
+                (label, ImgBoxes { img_id, img_size, gts, gt_idxs, crowd, dets, det_idxs, ious })
+            })
+            .collect()
+    }
+
+    pub(super) fn score(bbox: &BBox) -> f32 {
+        bbox.conf().unwrap_or(1.0)
+    }
+
+    pub(super) fn match_all<'a>(
+        &self, 
+        boxes: &BTreeMap<&'a str, Vec<ImgBoxes>>, 
+        threshs: &[f32], 
+        sz_range: (f32, f32),
+    ) -> BTreeMap<&'a str, Vec<ImgEval>> {
+        Self::match_range(boxes, threshs, &BTreeMap::new(), &AreaRange::new("", sz_range.0, sz_range.1))
+    }
+
+    /// Matches every group, labels in `label_threshs` being matched at 
+    /// their own threshold in place of each of `threshs`.
+    pub(super) fn match_range<'a>(
+        boxes: &BTreeMap<&'a str, Vec<ImgBoxes>>, 
+        threshs: &[f32], 
+        label_threshs: &BTreeMap<String, f32>,
+        range: &AreaRange,
+    ) -> BTreeMap<&'a str, Vec<ImgEval>> {
+        boxes.iter()
+            .map(|(&label, imgs)| {
+                let label_threshs = label_threshs.get(label)
+                    .map(|&t| vec![t; threshs.len()]);
+                let threshs = label_threshs.as_deref().unwrap_or(threshs);
+
+                let evals = imgs.iter()
+                    .map(|img| Self::match_img(img, threshs, range))
+                    .collect();
+                (label, evals)
+            })
+            .collect()
+    }
+
+    /// Greedily matches each detection, in decreasing confidence order, 
+    /// with the unmatched ground truth of highest overlap above the 
+    /// threshold. 
+    /// 
+    /// Ground truths outside the area range and ignore regions are ignored:
+    /// detections matching them are ignored as well, as are unmatched 
+    /// detections outside the area range. Ignore regions can be matched
+    /// by several detections.
+    pub(super) fn match_img(img: &ImgBoxes, threshs: &[f32], range: &AreaRange) -> ImgEval {
+        let gt_ignored: Vec<bool> = img.gts.iter()
+            .zip(&img.crowd)
+            .map(|(gt, &crowd)| crowd || !range.contains(gt, img.img_size))
+            .collect();
+
+        // Ground truths which are not ignored are matched first.
+        let mut order: Vec<usize> = (0..img.gts.len()).collect();
+        order.sort_by_key(|&g| gt_ignored[g]);
+
+        let mut matches = Vec::with_capacity(threshs.len());
+        let mut ignored = Vec::with_capacity(threshs.len());
+
+        for &thresh in threshs {
+            let mut gt_matched = vec![false; img.gts.len()];
+            let mut det_matches = vec![None; img.dets.len()];
+            let mut det_ignored = vec![false; img.dets.len()];
+
+            for (d, det) in img.dets.iter().enumerate() {
+                let ious = img.ious.row(d);
+                let mut best = thresh;
+                let mut matched: Option<usize> = None;
+
+                for &g in &order {
+                    if gt_matched[g] && !img.crowd[g] {
+                        continue
+                    }
+
+                    // A regular ground truth was already found, ignored
+                    // ones come next.
+                    if matched.is_some_and(|m| !gt_ignored[m]) && gt_ignored[g] {
+                        break
+                    }
+
+                    if ious[g] < best {
+                        continue
+                    }
+
+                    best = ious[g];
+                    matched = Some(g);
+                }
+
+                match matched {
+                    Some(g) => {
+                        gt_matched[g] = true;
+                        det_matches[d] = Some(g);
+                        det_ignored[d] = gt_ignored[g];
+                    },
+                    None => det_ignored[d] = !range.contains(det, img.img_size),
+                }
+            }
+
+            matches.push(det_matches);
+            ignored.push(det_ignored);
+        }
+
+        ImgEval { 
+            scores: img.dets.iter().map(|d| Self::score(d)).collect(), 
+            matches, 
+            ignored, 
+            nb_gts: gt_ignored.iter().filter(|&&i| !i).count(),
+            gt_ignored,
+        }
+    }
+
+    /// Computes the per-label metrics averaged over the given thresholds.
+    pub(super) fn metrics<K: AsRef<str>>(
+        evals: &BTreeMap<K, Vec<ImgEval>>, 
+        thresh_idxs: std::ops::Range<usize>, 
+        max_det: usize,
+    ) -> EvalResult {
+        let labels = evals.iter()
+            .map(|(label, imgs)| {
+                let nb_gts = imgs.iter().map(|img| img.nb_gts).sum();
+
+                let (mut aps, mut ars) = (vec![], vec![]);
+                for t in thresh_idxs.clone() {
+                    if let Some((ap, ar)) = Self::accumulate(imgs, t, max_det) {
+                        aps.push(ap);
+                        ars.push(ar);
+                    }
+                }
+
+                let mean = |values: Vec<f32>| {
+                    (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+                };
+
+                (label.as_ref().to_string(), LabelEval { ap: mean(aps), ar: mean(ars), nb_gts })
+            })
+            .collect();
+
+        EvalResult::new(labels)
+    }
+
+    /// The confidence of the detections which are not ignored and whether 
+    /// they are true positives, in decreasing confidence order.
+    pub(super) fn ranked_dets(imgs: &[ImgEval], thresh_idx: usize, max_det: usize) -> Vec<(f32, bool)> {
+        let mut dets: Vec<(f32, bool)> = imgs.iter()
+            .flat_map(|img| {
+                let nb_dets = img.scores.len().min(max_det);
+
+                (0..nb_dets)
+                    .filter(|&d| !img.ignored[thresh_idx][d])
+                    .map(|d| (img.scores[d], img.matches[thresh_idx][d].is_some()))
+            })
+            .collect();
+
+        dets.sort_by(|a, b| b.0.total_cmp(&a.0));
+        dets
+    }
+
+    /// Computes the Average Precision, using the COCO 101-point interpolation,
+    /// and the recall of one label at one IoU threshold. 
+    /// 
+    /// Returns `None` if the label has no ground truth.
+    fn accumulate(imgs: &[ImgEval], thresh_idx: usize, max_det: usize) -> Option<(f32, f32)> {
+        let nb_gts: usize = imgs.iter().map(|img| img.nb_gts).sum();
+
+        if nb_gts == 0 {
+            return None
+        }
+
+        let dets = Self::ranked_dets(imgs, thresh_idx, max_det);
+
+        Some(average_precision(&dets, nb_gts))
+    }
+}
+
+/// Computes the Average Precision, using the COCO 101-point interpolation,
+/// and the recall from detections sorted by decreasing confidence with 
+/// whether they are true positives.
+/// 
+/// `nb_gts` must not be zero.
+pub(super) fn average_precision(dets: &[(f32, bool)], nb_gts: usize) -> (f32, f32) {
+    let (mut tp, mut fp) = (0usize, 0usize);
+    let mut recalls = Vec::with_capacity(dets.len());
+    let mut precisions = Vec::with_capacity(dets.len());
+
+    for &(_, is_tp) in dets {
+        if is_tp { tp += 1 } else { fp += 1 }
+
+        recalls.push(tp as f32 / nb_gts as f32);
+        precisions.push(tp as f32 / (tp + fp) as f32);
+    }
+
+    // Make precision monotonically decreasing.
+    for i in (1..precisions.len()).rev() {
+        precisions[i - 1] = precisions[i - 1].max(precisions[i]);
+    }
+
+    let ap = (0..NB_REC_THRESHOLDS)
+        .map(|i| {
+            let rec_thresh = i as f32 / (NB_REC_THRESHOLDS - 1) as f32;
+            let idx = recalls.partition_point(|&r| r < rec_thresh);
+            precisions.get(idx).copied().unwrap_or(0.0)
+        })
+        .sum::<f32>() / NB_REC_THRESHOLDS as f32;
+
+    let recall = recalls.last().copied().unwrap_or(0.0);
+
+    (ap, recall)
+}
+
+impl COCOSummary {
+    /// The metrics with their description, whether they are precisions and 
+    /// their IoU, area range and maximum detections.
+    fn rows(&self) -> [(&'static str, &EvalResult, bool, &'static str, &'static str, usize); 12] {
+        [
+            ("ap", &self.ap, true, "0.50:0.95", "all", 100),
+            ("ap_50", &self.ap_50, true, "0.50", "all", 100),
+            ("ap_75", &self.ap_75, true, "0.75", "all", 100),
+            ("ap_small", &self.ap_small, true, "0.50:0.95", "small", 100),
+            ("ap_medium", &self.ap_medium, true, "0.50:0.95", "medium", 100),
+            ("ap_large", &self.ap_large, true, "0.50:0.95", "large", 100),
+            ("ar_1", &self.ar_1, false, "0.50:0.95", "all", 1),
+            ("ar_10", &self.ar_10, false, "0.50:0.95", "all", 10),
+            ("ar_100", &self.ar_100, false, "0.50:0.95", "all", 100),
+            ("ar_small", &self.ar_small, false, "0.50:0.95", "small", 100),
+            ("ar_medium", &self.ar_medium, false, "0.50:0.95", "medium", 100),
+            ("ar_large", &self.ar_large, false, "0.50:0.95", "large", 100),
+        ]
+    }
+
+    /// Exports the metrics as CSV with one row per label and one column
+    /// per metric. The first row, with an empty label, holds the means
+    /// over labels. Undefined values are left empty.
+    pub fn to_csv(&self) -> String {
+        let rows = self.rows();
+        let fmt = |v: Option<f32>| v.map(|v| v.to_string()).unwrap_or_default();
+
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        writer.write_record(std::iter::once("label").chain(rows.iter().map(|r| r.0)))
+            .expect("writing to memory should not fail");
+
+        writer.write_record(std::iter::once(String::new())
+            .chain(rows.iter().map(|&(_, res, is_ap, ..)| fmt(if is_ap { res.ap } else { res.ar }))))
+            .expect("writing to memory should not fail");
+
+        for label in self.ap.labels.keys() {
+            let values = rows.iter().map(|&(_, res, is_ap, ..)| {
+                let eval = res.labels.get(label);
+                fmt(eval.and_then(|e| if is_ap { e.ap } else { e.ar }))
+            });
+
+            writer.write_record(std::iter::once(label.clone()).chain(values))
+                .expect("writing to memory should not fail");
+        }
+
+        let bytes = writer.into_inner()
+            .expect("writing to memory should not fail");
+
+        String::from_utf8(bytes).expect("CSV should be valid UTF-8")
+    }
+}
+
+pub(super) fn fmt_metric(value: Option<f32>) -> String {
+    value.map_or(String::from("-"), |v| format!("{v:.3}"))
+}
+
+impl Display for COCOSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (_, res, is_ap, iou, area, max_det) in self.rows() {
+            let (title, value) = if is_ap { 
+                ("Average Precision  (AP)", res.ap) 
+            } else { 
+                ("Average Recall     (AR)", res.ar) 
+            };
+
+            writeln!(
+                f, " {title} @[ IoU={iou:<9} | area={area:>6} | maxDets={max_det:>3} ] = {}", 
+                fmt_metric(value),
+            )?;
+        }
+
+        writeln!(
+            f, "\n{:<24} {:>10} {:>10} {:>10} {:>10} {:>10}", 
+            "Label", "Boxes", "AP", "AP50", "AP75", "AR100",
+        )?;
+
+        for (label, eval) in &self.ap.labels {
+            let get = |res: &EvalResult, is_ap: bool| {
+                let eval = res.labels.get(label)?;
+                if is_ap { eval.ap } else { eval.ar }
+            };
+
+            writeln!(
+                f, "{:<24} {:>10} {:>10} {:>10} {:>10} {:>10}", 
+                label, 
+                eval.nb_gts,
+                fmt_metric(eval.ap), 
+                fmt_metric(get(&self.ap_50, true)),
+                fmt_metric(get(&self.ap_75, true)), 
+                fmt_metric(get(&self.ar_100, false)),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        evaluators::coco::*,
+        annotation::Ann,
+    };
+
+    fn gts() -> AnnSet {
+        AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None),
+                BBox::new("dog", 20.0, 20.0, 40.0, 40.0, None),
+            ]),
+            Ann::new("b.jpg", None, vec![
+                BBox::new("cat", 0.0, 0.0, 100.0, 100.0, None),
+            ]),
+        ])
+    }
+
+    #[test]
+    fn perfect() {
+        let gts = gts();
+        let summary = COCOEval::new(&gts, &gts).eval_all();
+
+        assert_eq!(summary.ap.ap, Some(1.0));
+        assert_eq!(summary.ar_100.ar, Some(1.0));
+        assert_eq!(summary.ap_small.labels["cat"].nb_gts, 1);
+        assert_eq!(summary.ap_large.ap, Some(1.0));
+        assert_eq!(summary.ap_medium.ap, None);
+    }
+
+    #[test]
+    fn matching() {
+        let gts = gts();
+        let dets = AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![
+                // Duplicate with a lower confidence, thus a false positive.
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.6)),
+                BBox::new("cat", 0.0, 0.0, 10.0, 12.0, Some(0.9)),
+                // Wrong label.
+                BBox::new("cat", 20.0, 20.0, 40.0, 40.0, Some(0.8)),
+            ]),
+            Ann::new("c.jpg", None, vec![
+                BBox::new("dog", 0.0, 0.0, 10.0, 10.0, Some(0.5)),
+            ]),
+        ]);
+
+        let evaluator = COCOEval::new(&gts, &dets);
+        let result = evaluator.eval(0.5, 100, AREA_ALL);
+
+        // Cat: TP, FP, TP with recall 1/2 then FP.
+        let cat = result.labels["cat"];
+        assert_eq!(cat.nb_gts, 2);
+        assert_eq!(cat.ar, Some(0.5));
+        assert_eq!(cat.ap, Some(51.0 / 101.0));
+
+        let dog = result.labels["dog"];
+        assert_eq!((dog.ap, dog.ar), (Some(0.0), Some(0.0)));
+
+        // Only the most confident detection is kept.
+        let result = evaluator.eval(0.5, 1, AREA_ALL);
+        assert_eq!(result.labels["cat"].ap, Some(51.0 / 101.0));
+
+        // IoU of the first detection is 10/12.
+        let result = evaluator.eval(0.85, 100, AREA_ALL);
+        assert!(result.labels["cat"].ap.unwrap() < 51.0 / 101.0);
+    }
+
+    /// A cat ground truth with the given attribute.
+    fn cat_region(attr: &str, value: &str, ltrb: (f32, f32, f32, f32)) -> BBox {
+        let mut bbox = BBox::new("cat", ltrb.0, ltrb.1, ltrb.2, ltrb.3, None);
+        bbox.set_attribute(attr, value);
+        bbox
+    }
+
+    #[test]
+    fn crowd_regions() {
+        let gts = AnnSet::from_iter([
+            Ann::new("a", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None),
+                cat_region(CROWD_ATTR, "1", (50.0, 50.0, 100.0, 100.0)),
+            ]),
+        ]);
+
+        // A true positive and two detections in the crowd region, ranked
+        // first, which can both match it.
+        let dets = AnnSet::from_iter([
+            Ann::new("a", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.5)),
+                BBox::new("cat", 60.0, 60.0, 70.0, 70.0, Some(0.9)),
+                BBox::new("cat", 70.0, 70.0, 80.0, 80.0, Some(0.8)),
+            ]),
+        ]);
+
+        let result = COCOEval::new(&gts, &dets).eval(0.5, 100, AREA_ALL);
+        assert_eq!(result.labels["cat"].nb_gts, 1);
+        assert_eq!(result.labels["cat"].ap, Some(1.0));
+    }
+
+    #[test]
+    fn difficult_ground_truths() {
+        let gts = AnnSet::from_iter([
+            Ann::new("a", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None),
+                cat_region(DIFFICULT_ATTR, "true", (50.0, 50.0, 60.0, 60.0)),
+            ]),
+        ]);
+
+        // A missed difficult ground truth is not a false negative.
+        let dets = AnnSet::from_iter([
+            Ann::new("a", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.5)),
+            ]),
+        ]);
+
+        let evaluator = COCOEval::new(&gts, &dets);
+        assert!(evaluator.is_ignore_region(&gts.get("a").unwrap().bboxes[1]));
+
+        let result = evaluator.eval(0.5, 100, AREA_ALL);
+        assert_eq!(result.labels["cat"].nb_gts, 1);
+        assert_eq!(result.labels["cat"].ar, Some(1.0));
+
+        // A detection of it is not a false positive either.
+        let dets = AnnSet::from_iter([
+            Ann::new("a", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.5)),
+                BBox::new("cat", 50.0, 50.0, 60.0, 60.0, Some(0.9)),
+            ]),
+        ]);
+
+        let result = COCOEval::new(&gts, &dets).eval(0.5, 100, AREA_ALL);
+        assert_eq!(result.labels["cat"].ap, Some(1.0));
+    }
+
+    #[test]
+    fn ignore_labels() {
+        let gts = AnnSet::from_iter([
+            Ann::new("a", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None),
+                BBox::new("DontCare", 0.0, 50.0, 40.0, 100.0, None),
+            ]),
+        ]);
+
+        // A true positive, one detection in the `DontCare` region and one 
+        // `DontCare` detection.
+        let dets = AnnSet::from_iter([
+            Ann::new("a", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.5)),
+                BBox::new("cat", 5.0, 55.0, 15.0, 65.0, Some(0.7)),
+                BBox::new("DontCare", 0.0, 0.0, 5.0, 5.0, Some(0.6)),
+            ]),
+        ]);
+
+        let result = COCOEval::new(&gts, &dets)
+            .with_ignore_labels(["DontCare"])
+            .eval(0.5, 100, AREA_ALL);
+
+        assert_eq!(result.labels.len(), 1);
+        assert_eq!(result.labels["cat"].ap, Some(1.0));
+
+        // Ignore labels are regular labels otherwise.
+        let result = COCOEval::new(&gts, &dets).eval(0.5, 100, AREA_ALL);
+        assert!(result.labels["cat"].ap.unwrap() < 1.0);
+        assert!(result.labels.contains_key("DontCare"));
+    }
+
+    #[test]
+    fn confusion_matrix_regions() {
+        let gts = AnnSet::from_iter([
+            Ann::new("a", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None),
+                cat_region(CROWD_ATTR, "1", (50.0, 50.0, 100.0, 100.0)),
+            ]),
+        ]);
+
+        let dets = AnnSet::from_iter([
+            Ann::new("a", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.5)),
+                BBox::new("cat", 60.0, 60.0, 70.0, 70.0, Some(0.9)),
+            ]),
+        ]);
+
+        let matrix = COCOEval::new(&gts, &dets).confusion_matrix(0.5, 0.0);
+        assert_eq!(matrix.get(None, Some("cat")), 0);
+        assert_eq!(matrix.get(Some("cat"), Some("cat")), 1);
+        assert_eq!(matrix.get(Some("cat"), None), 0);
+    }
+
+    #[test]
+    fn only_ignore_regions() {
+        let gts = AnnSet::from_iter([
+            Ann::new("a", None, vec![
+                cat_region(CROWD_ATTR, "1", (50.0, 50.0, 100.0, 100.0)),
+            ]),
+        ]);
+
+        let result = COCOEval::new(&gts, &AnnSet::new()).eval(0.5, 100, AREA_ALL);
+        assert_eq!(result.ap, None);
+        assert!(result.labels.values().all(|l| l.nb_gts == 0 && l.ap.is_none()));
+    }
+}
@@ -0,0 +1,277 @@
+use super::coco::COCOEval;
+
+use crate::{
+    bbox::BBox,
+    evaluation::iou_matrix_ref,
+};
+
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+};
+
+use serde::Serialize;
+
+/// A detection confusion matrix, where rows are ground truth labels and
+/// columns are predicted labels.
+/// 
+/// The last row and column are the background: the background row counts
+/// the false positives and the background column the missed ground truths.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfusionMatrix {
+    pub iou_thresh: f32,
+    pub conf_thresh: f32,
+
+    /// The sorted labels, excluding the background.
+    pub labels: Vec<String>,
+
+    /// The counts, indexed by ground truth label then predicted label.
+    pub counts: Vec<Vec<usize>>,
+}
+
+impl ConfusionMatrix {
+    fn index(&self, label: Option<&str>) -> Option<usize> {
+        match label {
+            Some(label) => self.labels.binary_search_by(|l| l.as_str().cmp(label)).ok(),
+            None => Some(self.labels.len()),
+        }
+    }
+
+    /// The number of ground truths with label `gt` matched by predictions
+    /// with label `det`, `None` standing for the background.
+    pub fn get(&self, gt: Option<&str>, det: Option<&str>) -> usize {
+        match (self.index(gt), self.index(det)) {
+            (Some(g), Some(d)) => self.counts[g][d],
+            _ => 0,
+        }
+    }
+
+    fn label_name(&self, idx: usize) -> &str {
+        self.labels.get(idx).map_or("background", String::as_str)
+    }
+
+    /// Exports the matrix as CSV, the first column holding the ground 
+    /// truth labels and the header the predicted labels.
+    pub fn to_csv(&self) -> String {
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        let header = std::iter::once("gt\\det")
+            .chain((0..=self.labels.len()).map(|i| self.label_name(i)));
+
+        writer.write_record(header)
+            .expect("writing to memory should not fail");
+
+        for (i, row) in self.counts.iter().enumerate() {
+            let record = std::iter::once(self.label_name(i).to_string())
+                .chain(row.iter().map(usize::to_string));
+
+            writer.write_record(record)
+                .expect("writing to memory should not fail");
+        }
+
+        let bytes = writer.into_inner()
+            .expect("writing to memory should not fail");
+
+        String::from_utf8(bytes).expect("CSV should be valid UTF-8")
+    }
+}
+
+impl COCOEval<'_> {
+    /// Computes the confusion matrix of the predictions with a confidence 
+    /// greater than or equal to `conf_thresh`.
+    /// 
+    /// Within each image, ground truths and predictions of any label are 
+    /// greedily matched by decreasing overlap, provided it is greater than
+    /// or equal to `iou_thresh`. Unmatched predictions covered by an ignore
+    /// region by at least `iou_thresh` are not counted.
+    pub fn confusion_matrix(&self, iou_thresh: f32, conf_thresh: f32) -> ConfusionMatrix {
+        let labels: BTreeSet<&str> = self.gts.iter()
+            .flat_map(|ann| &ann.bboxes)
+            .filter(|b| !self.is_ignore_region(b))
+            .chain(self.dets.iter().flat_map(|ann| &ann.bboxes))
+            .map(|b| b.label.as_str())
+            .filter(|l| !self.ignore_labels.contains(*l))
+            .collect();
+
+        let labels: Vec<String> = labels.into_iter().map(String::from).collect();
+        let background = labels.len();
+
+        let mut counts = vec![vec![0; background + 1]; background + 1];
+        let index = |bbox: &BBox| labels.binary_search(&bbox.label).unwrap();
+
+        for img_id in self.img_ids() {
+            let (regions, gts): (Vec<&BBox>, Vec<&BBox>) = self.gts.get(img_id).iter()
+                .flat_map(|ann| &ann.bboxes)
+                .partition(|b| self.is_ignore_region(b));
+
+            let dets: Vec<&BBox> = self.dets.get(img_id).iter()
+                .flat_map(|ann| &ann.bboxes)
+                .filter(|b| Self::score(b) >= conf_thresh && !self.ignore_labels.contains(&b.label))
+                .collect();
+
+            let ious = iou_matrix_ref(&dets, &gts, self.iou_kind);
+
+            let mut pairs: Vec<(usize, usize, f32)> = (0..dets.len())
+                .flat_map(|d| ious.row(d).iter().enumerate().map(move |(g, &iou)| (d, g, iou)))
+                .filter(|&(_, _, iou)| iou >= iou_thresh)
+                .collect();
+
+            // Stable sort so that ties are matched in input order.
+            pairs.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+            let mut gt_matched = vec![false; gts.len()];
+            let mut det_matched = vec![false; dets.len()];
+
+            for (d, g, _) in pairs {
+                if gt_matched[g] || det_matched[d] {
+                    continue
+                }
+
+                gt_matched[g] = true;
+                det_matched[d] = true;
+                counts[index(gts[g])][index(dets[d])] += 1;
+            }
+
+            for (gt, _) in gts.iter().zip(&gt_matched).filter(|(_, &m)| !m) {
+                counts[index(gt)][background] += 1;
+            }
+
+            for (det, _) in dets.iter().zip(&det_matched).filter(|(_, &m)| !m) {
+                if !regions.iter().any(|r| self.covers(r, det, iou_thresh)) {
+                    counts[background][index(det)] += 1;
+                }
+            }
+        }
+
+        ConfusionMatrix { iou_thresh, conf_thresh, labels, counts }
+    }
+}
+
+impl Display for ConfusionMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Confusion matrix @[ IoU={:.2} | conf={:.2} ] (rows: ground truths, columns: predictions)", self.iou_thresh, self.conf_thresh)?;
+
+        let width = self.labels.iter()
+            .map(|l| l.chars().count())
+            .chain([10])
+            .max()
+            .unwrap();
+
+        write!(f, "\n{:<width$}", "")?;
+        for i in 0..=self.labels.len() {
+            write!(f, " {:>width$}", self.label_name(i))?;
+        }
+        writeln!(f)?;
+
+        for (i, row) in self.counts.iter().enumerate() {
+            write!(f, "{:<width$}", self.label_name(i))?;
+            for count in row {
+                write!(f, " {:>width$}", count)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        evaluators::confusion::*,
+        annotation::Ann,
+        annotationset::AnnSet,
+    };
+
+    #[test]
+    fn mislabeled_predictions() {
+        let gts = AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None),
+                BBox::new("dog", 20.0, 20.0, 30.0, 30.0, None),
+            ]),
+        ]);
+
+        let dets = AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.9)),
+                BBox::new("cat", 20.0, 20.0, 30.0, 30.0, Some(0.8)),
+            ]),
+        ]);
+
+        let matrix = COCOEval::new(&gts, &dets).confusion_matrix(0.5, 0.5);
+
+        assert_eq!(matrix.labels, ["cat", "dog"]);
+        assert_eq!(matrix.get(Some("cat"), Some("cat")), 1);
+        assert_eq!(matrix.get(Some("dog"), Some("cat")), 1);
+        assert_eq!(matrix.counts.iter().flatten().sum::<usize>(), 2);
+        assert_eq!(matrix.get(Some("horse"), None), 0);
+    }
+
+    #[test]
+    fn background() {
+        let gts = AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![
+                BBox::new("dog", 50.0, 50.0, 60.0, 60.0, None),
+            ]),
+        ]);
+
+        // A missed dog, a dog on the background and a prediction on an
+        // image without ground truths.
+        let dets = AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![
+                BBox::new("dog", 80.0, 80.0, 90.0, 90.0, Some(0.7)),
+            ]),
+            Ann::new("b.jpg", None, vec![
+                BBox::new("bird", 0.0, 0.0, 10.0, 10.0, Some(0.9)),
+            ]),
+        ]);
+
+        let matrix = COCOEval::new(&gts, &dets).confusion_matrix(0.5, 0.5);
+
+        assert_eq!(matrix.get(Some("dog"), None), 1);
+        assert_eq!(matrix.get(None, Some("dog")), 1);
+        assert_eq!(matrix.get(None, Some("bird")), 1);
+        assert_eq!(matrix.counts.iter().flatten().sum::<usize>(), 3);
+    }
+
+    #[test]
+    fn confidence_threshold() {
+        let gts = AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![
+                BBox::new("dog", 50.0, 50.0, 60.0, 60.0, None),
+            ]),
+        ]);
+
+        let dets = AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![
+                BBox::new("dog", 50.0, 50.0, 60.0, 60.0, Some(0.1)),
+            ]),
+        ]);
+
+        let eval = COCOEval::new(&gts, &dets);
+
+        let matrix = eval.confusion_matrix(0.5, 0.5);
+        assert_eq!(matrix.get(Some("dog"), None), 1);
+        assert_eq!(matrix.get(Some("dog"), Some("dog")), 0);
+
+        let matrix = eval.confusion_matrix(0.5, 0.0);
+        assert_eq!(matrix.get(Some("dog"), Some("dog")), 1);
+
+        // Without predictions the background column holds every ground truth.
+        let matrix = COCOEval::new(&gts, &AnnSet::new()).confusion_matrix(0.5, 0.5);
+        assert_eq!(matrix.counts, [[0, 1], [0, 0]]);
+    }
+
+    #[test]
+    fn background_only() {
+        let empty = AnnSet::new();
+        let matrix = COCOEval::new(&empty, &empty).confusion_matrix(0.5, 0.5);
+
+        // The background row and column are always present.
+        assert!(matrix.labels.is_empty());
+        assert_eq!(matrix.counts, [[0]]);
+        assert_eq!(matrix.to_csv(), "gt\\det,background\nbackground,0\n");
+        assert!(matrix.to_string().contains("background"));
+    }
+}
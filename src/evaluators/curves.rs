@@ -0,0 +1,268 @@
+use super::coco::{COCOEval, ImgEval};
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+use serde::Serialize;
+
+/// The precision, recall and F1 score obtained when keeping the detections
+/// with a confidence greater than or equal to `conf`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PRPoint {
+    pub conf: f32,
+    pub precision: f32,
+    pub recall: f32,
+    pub f1: f32,
+}
+
+impl PRPoint {
+    fn new(conf: f32, tp: usize, fp: usize, nb_gts: usize) -> Self {
+        let precision = if tp + fp == 0 { 0.0 } else { tp as f32 / (tp + fp) as f32 };
+        let recall = tp as f32 / nb_gts as f32;
+        let f1 = if tp == 0 { 0.0 } else { 2.0 * precision * recall / (precision + recall) };
+
+        PRPoint { conf, precision, recall, f1 }
+    }
+}
+
+/// A raw, non-interpolated, precision-recall curve.
+#[derive(Debug, Clone, Serialize)]
+pub struct PRCurve {
+    /// The number of ground truths.
+    pub nb_gts: usize,
+
+    /// One point per distinct detection confidence, in decreasing 
+    /// confidence order.
+    pub points: Vec<PRPoint>,
+
+    /// The point with the highest F1 score, the most confident one in case
+    /// of ties. `None` if there are no detections.
+    pub best_f1: Option<PRPoint>,
+
+    /// The point at the confidence set with `PRCurves::with_conf`.
+    pub at_conf: Option<PRPoint>,
+}
+
+impl PRCurve {
+    /// Builds the curve from detections sorted by decreasing confidence
+    /// with whether they are true positives.
+    /// 
+    /// Returns `None` if there are no ground truths.
+    fn new(dets: &[(f32, bool)], nb_gts: usize) -> Option<Self> {
+        if nb_gts == 0 {
+            return None
+        }
+
+        let (mut tp, mut fp) = (0, 0);
+        let mut points = vec![];
+
+        for (i, &(conf, is_tp)) in dets.iter().enumerate() {
+            if is_tp { tp += 1 } else { fp += 1 }
+
+            // Detections with equal confidence can not be separated by a threshold.
+            if dets.get(i + 1).is_some_and(|next| next.0 == conf) {
+                continue
+            }
+
+            points.push(PRPoint::new(conf, tp, fp, nb_gts));
+        }
+
+        let best_f1 = points.iter()
+            .copied()
+            .reduce(|best, p| if p.f1 > best.f1 { p } else { best });
+
+        Some(PRCurve { nb_gts, points, best_f1, at_conf: None })
+    }
+
+    /// The point obtained when keeping the detections with a confidence
+    /// greater than or equal to `conf`.
+    pub fn at(&self, conf: f32) -> PRPoint {
+        let idx = self.points.partition_point(|p| p.conf >= conf);
+
+        match idx {
+            0 => PRPoint { conf, precision: 0.0, recall: 0.0, f1: 0.0 },
+            _ => PRPoint { conf, ..self.points[idx - 1] },
+        }
+    }
+}
+
+/// The precision-recall curves at one IoU threshold.
+#[derive(Debug, Clone, Serialize)]
+pub struct PRCurves {
+    pub iou_thresh: f32,
+
+    /// The curves of the labels with ground truths.
+    pub labels: BTreeMap<String, PRCurve>,
+
+    /// The curve of the detections of all labels pooled together, `None`
+    /// if there are no ground truths.
+    pub all: Option<PRCurve>,
+}
+
+impl COCOEval<'_> {
+    /// Computes the precision-recall curves at a single IoU threshold, 
+    /// keeping at most `ndets` detections per image and label and only 
+    /// objects with an area in `sz_range`.
+    /// 
+    /// # Panics
+    /// Will panic if the threshold is not in `[0, 1]` or if the size
+    /// range is invalid.
+    pub fn pr_curves(&self, thresh: f32, ndets: usize, sz_range: (f32, f32)) -> PRCurves {
+        assert!(Self::validate_args(thresh, sz_range), "invalid evaluation parameters");
+
+        let boxes = self.group(ndets);
+        let evals = self.match_all(&boxes, &[thresh], sz_range);
+
+        let nb_gts = |imgs: &[ImgEval]| imgs.iter().map(|img| img.nb_gts).sum::<usize>();
+
+        let labels = evals.iter()
+            .filter_map(|(&label, imgs)| {
+                let dets = Self::ranked_dets(imgs, 0, ndets);
+                PRCurve::new(&dets, nb_gts(imgs)).map(|c| (label.to_string(), c))
+            })
+            .collect();
+
+        let mut dets: Vec<(f32, bool)> = evals.values()
+            .flat_map(|imgs| Self::ranked_dets(imgs, 0, ndets))
+            .collect();
+        dets.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let all = PRCurve::new(&dets, evals.values().map(|imgs| nb_gts(imgs)).sum());
+
+        PRCurves { iou_thresh: thresh, labels, all }
+    }
+}
+
+impl PRCurves {
+    /// Computes the precision, recall and F1 score of each curve at the
+    /// given confidence threshold.
+    pub fn with_conf(mut self, conf: f32) -> Self {
+        for curve in self.labels.values_mut().chain(self.all.as_mut()) {
+            curve.at_conf = Some(curve.at(conf));
+        }
+        self
+    }
+
+    /// The curves, the one of all labels having an empty label.
+    fn curves(&self) -> impl Iterator<Item = (&str, &PRCurve)> {
+        self.all.iter()
+            .map(|c| ("", c))
+            .chain(self.labels.iter().map(|(l, c)| (l.as_str(), c)))
+    }
+
+    /// Exports the curves as CSV with one row per point. The `kind` column 
+    /// is `curve` for curve points, `best_f1` for the F1-maximising points 
+    /// and `at_conf` for the points at the chosen confidence. Rows with an 
+    /// empty label are computed over all labels.
+    pub fn to_csv(&self) -> String {
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        writer.write_record(["label", "kind", "conf", "precision", "recall", "f1"])
+            .expect("writing to memory should not fail");
+
+        for (label, curve) in self.curves() {
+            let points = curve.points.iter().map(|p| ("curve", p))
+                .chain(curve.best_f1.iter().map(|p| ("best_f1", p)))
+                .chain(curve.at_conf.iter().map(|p| ("at_conf", p)));
+
+            for (kind, p) in points {
+                writer.write_record([
+                    label.to_string(), 
+                    kind.to_string(), 
+                    p.conf.to_string(), 
+                    p.precision.to_string(), 
+                    p.recall.to_string(), 
+                    p.f1.to_string(),
+                ]).expect("writing to memory should not fail");
+            }
+        }
+
+        let bytes = writer.into_inner()
+            .expect("writing to memory should not fail");
+
+        String::from_utf8(bytes).expect("CSV should be valid UTF-8")
+    }
+}
+
+impl Display for PRCurves {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Operating points @[ IoU={:.2} ]", self.iou_thresh)?;
+
+        write!(f, "\n{:<24} {:>10} {:>10} {:>10} {:>10}", "Label", "Best conf", "Precision", "Recall", "F1")?;
+
+        let at_conf = self.all.as_ref().and_then(|c| c.at_conf);
+        if let Some(p) = at_conf {
+            write!(f, " {:>10} {:>10} {:>10}", format!("P@{:.2}", p.conf), format!("R@{:.2}", p.conf), format!("F1@{:.2}", p.conf))?;
+        }
+        writeln!(f)?;
+
+        for (label, curve) in self.curves() {
+            let label = if label.is_empty() { "(all)" } else { label };
+
+            match curve.best_f1 {
+                Some(p) => write!(f, "{:<24} {:>10.3} {:>10.3} {:>10.3} {:>10.3}", label, p.conf, p.precision, p.recall, p.f1)?,
+                None => write!(f, "{:<24} {:>10} {:>10} {:>10} {:>10}", label, "-", "-", "-", "-")?,
+            }
+
+            if let Some(p) = curve.at_conf {
+                write!(f, " {:>10.3} {:>10.3} {:>10.3}", p.precision, p.recall, p.f1)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        evaluators::{coco::AREA_ALL, curves::*},
+        annotation::Ann,
+        annotationset::AnnSet,
+        bbox::BBox,
+    };
+
+    #[test]
+    fn pr_curves() {
+        let gts = AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None),
+                BBox::new("cat", 20.0, 20.0, 30.0, 30.0, None),
+                BBox::new("dog", 50.0, 50.0, 60.0, 60.0, None),
+            ]),
+        ]);
+
+        let dets = AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.9)),
+                BBox::new("cat", 80.0, 80.0, 90.0, 90.0, Some(0.8)),
+                BBox::new("cat", 20.0, 20.0, 30.0, 30.0, Some(0.4)),
+                BBox::new("cat", 70.0, 70.0, 75.0, 75.0, Some(0.4)),
+                BBox::new("bird", 0.0, 0.0, 10.0, 10.0, Some(0.5)),
+            ]),
+        ]);
+
+        let curves = COCOEval::new(&gts, &dets)
+            .pr_curves(0.5, 100, AREA_ALL)
+            .with_conf(0.5);
+
+        // Labels without ground truths have no curve.
+        assert_eq!(curves.labels.keys().collect::<Vec<_>>(), ["cat", "dog"]);
+
+        let cat = &curves.labels["cat"];
+        assert_eq!(cat.points.len(), 3);
+        assert_eq!(cat.points[2], PRPoint { conf: 0.4, precision: 0.5, recall: 1.0, f1: 2.0 / 3.0 });
+        assert_eq!(cat.best_f1.unwrap().conf, 0.9);
+        assert_eq!(cat.at_conf.unwrap().precision, 0.5);
+        assert_eq!(cat.at(0.95).f1, 0.0);
+
+        assert!(curves.labels["dog"].best_f1.is_none());
+
+        let all = curves.all.unwrap();
+        assert_eq!(all.nb_gts, 3);
+        assert_eq!(all.points.len(), 4);
+    }
+}
@@ -0,0 +1,331 @@
+use super::coco::{COCOEval, AREA_ALL, MAX_DETS};
+
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+use serde::Serialize;
+
+/// The outcome of a bounding box in the evaluation of an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    /// A prediction matching a ground truth.
+    TP,
+    /// A prediction matching no ground truth.
+    FP,
+    /// A ground truth matched by no prediction.
+    FN,
+}
+
+/// A true positive, false positive or false negative of an image.
+#[derive(Debug, Clone, Serialize)]
+pub struct BoxMatch {
+    pub outcome: Outcome,
+    pub label: String,
+
+    /// The index of the ground truth in the ground truth annotation of
+    /// the image, `None` for false positives.
+    pub gt: Option<usize>,
+
+    /// The index of the prediction in the prediction annotation of the
+    /// image, `None` for false negatives.
+    pub det: Option<usize>,
+
+    /// The confidence of the prediction.
+    pub conf: Option<f32>,
+
+    /// The overlap of the prediction with the ground truth, for true
+    /// positives only.
+    pub iou: Option<f32>,
+}
+
+/// The evaluation breakdown of one image.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImgReport {
+    pub img_id: String,
+    pub tp: usize,
+    pub fp: usize,
+    #[serde(rename = "fn")]
+    pub fn_: usize,
+    pub matches: Vec<BoxMatch>,
+}
+
+impl ImgReport {
+    /// The number of false positives and false negatives.
+    pub fn nb_errors(&self) -> usize {
+        self.fp + self.fn_
+    }
+
+    fn push(&mut self, m: BoxMatch) {
+        match m.outcome {
+            Outcome::TP => self.tp += 1,
+            Outcome::FP => self.fp += 1,
+            Outcome::FN => self.fn_ += 1,
+        }
+
+        self.matches.push(m);
+    }
+}
+
+/// The evaluation breakdown of every image.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImgBreakdown {
+    pub iou_thresh: f32,
+    pub conf_thresh: f32,
+
+    /// The image reports, in evaluation order.
+    pub images: Vec<ImgReport>,
+}
+
+impl COCOEval<'_> {
+    /// Computes the true positives, false positives and false negatives 
+    /// of each image at one IoU threshold, with the COCO matching and 
+    /// keeping only the predictions with a confidence greater than or 
+    /// equal to `conf_thresh`.
+    /// 
+    /// # Panics
+    /// Will panic if the threshold is not in `[0, 1]`.
+    pub fn img_breakdown(&self, iou_thresh: f32, conf_thresh: f32) -> ImgBreakdown {
+        assert!(Self::validate_args(iou_thresh, AREA_ALL), "invalid evaluation parameters");
+
+        let boxes = self.group(MAX_DETS[MAX_DETS.len() - 1]);
+        let evals = self.match_all(&boxes, &[iou_thresh], AREA_ALL);
+
+        let mut images: Vec<ImgReport> = self.img_ids()
+            .map(|img_id| ImgReport { img_id: img_id.to_string(), tp: 0, fp: 0, fn_: 0, matches: vec![] })
+            .collect();
+
+        let positions: HashMap<&str, usize> = self.img_ids()
+            .enumerate()
+            .map(|(i, img_id)| (img_id, i))
+            .collect();
+
+        for (label, imgs) in &boxes {
+            for (img, eval) in imgs.iter().zip(&evals[label]) {
+                let report = &mut images[positions[img.img_id]];
+                let mut gt_matched = vec![false; img.gts.len()];
+
+                for d in 0..img.dets.len() {
+                    if eval.scores[d] < conf_thresh || eval.ignored[0][d] {
+                        continue
+                    }
+
+                    let matched = eval.matches[0][d];
+
+                    if let Some(g) = matched {
+                        gt_matched[g] = true;
+                    }
+
+                    report.push(BoxMatch { 
+                        outcome: if matched.is_some() { Outcome::TP } else { Outcome::FP }, 
+                        label: label.to_string(), 
+                        gt: matched.map(|g| img.gt_idxs[g]), 
+                        det: Some(img.det_idxs[d]), 
+                        conf: Some(eval.scores[d]), 
+                        iou: matched.map(|g| img.ious.get(d, g)),
+                    });
+                }
+
+                for (g, &gt_idx) in img.gt_idxs.iter().enumerate() {
+                    if gt_matched[g] || eval.gt_ignored[g] {
+                        continue
+                    }
+
+                    report.push(BoxMatch { 
+                        outcome: Outcome::FN, 
+                        label: label.to_string(), 
+                        gt: Some(gt_idx), 
+                        det: None, 
+                        conf: None, 
+                        iou: None,
+                    });
+                }
+            }
+        }
+
+        ImgBreakdown { iou_thresh, conf_thresh, images }
+    }
+}
+
+impl ImgBreakdown {
+    /// The `n` images with the most errors, in decreasing error count and 
+    /// evaluation order for ties.
+    pub fn worst(&self, n: usize) -> Vec<&ImgReport> {
+        let mut images: Vec<&ImgReport> = self.images.iter().collect();
+        images.sort_by_key(|img| Reverse(img.nb_errors()));
+        images.truncate(n);
+        images
+    }
+
+    /// Exports the per-image counts as CSV, one row per image.
+    pub fn to_csv(&self) -> String {
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        writer.write_record(["img_id", "tp", "fp", "fn", "errors"])
+            .expect("writing to memory should not fail");
+
+        for img in &self.images {
+            writer.write_record([
+                img.img_id.clone(), 
+                img.tp.to_string(), 
+                img.fp.to_string(), 
+                img.fn_.to_string(), 
+                img.nb_errors().to_string(),
+            ]).expect("writing to memory should not fail");
+        }
+
+        let bytes = writer.into_inner()
+            .expect("writing to memory should not fail");
+
+        String::from_utf8(bytes).expect("CSV should be valid UTF-8")
+    }
+
+    /// Exports the matching details as CSV, one row per true positive, 
+    /// false positive and false negative.
+    pub fn matches_to_csv(&self) -> String {
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        writer.write_record(["img_id", "outcome", "label", "gt", "det", "conf", "iou"])
+            .expect("writing to memory should not fail");
+
+        let fmt = |v: Option<String>| v.unwrap_or_default();
+
+        for img in &self.images {
+            for m in &img.matches {
+                let outcome = match m.outcome {
+                    Outcome::TP => "tp",
+                    Outcome::FP => "fp",
+                    Outcome::FN => "fn",
+                };
+
+                writer.write_record([
+                    img.img_id.clone(),
+                    outcome.to_string(),
+                    m.label.clone(),
+                    fmt(m.gt.map(|g| g.to_string())),
+                    fmt(m.det.map(|d| d.to_string())),
+                    fmt(m.conf.map(|c| c.to_string())),
+                    fmt(m.iou.map(|i| i.to_string())),
+                ]).expect("writing to memory should not fail");
+            }
+        }
+
+        let bytes = writer.into_inner()
+            .expect("writing to memory should not fail");
+
+        String::from_utf8(bytes).expect("CSV should be valid UTF-8")
+    }
+}
+
+/// Displays the ten worst images.
+impl Display for ImgBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Worst images @[ IoU={:.2} | conf={:.2} ]", self.iou_thresh, self.conf_thresh)?;
+
+        writeln!(f, "\n{:<24} {:>10} {:>10} {:>10} {:>10}", "Image", "TP", "FP", "FN", "Errors")?;
+        for img in self.worst(10) {
+            writeln!(f, "{:<24} {:>10} {:>10} {:>10} {:>10}", img.img_id, img.tp, img.fp, img.fn_, img.nb_errors())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        evaluators::images::*,
+        annotation::Ann,
+        annotationset::AnnSet,
+        bbox::BBox,
+    };
+
+    fn gts() -> AnnSet {
+        AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None),
+                BBox::new("dog", 20.0, 20.0, 30.0, 30.0, None),
+            ]),
+            Ann::new("b.jpg", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None),
+            ]),
+        ])
+    }
+
+    fn dets() -> AnnSet {
+        AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![
+                BBox::new("dog", 50.0, 50.0, 60.0, 60.0, Some(0.8)),
+                BBox::new("cat", 0.0, 0.0, 10.0, 12.0, Some(0.9)),
+                BBox::new("dog", 20.0, 20.0, 30.0, 30.0, Some(0.1)),
+            ]),
+            Ann::new("b.jpg", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.7)),
+            ]),
+        ])
+    }
+
+    #[test]
+    fn counts() {
+        let (gts, dets) = (gts(), dets());
+        let breakdown = COCOEval::new(&gts, &dets).img_breakdown(0.5, 0.5);
+
+        // The dog prediction below the confidence threshold is skipped.
+        let a = &breakdown.images[0];
+        assert_eq!((a.tp, a.fp, a.fn_, a.nb_errors()), (1, 1, 1, 2));
+
+        let b = &breakdown.images[1];
+        assert_eq!((b.tp, b.fp, b.fn_), (1, 0, 0));
+
+        let low_conf = COCOEval::new(&gts, &dets).img_breakdown(0.5, 0.0);
+        assert_eq!(low_conf.images[0].tp, 2);
+
+        // Without predictions every ground truth is missed.
+        let no_dets = COCOEval::new(&gts, &AnnSet::new()).img_breakdown(0.5, 0.5);
+        let counts: Vec<_> = no_dets.images.iter().map(|img| (img.tp, img.fp, img.fn_)).collect();
+        assert_eq!(counts, [(0, 0, 2), (0, 0, 1)]);
+    }
+
+    #[test]
+    fn match_details() {
+        let (gts, dets) = (gts(), dets());
+        let breakdown = COCOEval::new(&gts, &dets).img_breakdown(0.5, 0.5);
+
+        let matches = &breakdown.images[0].matches;
+        let find = |outcome| matches.iter().find(|m| m.outcome == outcome).unwrap();
+
+        let tp = find(Outcome::TP);
+        assert_eq!((tp.gt, tp.det, tp.conf), (Some(0), Some(1), Some(0.9)));
+        assert_eq!(tp.iou, Some(100.0 / 120.0));
+
+        let fp = find(Outcome::FP);
+        assert_eq!((fp.label.as_str(), fp.gt, fp.det, fp.iou), ("dog", None, Some(0), None));
+
+        let fn_ = find(Outcome::FN);
+        assert_eq!((fn_.label.as_str(), fn_.gt, fn_.det), ("dog", Some(1), None));
+
+        // A header and one row per match.
+        assert_eq!(breakdown.matches_to_csv().lines().count(), 1 + 4);
+    }
+
+    #[test]
+    fn worst_images() {
+        let (gts, dets) = (gts(), dets());
+        let breakdown = COCOEval::new(&gts, &dets).img_breakdown(0.5, 0.5);
+
+        let worst: Vec<_> = breakdown.worst(1).iter().map(|img| img.img_id.as_str()).collect();
+        assert_eq!(worst, ["a.jpg"]);
+
+        let worst: Vec<_> = breakdown.worst(10).iter().map(|img| img.img_id.as_str()).collect();
+        assert_eq!(worst, ["a.jpg", "b.jpg"]);
+
+        // Only the header is exported without images.
+        let empty = AnnSet::new();
+        let breakdown = COCOEval::new(&empty, &empty).img_breakdown(0.5, 0.5);
+        assert!(breakdown.worst(10).is_empty());
+        assert_eq!(breakdown.to_csv(), "img_id,tp,fp,fn,errors\n");
+    }
+}
@@ -0,0 +1,490 @@
+use super::coco::{AREA_ALL, COCOEval, ImgBoxes, fmt_metric};
+
+use crate::{
+    bbox::GROUP_OF_ATTR,
+    evaluation::ioa,
+    labels::LabelHierarchy,
+    parsers::ParseError,
+    serde_records::openimage::OAImageLabel,
+};
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display},
+    path::Path,
+};
+
+use serde::Serialize;
+
+/// Image-level labels verified present (positive) or absent (negative)
+/// by annotators.
+#[derive(Debug, Clone, Default)]
+pub struct VerifiedLabels {
+    images: HashMap<String, HashMap<String, bool>>,
+}
+
+impl VerifiedLabels {
+    pub fn new() -> Self {
+        VerifiedLabels::default()
+    }
+
+    /// Marks the label as verified present or absent in the image.
+    pub fn insert<I: Into<String>, L: Into<String>>(&mut self, img_id: I, label: L, positive: bool) {
+        self.images.entry(img_id.into()).or_default().insert(label.into(), positive);
+    }
+
+    /// Whether the label is verified present or absent in the image,
+    /// `None` if it is not verified.
+    pub fn get(&self, img_id: &str, label: &str) -> Option<bool> {
+        self.images.get(img_id)?.get(label).copied()
+    }
+
+    /// Reads an OpenImages image-level labels CSV file with `ImageID`,
+    /// `LabelName` and `Confidence` columns.
+    pub fn from_openimages<P: AsRef<Path>>(path: P) -> Result<Self, ParseError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(path)
+            .map_err(|_| ParseError {})?;
+
+        let mut labels = VerifiedLabels::new();
+
+        for line in reader.deserialize::<OAImageLabel>() {
+            let line = line.map_err(|_| ParseError {})?;
+            labels.insert(line.img_id, line.label, line.conf > 0.0);
+        }
+
+        Ok(labels)
+    }
+
+    /// Expands positive labels to their ancestors and negative labels to
+    /// their descendants. Positive labels take precedence.
+    pub fn expand_labels(&mut self, hierarchy: &LabelHierarchy) {
+        for labels in self.images.values_mut() {
+            let mut expanded = HashMap::new();
+
+            for (label, &positive) in labels.iter() {
+                let others = match positive {
+                    true => hierarchy.ancestors(label),
+                    false => hierarchy.descendants(label),
+                };
+
+                for other in others {
+                    let entry = expanded.entry(other.to_string()).or_insert(positive);
+                    *entry |= positive;
+                }
+            }
+
+            for (label, positive) in expanded {
+                let entry = labels.entry(label).or_insert(positive);
+                *entry |= positive;
+            }
+        }
+    }
+}
+
+/// The OpenImages challenge results of one label.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct OIDLabel {
+    /// The Average Precision, `None` if the label has no ground truth.
+    pub ap: Option<f32>,
+
+    /// The number of ground truths, group-of boxes counting once.
+    pub nb_gts: usize,
+
+    pub tp: usize,
+    pub fp: usize,
+}
+
+/// The OpenImages detection challenge metric.
+#[derive(Debug, Clone, Serialize)]
+pub struct OIDReport {
+    pub iou_thresh: f32,
+
+    /// The mean Average Precision over labels with ground truths.
+    pub map: Option<f32>,
+
+    pub labels: BTreeMap<String, OIDLabel>,
+}
+
+impl COCOEval<'_> {
+    /// Computes the OpenImages detection challenge metric at an IoU
+    /// threshold, usually 0.5.
+    ///
+    /// Predictions are only evaluated on images where their label is
+    /// verified, positively or negatively, or has ground truths, others
+    /// are ignored. Without verified labels every prediction is evaluated.
+    ///
+    /// Each prediction is matched with its most overlapping regular ground
+    /// truth, a true positive if not already matched. Remaining predictions
+    /// inside a group-of box by at least the threshold (IoA) are merged into
+    /// a single true positive per group-of box, with their highest
+    /// confidence. The Average Precision is the area under the monotonic
+    /// precision-recall curve (Pascal VOC 2012).
+    ///
+    /// Ground truth labels and verified labels are not expanded here, see
+    /// `AnnSet::expand_labels` and `VerifiedLabels::expand_labels`.
+    ///
+    /// # Panics
+    /// Will panic if the threshold is not in `[0, 1]`.
+    pub fn openimages(&self, verified: Option<&VerifiedLabels>, iou_thresh: f32) -> OIDReport {
+        assert!(Self::validate_args(iou_thresh, AREA_ALL), "invalid evaluation parameters");
+
+        let boxes = self.group(usize::MAX);
+
+        let labels: BTreeMap<String, OIDLabel> = boxes.iter()
+            .map(|(&label, imgs)| {
+                let mut dets = vec![];
+                let mut nb_gts = 0;
+
+                for img in imgs {
+                    let is_evaluated = verified.is_none_or(|v| v.get(img.img_id, label).is_some())
+                        || img.gts.iter().any(|gt| gt.label == label);
+
+                    if is_evaluated {
+                        nb_gts += Self::match_oid(img, iou_thresh, &mut dets);
+                    }
+                }
+
+                dets.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+                let tp = dets.iter().filter(|d| d.1).count();
+                let ap = (nb_gts > 0).then(|| voc_average_precision(&dets, nb_gts));
+
+                (label.to_string(), OIDLabel { ap, nb_gts, tp, fp: dets.len() - tp })
+            })
+            .collect();
+
+        let aps: Vec<f32> = labels.values().filter_map(|l| l.ap).collect();
+        let map = (!aps.is_empty()).then(|| aps.iter().sum::<f32>() / aps.len() as f32);
+
+        OIDReport { iou_thresh, map, labels }
+    }
+
+    /// Matches the bounding boxes of one image, pushing the confidence of
+    /// the predictions which are not ignored and whether they are true
+    /// positives. Returns the number of ground truths which are not ignored.
+    fn match_oid(img: &ImgBoxes, iou_thresh: f32, dets: &mut Vec<(f32, bool)>) -> usize {
+        let group_of: Vec<bool> = img.gts.iter()
+            .map(|gt| gt.attribute(GROUP_OF_ATTR) == Some("1"))
+            .collect();
+
+        let mut gt_matched = vec![false; img.gts.len()];
+        let mut group_scores: Vec<Option<f32>> = vec![None; img.gts.len()];
+
+        for (d, det) in img.dets.iter().enumerate() {
+            let score = Self::score(det);
+
+            // The most overlapping regular box, including ignore regions.
+            let best = (0..img.gts.len())
+                .filter(|&g| !group_of[g])
+                .max_by(|&a, &b| img.ious.get(d, a).total_cmp(&img.ious.get(d, b)))
+                .filter(|&g| img.ious.get(d, g) >= iou_thresh);
+
+            match best {
+                Some(g) if img.crowd[g] => continue,
+                Some(g) if !gt_matched[g] => {
+                    gt_matched[g] = true;
+                    dets.push((score, true));
+                    continue
+                },
+                _ => {},
+            }
+
+            let group = (0..img.gts.len())
+                .filter(|&g| group_of[g] && !img.crowd[g])
+                .map(|g| (g, ioa(det, img.gts[g])))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .filter(|&(_, ioa)| ioa >= iou_thresh);
+
+            match group {
+                Some((g, _)) => {
+                    let best = group_scores[g].get_or_insert(score);
+                    *best = best.max(score);
+                },
+                None => dets.push((score, false)),
+            }
+        }
+
+        dets.extend(group_scores.into_iter().flatten().map(|score| (score, true)));
+
+        img.crowd.iter().filter(|&&crowd| !crowd).count()
+    }
+}
+
+/// Computes the area under the precision-recall curve, precision being
+/// made monotonically decreasing, from predictions sorted by decreasing
+/// confidence with whether they are true positives.
+///
+/// `nb_gts` must not be zero.
+fn voc_average_precision(dets: &[(f32, bool)], nb_gts: usize) -> f32 {
+    let mut tp = 0;
+    let mut points = Vec::with_capacity(dets.len());
+
+    for (i, &(_, is_tp)) in dets.iter().enumerate() {
+        tp += is_tp as usize;
+        points.push((tp as f32 / nb_gts as f32, tp as f32 / (i + 1) as f32));
+    }
+
+    let mut precision = 0.0f32;
+    for point in points.iter_mut().rev() {
+        precision = precision.max(point.1);
+        point.1 = precision;
+    }
+
+    let mut ap = 0.0;
+    let mut prev_recall = 0.0;
+
+    for (recall, precision) in points {
+        ap += (recall - prev_recall) * precision;
+        prev_recall = recall;
+    }
+
+    ap
+}
+
+impl OIDReport {
+    /// Exports the results as CSV with one row per label. The first row,
+    /// with an empty label, holds the mean Average Precision and totals.
+    pub fn to_csv(&self) -> String {
+        let fmt = |v: Option<f32>| v.map(|v| v.to_string()).unwrap_or_default();
+        let total = |f: fn(&OIDLabel) -> usize| self.labels.values().map(f).sum::<usize>().to_string();
+
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        writer.write_record(["label", "nb_gts", "tp", "fp", "ap"])
+            .expect("writing to memory should not fail");
+
+        writer.write_record([String::new(), total(|l| l.nb_gts), total(|l| l.tp), total(|l| l.fp), fmt(self.map)])
+            .expect("writing to memory should not fail");
+
+        for (label, res) in &self.labels {
+            writer.write_record([label.clone(), res.nb_gts.to_string(), res.tp.to_string(), res.fp.to_string(), fmt(res.ap)])
+                .expect("writing to memory should not fail");
+        }
+
+        let bytes = writer.into_inner()
+            .expect("writing to memory should not fail");
+
+        String::from_utf8(bytes).expect("CSV should be valid UTF-8")
+    }
+}
+
+impl Display for OIDReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "OpenImages challenge metric @[ IoU={:.2} ] mAP = {}", self.iou_thresh, fmt_metric(self.map))?;
+
+        writeln!(f, "\n{:<24} {:>10} {:>10} {:>10} {:>10}", "Label", "Boxes", "TP", "FP", "AP")?;
+
+        for (label, res) in &self.labels {
+            writeln!(
+                f, "{:<24} {:>10} {:>10} {:>10} {:>10}",
+                label, res.nb_gts, res.tp, res.fp, fmt_metric(res.ap),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{annotation::Ann, annotationset::AnnSet, bbox::BBox, evaluators::openimages::*, path::test_dir};
+
+    fn group_of(label: &str, xmin: f32, ymin: f32, xmax: f32, ymax: f32) -> BBox {
+        let mut bbox = BBox::new(label, xmin, ymin, xmax, ymax, None);
+        bbox.set_attribute(GROUP_OF_ATTR, "1");
+        bbox
+    }
+
+    /// A regular person box and a group-of person box in image `a`, no
+    /// box in image `b`.
+    fn gts() -> AnnSet {
+        AnnSet::from_iter([
+            Ann::new("a", None, vec![
+                BBox::new("person", 0.0, 0.0, 10.0, 10.0, None),
+                group_of("person", 50.0, 50.0, 100.0, 100.0),
+            ]),
+            Ann::new("b", None, vec![]),
+        ])
+    }
+
+    #[test]
+    fn group_of_boxes() {
+        let gts = gts();
+
+        // A true positive, three predictions in the group-of box, a duplicate
+        // and a prediction overlapping the group-of box too little.
+        let dets = AnnSet::from_iter([
+            Ann::new("a", None, vec![
+                BBox::new("person", 0.0, 0.0, 10.0, 10.0, Some(0.9)),
+                BBox::new("person", 60.0, 60.0, 70.0, 70.0, Some(0.8)),
+                BBox::new("person", 70.0, 70.0, 80.0, 80.0, Some(0.7)),
+                BBox::new("person", 0.0, 0.0, 10.0, 10.0, Some(0.6)),
+                BBox::new("person", 80.0, 80.0, 90.0, 90.0, Some(0.5)),
+                BBox::new("person", 90.0, 90.0, 130.0, 130.0, Some(0.4)),
+            ]),
+        ]);
+
+        let report = COCOEval::new(&gts, &dets).openimages(None, 0.5);
+
+        // Ranked: TP (0.9), group-of TP (0.8), duplicate FP (0.6), FP (0.4).
+        let person = report.labels["person"];
+        assert_eq!((person.nb_gts, person.tp, person.fp), (2, 2, 2));
+        assert_eq!(person.ap, Some(1.0));
+        assert_eq!(report.map, Some(1.0));
+
+        // The totals row followed by one row per label.
+        assert_eq!(report.to_csv(), "label,nb_gts,tp,fp,ap\n,2,2,2,1\nperson,2,2,2,1\n");
+
+        // Group-of boxes are missed once, like regular boxes.
+        let no_dets = AnnSet::new();
+        let person = COCOEval::new(&gts, &no_dets).openimages(None, 0.5).labels["person"];
+        assert_eq!((person.ap, person.nb_gts, person.tp, person.fp), (Some(0.0), 2, 0, 0));
+    }
+
+    #[test]
+    fn group_of_only() {
+        let gts = AnnSet::from_iter([
+            Ann::new("a", None, vec![group_of("person", 0.0, 0.0, 100.0, 100.0)]),
+        ]);
+
+        // Only the highest confidence of the merged predictions is kept,
+        // ranking it above the false positive.
+        let dets = AnnSet::from_iter([
+            Ann::new("a", None, vec![
+                BBox::new("person", 200.0, 200.0, 210.0, 210.0, Some(0.7)),
+                BBox::new("person", 10.0, 10.0, 20.0, 20.0, Some(0.6)),
+                BBox::new("person", 30.0, 30.0, 40.0, 40.0, Some(0.8)),
+            ]),
+        ]);
+
+        let person = COCOEval::new(&gts, &dets).openimages(None, 0.5).labels["person"];
+        assert_eq!((person.nb_gts, person.tp, person.fp), (1, 1, 1));
+        assert_eq!(person.ap, Some(1.0));
+    }
+
+    #[test]
+    fn parsed_group_of() {
+        let dir = test_dir("openimages-group-of");
+
+        // The header of a 100x100 PNG image, all the parser reads.
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend(100u32.to_be_bytes());
+        png.extend(100u32.to_be_bytes());
+        png.extend([8, 2, 0, 0, 0, 0, 0, 0, 0]);
+        std::fs::write(dir.join("a.png"), png).unwrap();
+
+        let path = dir.join("gts.csv");
+        std::fs::write(&path, "\
+            ImageID,Source,LabelName,Confidence,XMin,XMax,YMin,YMax,IsOccluded,IsTruncated,IsGroupOf,IsDepiction,IsInside\n\
+            a.png,xclick,person,1,0.5,1.0,0.5,1.0,0,0,1,0,0\n\
+            a.png,xclick,person,1,0.0,0.1,0.0,0.1,0,0,0,0,0\n\
+        ").unwrap();
+
+        let gts = AnnSet::parse_openimage(&path, &dir).unwrap();
+        let flags: Vec<_> = gts.get("a.png").unwrap().bboxes.iter().map(|b| b.attribute(GROUP_OF_ATTR)).collect();
+        assert_eq!(flags, [Some("1"), Some("0")]);
+
+        let dets = AnnSet::from_iter([
+            Ann::new("a.png", None, vec![
+                BBox::new("person", 60.0, 60.0, 70.0, 70.0, Some(0.8)),
+                BBox::new("person", 80.0, 80.0, 90.0, 90.0, Some(0.7)),
+            ]),
+        ]);
+
+        // Both predictions fall in the group-of box and count as one true
+        // positive, the regular box is missed.
+        let person = COCOEval::new(&gts, &dets).openimages(None, 0.5).labels["person"];
+        assert_eq!((person.nb_gts, person.tp, person.fp), (2, 1, 0));
+        assert_eq!(person.ap, Some(0.5));
+    }
+
+    #[test]
+    fn verified_labels() {
+        let gts = gts();
+
+        // Cars are not verified in either image, people are verified in `b`.
+        let dets = AnnSet::from_iter([
+            Ann::new("a", None, vec![
+                BBox::new("person", 0.0, 0.0, 10.0, 10.0, Some(0.9)),
+                BBox::new("car", 0.0, 0.0, 10.0, 10.0, Some(0.9)),
+            ]),
+            Ann::new("b", None, vec![
+                BBox::new("person", 0.0, 0.0, 10.0, 10.0, Some(0.8)),
+                BBox::new("car", 0.0, 0.0, 10.0, 10.0, Some(0.9)),
+            ]),
+        ]);
+
+        let mut verified = VerifiedLabels::new();
+        verified.insert("b", "person", true);
+
+        let report = COCOEval::new(&gts, &dets).openimages(Some(&verified), 0.5);
+
+        let person = report.labels["person"];
+        assert_eq!((person.nb_gts, person.tp, person.fp), (2, 1, 1));
+
+        let car = report.labels["car"];
+        assert_eq!((car.ap, car.tp, car.fp), (None, 0, 0));
+
+        // Without verified labels every prediction counts.
+        let report = COCOEval::new(&gts, &dets).openimages(None, 0.5);
+        assert_eq!(report.labels["car"].fp, 2);
+    }
+
+    #[test]
+    fn verified_negative_label() {
+        let gts = gts();
+
+        let dets = AnnSet::from_iter([
+            Ann::new("b", None, vec![BBox::new("car", 0.0, 0.0, 10.0, 10.0, Some(0.9))]),
+        ]);
+
+        let mut verified = VerifiedLabels::new();
+        verified.insert("b", "car", false);
+
+        // Evaluated as a false positive although the image has no ground truth.
+        let car = COCOEval::new(&gts, &dets).openimages(Some(&verified), 0.5).labels["car"];
+        assert_eq!((car.ap, car.nb_gts, car.tp, car.fp), (None, 0, 0, 1));
+    }
+
+    #[test]
+    fn expand_verified_labels() {
+        let mut hierarchy = LabelHierarchy::new();
+        hierarchy.add("person", "human");
+        hierarchy.add("woman", "person");
+        hierarchy.add("man", "person");
+
+        let mut verified = VerifiedLabels::new();
+        verified.insert("a", "person", true);
+        verified.insert("b", "person", false);
+        verified.insert("c", "woman", true);
+        verified.insert("c", "person", false);
+        verified.expand_labels(&hierarchy);
+
+        // Positive labels go up, negative labels go down.
+        assert_eq!(verified.get("a", "human"), Some(true));
+        assert_eq!(verified.get("a", "woman"), None);
+        assert_eq!(verified.get("b", "woman"), Some(false));
+        assert_eq!(verified.get("b", "human"), None);
+
+        // Positive labels take precedence.
+        assert_eq!(verified.get("c", "person"), Some(true));
+        assert_eq!(verified.get("c", "man"), Some(false));
+        assert_eq!(verified.get("c", "woman"), Some(true));
+    }
+
+    #[test]
+    fn verified_labels_file() {
+        let path = test_dir("verified-labels").join("labels.csv");
+        std::fs::write(&path, "ImageID,Source,LabelName,Confidence\na,human,/m/cat,1\nb,human,/m/cat,0\n").unwrap();
+
+        let verified = VerifiedLabels::from_openimages(&path).unwrap();
+        assert_eq!(verified.get("a", "/m/cat"), Some(true));
+        assert_eq!(verified.get("b", "/m/cat"), Some(false));
+        assert_eq!(verified.get("c", "/m/cat"), None);
+
+        std::fs::write(&path, "ImageID,LabelName\na,/m/cat\n").unwrap();
+        assert!(VerifiedLabels::from_openimages(&path).is_err());
+    }
+}
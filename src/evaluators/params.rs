@@ -0,0 +1,414 @@
+use super::coco::{
+    COCOEval, EvalResult, ImgEval, fmt_metric,
+    AREA_ALL, AREA_SMALL, AREA_MEDIUM, AREA_LARGE, IOU_THRESHOLDS, MAX_DETS,
+};
+
+use crate::{
+    bbox::BBox,
+    imgsize::ImgSize,
+    parsers::ParseError,
+};
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    fs,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A named range of bounding box areas.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AreaRange {
+    pub name: String,
+    pub min: f32,
+    pub max: f32,
+
+    /// Whether the bounds are fractions of the image area instead of
+    /// pixel areas.
+    #[serde(default)]
+    pub relative: bool,
+}
+
+impl AreaRange {
+    /// Creates a range of pixel areas.
+    pub fn new<S: Into<String>>(name: S, min: f32, max: f32) -> Self {
+        AreaRange { name: name.into(), min, max, relative: false }
+    }
+
+    /// Creates a range of fractions of the image area.
+    pub fn relative<S: Into<String>>(name: S, min: f32, max: f32) -> Self {
+        AreaRange { name: name.into(), min, max, relative: true }
+    }
+
+    /// Whether the area of the bounding box is in the range, bounds
+    /// included.
+    ///
+    /// Boxes of relative ranges are never contained when the image size
+    /// is unknown or empty.
+    pub fn contains(&self, bbox: &BBox, img_size: Option<ImgSize>) -> bool {
+        let area = if self.relative {
+            match img_size {
+                Some(ImgSize { width, height }) if width > 0 && height > 0 =>
+                    bbox.area() / (width as f32 * height as f32),
+                _ => return false,
+            }
+        } else {
+            bbox.area()
+        };
+
+        self.min <= area && area <= self.max
+    }
+
+    fn is_valid(&self) -> bool {
+        0.0 <= self.min && self.min <= self.max
+    }
+}
+
+/// Custom evaluation parameters. The defaults are the COCO ones.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvalParams {
+    /// The IoU thresholds metrics are averaged over.
+    pub iou_thresholds: Vec<f32>,
+
+    /// Labels evaluated at a single IoU threshold instead of
+    /// `iou_thresholds`.
+    pub label_iou_thresholds: BTreeMap<String, f32>,
+
+    pub area_ranges: Vec<AreaRange>,
+
+    /// The maximum numbers of detections per image and label.
+    pub max_dets: Vec<usize>,
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        EvalParams {
+            iou_thresholds: IOU_THRESHOLDS.to_vec(),
+            label_iou_thresholds: BTreeMap::new(),
+            area_ranges: vec![
+                AreaRange::new("all", AREA_ALL.0, AREA_ALL.1),
+                AreaRange::new("small", AREA_SMALL.0, AREA_SMALL.1),
+                AreaRange::new("medium", AREA_MEDIUM.0, AREA_MEDIUM.1),
+                AreaRange::new("large", AREA_LARGE.0, AREA_LARGE.1),
+            ],
+            max_dets: MAX_DETS.to_vec(),
+        }
+    }
+}
+
+impl EvalParams {
+    /// Reads parameters from a JSON file, missing fields taking their
+    /// default value.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ParseError> {
+        let content = fs::read_to_string(path)
+            .map_err(|_| ParseError {})?;
+
+        serde_json::from_str(&content)
+            .map_err(|_| ParseError {})
+    }
+
+    /// Whether there is at least one IoU threshold, area range and maximum
+    /// number of detections, all thresholds are in `[0, 1]` and all area
+    /// ranges are valid.
+    pub fn is_valid(&self) -> bool {
+        let valid_thresh = |t: &f32| (0.0..=1.0).contains(t);
+
+        !self.iou_thresholds.is_empty()
+            && !self.area_ranges.is_empty()
+            && !self.max_dets.is_empty()
+            && self.iou_thresholds.iter().all(valid_thresh)
+            && self.label_iou_thresholds.values().all(valid_thresh)
+            && self.area_ranges.iter().all(AreaRange::is_valid)
+    }
+
+    /// The largest maximum number of detections.
+    pub(super) fn max_det(&self) -> usize {
+        self.max_dets.iter().copied().max().unwrap_or_default()
+    }
+}
+
+/// The results of one combination of parameters.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParamsResult {
+    /// The name of the area range.
+    pub area: String,
+
+    pub max_dets: usize,
+
+    /// The IoU threshold, `None` if averaged over all thresholds.
+    pub iou: Option<f32>,
+
+    pub result: EvalResult,
+}
+
+/// The results of an evaluation with custom parameters.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParamsEval {
+    pub params: EvalParams,
+
+    /// The results averaged over IoU thresholds for every area range and
+    /// maximum number of detections, followed by the results at every
+    /// IoU threshold for the first area range and the largest maximum
+    /// number of detections.
+    pub results: Vec<ParamsResult>,
+}
+
+impl COCOEval<'_> {
+    /// Evaluates with custom parameters.
+    ///
+    /// # Panics
+    /// Will panic if the parameters are invalid.
+    pub fn eval_params(&self, params: &EvalParams) -> ParamsEval {
+        assert!(params.is_valid(), "invalid evaluation parameters");
+
+        let boxes = self.group(params.max_det());
+
+        Self::summarize_params(params, |range| {
+            Self::match_range(&boxes, &params.iou_thresholds, &params.label_iou_thresholds, range)
+        })
+    }
+
+    /// Computes the metrics of every combination of parameters from the 
+    /// matching of each area range.
+    pub(super) fn summarize_params<K, F>(params: &EvalParams, mut match_range: F) -> ParamsEval where
+        K: AsRef<str> + Ord,
+        F: FnMut(&AreaRange) -> BTreeMap<K, Vec<ImgEval>>,
+    {
+        let max_det = params.max_det();
+        let threshs = &params.iou_thresholds;
+        let every = 0..threshs.len();
+
+        let mut results = vec![];
+        let mut first = None;
+
+        for range in &params.area_ranges {
+            let evals = match_range(range);
+
+            for &ndets in &params.max_dets {
+                results.push(ParamsResult {
+                    area: range.name.clone(),
+                    max_dets: ndets,
+                    iou: None,
+                    result: Self::metrics(&evals, every.clone(), ndets),
+                });
+            }
+
+            first.get_or_insert(evals);
+        }
+
+        if let Some(evals) = first {
+            for (t, &thresh) in threshs.iter().enumerate() {
+                results.push(ParamsResult {
+                    area: params.area_ranges[0].name.clone(),
+                    max_dets: max_det,
+                    iou: Some(thresh),
+                    result: Self::metrics(&evals, t..t + 1, max_det),
+                });
+            }
+        }
+
+        ParamsEval { params: params.clone(), results }
+    }
+}
+
+impl ParamsEval {
+    /// Exports the results as CSV with one row per combination of
+    /// parameters and label. Rows with an empty label hold the means over
+    /// labels and rows with an empty IoU are averaged over thresholds.
+    pub fn to_csv(&self) -> String {
+        let fmt = |v: Option<f32>| v.map(|v| v.to_string()).unwrap_or_default();
+
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        writer.write_record(["iou", "area", "max_dets", "label", "ap", "ar"])
+            .expect("writing to memory should not fail");
+
+        for res in &self.results {
+            let (iou, max_dets) = (fmt(res.iou), res.max_dets.to_string());
+            let rows = std::iter::once((String::new(), res.result.ap, res.result.ar))
+                .chain(res.result.labels.iter().map(|(label, e)| (label.clone(), e.ap, e.ar)));
+
+            for (label, ap, ar) in rows {
+                writer.write_record([&iou, &res.area, &max_dets, &label, &fmt(ap), &fmt(ar)])
+                    .expect("writing to memory should not fail");
+            }
+        }
+
+        let bytes = writer.into_inner()
+            .expect("writing to memory should not fail");
+
+        String::from_utf8(bytes).expect("CSV should be valid UTF-8")
+    }
+}
+
+impl Display for ParamsEval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let threshs = &self.params.iou_thresholds;
+        let all_threshs = match threshs.as_slice() {
+            [thresh] => format!("{thresh:.2}"),
+            _ => format!("{:.2}:{:.2}", threshs[0], threshs[threshs.len() - 1]),
+        };
+
+        writeln!(
+            f, "{:<10} {:>12} {:>8} {:>10} {:>10}",
+            "IoU", "Area", "MaxDets", "AP", "AR",
+        )?;
+
+        for res in &self.results {
+            let iou = res.iou.map_or(all_threshs.clone(), |t| format!("{t:.2}"));
+
+            writeln!(
+                f, "{:<10} {:>12} {:>8} {:>10} {:>10}",
+                iou, res.area, res.max_dets, fmt_metric(res.result.ap), fmt_metric(res.result.ar),
+            )?;
+        }
+
+        if !self.params.label_iou_thresholds.is_empty() {
+            writeln!(f, "\n{:<24} {:>10}", "Label", "IoU")?;
+
+            for (label, thresh) in &self.params.label_iou_thresholds {
+                writeln!(f, "{label:<24} {thresh:>10.2}")?;
+            }
+        }
+
+        // The first area range at the largest number of detections.
+        let max_det = self.params.max_det();
+        let main = self.results.iter()
+            .find(|res| res.iou.is_none() && res.max_dets == max_det);
+
+        if let Some(main) = main {
+            writeln!(
+                f, "\n{:<24} {:>10} {:>10} {:>10}",
+                "Label", "Boxes", "AP", "AR",
+            )?;
+
+            for (label, eval) in &main.result.labels {
+                writeln!(
+                    f, "{:<24} {:>10} {:>10} {:>10}",
+                    label, eval.nb_gts, fmt_metric(eval.ap), fmt_metric(eval.ar),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{annotation::Ann, annotationset::AnnSet, bbox::BBox, evaluators::params::*, path::test_dir};
+
+    fn gts() -> AnnSet {
+        AnnSet::from_iter([
+            Ann::new("a", Some(ImgSize::new(100, 100)), vec![
+                BBox::new("car", 0.0, 0.0, 10.0, 10.0, None),
+                BBox::new("person", 50.0, 50.0, 90.0, 90.0, None),
+            ]),
+        ])
+    }
+
+    /// Car IoU 0.64, person IoU 1.
+    fn dets() -> AnnSet {
+        AnnSet::from_iter([
+            Ann::new("a", Some(ImgSize::new(100, 100)), vec![
+                BBox::new("car", 0.0, 0.0, 8.0, 8.0, Some(0.9)),
+                BBox::new("person", 50.0, 50.0, 90.0, 90.0, Some(0.8)),
+            ]),
+        ])
+    }
+
+    fn params() -> EvalParams {
+        EvalParams {
+            iou_thresholds: vec![0.5, 0.7],
+            label_iou_thresholds: BTreeMap::new(),
+            area_ranges: vec![
+                AreaRange::new("all", 0.0, 1e10),
+                AreaRange::relative("tiny", 0.0, 0.05),
+            ],
+            max_dets: vec![1, 10],
+        }
+    }
+
+    #[test]
+    fn iou_thresholds() {
+        let (gts, dets) = (gts(), dets());
+        let res = COCOEval::new(&gts, &dets).eval_params(&params());
+
+        // Every area range and maximum number of detections, then every
+        // threshold.
+        assert_eq!(res.results.len(), 2 * 2 + 2);
+
+        // The car is matched at 0.5 only.
+        let all = &res.results[1].result;
+        assert_eq!(all.labels["car"].ap, Some(0.5));
+        assert_eq!(all.labels["person"].ap, Some(1.0));
+
+        assert_eq!(res.results[4].iou, Some(0.5));
+        assert_eq!(res.results[4].result.ap, Some(1.0));
+        assert_eq!(res.results[5].result.labels["car"].ap, Some(0.0));
+
+        // The same rows are reported without annotations.
+        let empty = AnnSet::new();
+        let res = COCOEval::new(&empty, &empty).eval_params(&params());
+        assert_eq!(res.results.len(), 2 * 2 + 2);
+        assert_eq!(res.to_csv().lines().count(), 1 + 6);
+    }
+
+    #[test]
+    fn relative_area_ranges() {
+        let (gts, dets) = (gts(), dets());
+        let res = COCOEval::new(&gts, &dets).eval_params(&params());
+
+        // Only the car is tiny.
+        let tiny = &res.results[3].result;
+        assert_eq!(tiny.labels["car"].nb_gts, 1);
+        assert_eq!(tiny.labels["person"].ap, None);
+
+        // Relative ranges contain nothing without image size.
+        let bbox = BBox::new("car", 0.0, 0.0, 10.0, 10.0, None);
+        assert!(AreaRange::relative("tiny", 0.0, 0.05).contains(&bbox, Some(ImgSize::new(100, 100))));
+        assert!(!AreaRange::relative("tiny", 0.0, 0.05).contains(&bbox, None));
+    }
+
+    #[test]
+    fn label_iou_thresholds() {
+        let (gts, dets) = (gts(), dets());
+
+        // A per-label threshold overrides every threshold.
+        let params = EvalParams {
+            label_iou_thresholds: BTreeMap::from([("car".to_string(), 0.6)]),
+            ..params()
+        };
+
+        let res = COCOEval::new(&gts, &dets).eval_params(&params);
+        assert_eq!(res.results[1].result.labels["car"].ap, Some(1.0));
+    }
+
+    #[test]
+    fn validity() {
+        assert!(EvalParams::default().is_valid());
+        assert!(params().is_valid());
+
+        assert!(!EvalParams { max_dets: vec![], ..params() }.is_valid());
+        assert!(!EvalParams { iou_thresholds: vec![1.5], ..params() }.is_valid());
+        assert!(!EvalParams { area_ranges: vec![AreaRange::new("bad", 10.0, 1.0)], ..params() }.is_valid());
+    }
+
+    #[test]
+    fn from_file() {
+        let path = test_dir("params").join("params.json");
+
+        fs::write(&path, r#"{"iou_thresholds": [0.5], "area_ranges": [{"name": "all", "min": 0, "max": 1e10}]}"#).unwrap();
+        let params = EvalParams::from_file(&path).unwrap();
+
+        assert_eq!(params.iou_thresholds, [0.5]);
+        assert!(!params.area_ranges[0].relative);
+        assert_eq!(params.max_dets, EvalParams::default().max_dets);
+
+        fs::write(&path, r#"{"iou_thresholds": 0.5}"#).unwrap();
+        assert!(EvalParams::from_file(&path).is_err());
+    }
+}
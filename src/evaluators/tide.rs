@@ -0,0 +1,475 @@
+use super::coco::{COCOEval, AREA_ALL, MAX_DETS, average_precision};
+
+use crate::bbox::BBox;
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+};
+
+use serde::Serialize;
+
+/// The TIDE error categories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TideError {
+    /// A prediction localised correctly but with the wrong label.
+    Classification,
+    /// A prediction with the right label but localised poorly.
+    Localization,
+    /// A prediction with the wrong label and localised poorly.
+    Both,
+    /// A prediction of a ground truth already matched by a more confident 
+    /// prediction.
+    Duplicate,
+    /// A prediction overlapping no ground truth.
+    Background,
+    /// A ground truth matched by no prediction nor explained by a
+    /// classification or localisation error.
+    Missed,
+}
+
+impl TideError {
+    fn name(self) -> &'static str {
+        match self {
+            TideError::Classification => "classification",
+            TideError::Localization => "localization",
+            TideError::Both => "both",
+            TideError::Duplicate => "duplicate",
+            TideError::Background => "background",
+            TideError::Missed => "missed",
+        }
+    }
+}
+
+/// The number of errors of a category and the mAP gained by fixing them.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ErrorStats {
+    pub count: usize,
+    pub dap: f32,
+}
+
+/// A TIDE error decomposition.
+#[derive(Debug, Clone, Serialize)]
+pub struct TideReport {
+    /// The IoU threshold above which a prediction is a true positive.
+    pub pos_thresh: f32,
+
+    /// The IoU threshold below which a prediction is a background error.
+    pub bg_thresh: f32,
+
+    /// The mAP at the positive threshold, `None` if there are no ground truths.
+    pub ap: Option<f32>,
+
+    pub classification: ErrorStats,
+    pub localization: ErrorStats,
+    pub both: ErrorStats,
+    pub duplicate: ErrorStats,
+    pub background: ErrorStats,
+    pub missed: ErrorStats,
+
+    /// The mAP gained by removing all false positives.
+    pub false_positives: ErrorStats,
+
+    /// The mAP gained by removing all false negatives.
+    pub false_negatives: ErrorStats,
+}
+
+/// A ground truth, identified by its label, image and index in its group.
+type GtKey<'a> = (&'a str, &'a str, usize);
+
+/// The status of a prediction after matching.
+#[derive(Clone, Copy)]
+enum Status<'a> {
+    TP,
+    /// A false positive with the ground truth it should have matched for
+    /// classification and localisation errors.
+    FP(TideError, Option<GtKey<'a>>),
+}
+
+struct Det<'a> {
+    label: &'a str,
+    score: f32,
+    status: Status<'a>,
+}
+
+/// What to fix when recomputing the mAP.
+#[derive(Clone, Copy, PartialEq)]
+enum Fix {
+    Error(TideError),
+    FalsePositives,
+    FalseNegatives,
+}
+
+impl COCOEval<'_> {
+    /// Decomposes the errors of the predictions following TIDE. 
+    /// 
+    /// Predictions are first matched at `pos_thresh` with the COCO matching. 
+    /// Each remaining false positive is then classified, in this order, as
+    /// a localisation error if it overlaps a ground truth of the same label
+    /// by at least `bg_thresh`, a classification error if it overlaps a 
+    /// ground truth of another label by at least `pos_thresh`, a duplicate
+    /// if it overlaps an already matched ground truth of the same label by 
+    /// at least `pos_thresh`, a "both" error if it overlaps a ground truth 
+    /// of another label by at least `bg_thresh` and a background error 
+    /// otherwise.
+    /// 
+    /// The dAP of each category is the mAP gained when fixing its errors:
+    /// classification and localisation errors become true positives of
+    /// their ground truth if not matched yet, other false positives are
+    /// removed and missed ground truths are ignored.
+    /// 
+    /// # Panics
+    /// Will panic if the thresholds are not in `[0, 1]`.
+    pub fn tide(&self, pos_thresh: f32, bg_thresh: f32) -> TideReport {
+        assert!(
+            Self::validate_args(pos_thresh, AREA_ALL) && Self::validate_args(bg_thresh, AREA_ALL), 
+            "invalid evaluation parameters"
+        );
+
+        let boxes = self.group(MAX_DETS[MAX_DETS.len() - 1]);
+        let evals = self.match_all(&boxes, &[pos_thresh], AREA_ALL);
+
+        // The ground truths of every label of each image, for cross-label overlaps.
+        let mut img_gts = HashMap::<&str, Vec<(GtKey, &BBox)>>::new();
+        for (&label, imgs) in &boxes {
+            for img in imgs {
+                let gts = img.gts.iter()
+                    .enumerate()
+                    .filter(|&(g, _)| !img.crowd[g])
+                    .map(|(g, &gt)| ((label, img.img_id, g), gt));
+                img_gts.entry(img.img_id).or_default().extend(gts);
+            }
+        }
+
+        let mut dets = vec![];
+        let mut nb_gts = HashMap::<&str, usize>::new();
+        let mut matched = HashSet::<GtKey>::new();
+        let mut explained = HashSet::<GtKey>::new();
+
+        for (&label, imgs) in &boxes {
+            for (img, eval) in imgs.iter().zip(&evals[label]) {
+                *nb_gts.entry(label).or_default() += eval.nb_gts;
+
+                for (d, &det) in img.dets.iter().enumerate() {
+                    if eval.ignored[0][d] {
+                        continue
+                    }
+
+                    let status = match eval.matches[0][d] {
+                        Some(g) => {
+                            matched.insert((label, img.img_id, g));
+                            Status::TP
+                        },
+                        None => {
+                            let (error, target) = self.classify(det, &img_gts[img.img_id], pos_thresh, bg_thresh);
+
+                            if let (TideError::Classification | TideError::Localization, Some(key)) = (error, target) {
+                                explained.insert(key);
+                            }
+
+                            Status::FP(error, target)
+                        },
+                    };
+
+                    dets.push(Det { label, score: eval.scores[d], status });
+                }
+            }
+        }
+
+        dets.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        // Ignored ground truths are never missed.
+        let mut missed = HashMap::<&str, usize>::new();
+        for (&label, imgs) in &boxes {
+            for (img, eval) in imgs.iter().zip(&evals[label]) {
+                for g in 0..img.gts.len() {
+                    let key = (label, img.img_id, g);
+
+                    if !eval.gt_ignored[g] && !matched.contains(&key) && !explained.contains(&key) {
+                        *missed.entry(label).or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        let ap = Self::fixed_map(&dets, &nb_gts, &missed, &matched, None);
+
+        let stats = |fix: Fix| {
+            let count = match fix {
+                Fix::Error(TideError::Missed) => missed.values().sum(),
+                Fix::Error(error) => dets.iter()
+                    .filter(|d| matches!(d.status, Status::FP(e, _) if e == error))
+                    .count(),
+                Fix::FalsePositives => dets.iter()
+                    .filter(|d| matches!(d.status, Status::FP(..)))
+                    .count(),
+                Fix::FalseNegatives => nb_gts.values().sum::<usize>() - matched.len(),
+            };
+
+            let fixed = Self::fixed_map(&dets, &nb_gts, &missed, &matched, Some(fix));
+            let dap = match (ap, fixed) {
+                (Some(ap), Some(fixed)) => fixed - ap,
+                _ => 0.0,
+            };
+
+            ErrorStats { count, dap }
+        };
+
+        TideReport {
+            pos_thresh,
+            bg_thresh,
+            ap,
+            classification: stats(Fix::Error(TideError::Classification)),
+            localization: stats(Fix::Error(TideError::Localization)),
+            both: stats(Fix::Error(TideError::Both)),
+            duplicate: stats(Fix::Error(TideError::Duplicate)),
+            background: stats(Fix::Error(TideError::Background)),
+            missed: stats(Fix::Error(TideError::Missed)),
+            false_positives: stats(Fix::FalsePositives),
+            false_negatives: stats(Fix::FalseNegatives),
+        }
+    }
+
+    /// Classifies a false positive given the ground truths of its image.
+    fn classify<'a>(
+        &self, 
+        det: &BBox, 
+        gts: &[(GtKey<'a>, &BBox)], 
+        pos_thresh: f32, 
+        bg_thresh: f32,
+    ) -> (TideError, Option<GtKey<'a>>) {
+        let best = |same_label: bool| {
+            gts.iter()
+                .filter(|(_, gt)| (gt.label == det.label) == same_label)
+                .map(|&(key, gt)| (key, self.iou_kind.compute(det, gt)))
+                .fold(None, |best: Option<(GtKey, f32)>, (key, iou)| match best {
+                    Some((_, best_iou)) if best_iou >= iou => best,
+                    _ => Some((key, iou)),
+                })
+        };
+
+        let same = best(true);
+        let other = best(false);
+
+        let same_iou = same.map_or(0.0, |s| s.1);
+        let other_iou = other.map_or(0.0, |o| o.1);
+
+        if bg_thresh <= same_iou && same_iou < pos_thresh {
+            (TideError::Localization, same.map(|s| s.0))
+        } else if other_iou >= pos_thresh {
+            (TideError::Classification, other.map(|o| o.0))
+        } else if same_iou >= pos_thresh {
+            (TideError::Duplicate, None)
+        } else if other_iou >= bg_thresh {
+            (TideError::Both, None)
+        } else {
+            (TideError::Background, None)
+        }
+    }
+
+    /// The mAP after applying the fix, `None` if there are no ground truths.
+    fn fixed_map(
+        dets: &[Det], 
+        nb_gts: &HashMap<&str, usize>, 
+        missed: &HashMap<&str, usize>,
+        matched: &HashSet<GtKey>,
+        fix: Option<Fix>,
+    ) -> Option<f32> {
+        let mut used = matched.clone();
+        let mut ranked = HashMap::<&str, Vec<(f32, bool)>>::new();
+
+        for det in dets {
+            let (label, is_tp) = match (det.status, fix) {
+                (Status::TP, _) => (det.label, true),
+                (Status::FP(..), Some(Fix::FalsePositives)) => continue,
+                (Status::FP(error, target), Some(Fix::Error(fixed))) if error == fixed => {
+                    match target {
+                        // Fixed errors of an already matched ground truth become duplicates.
+                        Some(key) if used.insert(key) => (key.0, true),
+                        _ => continue,
+                    }
+                },
+                (Status::FP(..), _) => (det.label, false),
+            };
+
+            ranked.entry(label).or_default().push((det.score, is_tp));
+        }
+
+        let aps: Vec<f32> = nb_gts.iter()
+            .filter_map(|(&label, &n)| {
+                let n = match fix {
+                    Some(Fix::Error(TideError::Missed)) => n - missed.get(label).copied().unwrap_or(0),
+                    Some(Fix::FalseNegatives) => matched.iter().filter(|k| k.0 == label).count(),
+                    _ => n,
+                };
+
+                let dets = ranked.get(label).map_or(&[][..], Vec::as_slice);
+
+                (n > 0).then(|| average_precision(dets, n).0)
+            })
+            .collect();
+
+        (!aps.is_empty()).then(|| aps.iter().sum::<f32>() / aps.len() as f32)
+    }
+}
+
+impl TideReport {
+    fn rows(&self) -> [(&'static str, &ErrorStats); 8] {
+        [
+            (TideError::Classification.name(), &self.classification),
+            (TideError::Localization.name(), &self.localization),
+            (TideError::Both.name(), &self.both),
+            (TideError::Duplicate.name(), &self.duplicate),
+            (TideError::Background.name(), &self.background),
+            (TideError::Missed.name(), &self.missed),
+            ("false_positives", &self.false_positives),
+            ("false_negatives", &self.false_negatives),
+        ]
+    }
+
+    /// Exports the decomposition as CSV, one row per error category.
+    pub fn to_csv(&self) -> String {
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        writer.write_record(["error", "count", "dap"])
+            .expect("writing to memory should not fail");
+
+        for (name, stats) in self.rows() {
+            writer.write_record([name.to_string(), stats.count.to_string(), stats.dap.to_string()])
+                .expect("writing to memory should not fail");
+        }
+
+        let bytes = writer.into_inner()
+            .expect("writing to memory should not fail");
+
+        String::from_utf8(bytes).expect("CSV should be valid UTF-8")
+    }
+}
+
+impl Display for TideReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f, "TIDE errors @[ IoU={:.2} | background IoU={:.2} ] AP = {}", 
+            self.pos_thresh, 
+            self.bg_thresh, 
+            self.ap.map_or(String::from("-"), |ap| format!("{ap:.3}")),
+        )?;
+
+        writeln!(f, "\n{:<24} {:>10} {:>10}", "Error", "Count", "dAP")?;
+        for (name, stats) in self.rows() {
+            writeln!(f, "{:<24} {:>10} {:>10.3}", name, stats.count, stats.dap)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        evaluators::tide::*,
+        annotation::Ann,
+        annotationset::AnnSet,
+    };
+
+    /// Decomposes the errors of the predictions on an image with a cat and
+    /// a dog.
+    fn tide(dets: Vec<BBox>) -> TideReport {
+        let gts = AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None),
+                BBox::new("dog", 20.0, 20.0, 30.0, 30.0, None),
+            ]),
+        ]);
+
+        let dets = AnnSet::from_iter([Ann::new("a.jpg", None, dets)]);
+
+        COCOEval::new(&gts, &dets).tide(0.5, 0.1)
+    }
+
+    /// Two true positives and the given false positive.
+    fn tide_with(error: BBox) -> TideReport {
+        tide(vec![
+            BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.9)),
+            BBox::new("dog", 20.0, 20.0, 30.0, 30.0, Some(0.8)),
+            error,
+        ])
+    }
+
+    /// The error categories with a non-zero count.
+    fn errors(report: &TideReport) -> Vec<&'static str> {
+        report.rows()[..6].iter()
+            .filter(|(_, stats)| stats.count > 0)
+            .map(|&(name, _)| name)
+            .collect()
+    }
+
+    #[test]
+    fn classification() {
+        let report = tide_with(BBox::new("bird", 20.0, 20.0, 30.0, 30.0, Some(0.7)));
+        assert_eq!(errors(&report), [TideError::Classification.name()]);
+    }
+
+    #[test]
+    fn localization() {
+        let report = tide_with(BBox::new("dog", 20.0, 20.0, 30.0, 24.0, Some(0.7)));
+        assert_eq!(errors(&report), [TideError::Localization.name()]);
+    }
+
+    #[test]
+    fn both() {
+        let report = tide_with(BBox::new("cat", 20.0, 20.0, 30.0, 23.0, Some(0.7)));
+        assert_eq!(errors(&report), [TideError::Both.name()]);
+    }
+
+    #[test]
+    fn duplicate() {
+        let report = tide_with(BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.7)));
+        assert_eq!(errors(&report), [TideError::Duplicate.name()]);
+
+        // Ranked below the true positive, the duplicate does not lower AP.
+        assert_eq!(report.duplicate.dap, 0.0);
+    }
+
+    #[test]
+    fn background() {
+        let report = tide_with(BBox::new("cat", 90.0, 90.0, 99.0, 99.0, Some(0.7)));
+        assert_eq!(errors(&report), [TideError::Background.name()]);
+        assert_eq!(report.background.dap, 0.0);
+
+        // Ranked above the true positive, it does.
+        let report = tide_with(BBox::new("cat", 90.0, 90.0, 99.0, 99.0, Some(0.95)));
+        assert!(report.background.dap > 0.0);
+        assert_eq!(report.false_positives.dap, report.background.dap);
+    }
+
+    #[test]
+    fn missed() {
+        let report = tide(vec![BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.9))]);
+
+        assert_eq!(errors(&report), [TideError::Missed.name()]);
+        assert_eq!(report.false_negatives.count, 1);
+        assert!(report.missed.dap > 0.0);
+        assert_eq!(report.false_negatives.dap + report.ap.unwrap(), 1.0);
+
+        // Without any ground truth left the fixed mAP is undefined.
+        let report = tide(vec![]);
+        assert_eq!(report.missed.count, 2);
+        assert_eq!(report.false_negatives.dap, 0.0);
+    }
+
+    #[test]
+    fn csv() {
+        let report = tide_with(BBox::new("bird", 20.0, 20.0, 30.0, 30.0, Some(0.7)));
+
+        // A header and one row per error category and group, even when
+        // nothing is evaluated.
+        assert_eq!(report.to_csv().lines().count(), 1 + 8);
+
+        let empty = AnnSet::new();
+        let report = COCOEval::new(&empty, &empty).tide(0.5, 0.1);
+        assert_eq!(report.ap, None);
+        assert_eq!(report.to_csv().lines().count(), 1 + 8);
+    }
+}
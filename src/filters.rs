@@ -0,0 +1,267 @@
+use crate::{
+    annotation::Ann,
+    annotationset::AnnSet,
+    bbox::BBox,
+};
+
+use std::collections::HashMap;
+
+fn in_range(value: f32, (min, max): (f32, f32)) -> bool {
+    min <= value && value <= max
+}
+
+/// The width over height ratio, infinite for boxes with a null height.
+fn aspect_ratio(bbox: &BBox) -> f32 {
+    match bbox.height() {
+        0.0 => f32::INFINITY,
+        height => bbox.width() / height,
+    }
+}
+
+impl Ann {
+    /// Removes the detections, only keeping the ground truths.
+    pub fn drop_detections(&mut self) {
+        self.bboxes.retain(BBox::is_ground_truth);
+    }
+
+    /// Removes the ground truths, only keeping the detections.
+    pub fn drop_ground_truths(&mut self) {
+        self.bboxes.retain(BBox::is_detection);
+    }
+
+    /// Removes the detections with a confidence below the threshold. 
+    /// Ground truths are kept.
+    pub fn filter_conf(&mut self, min_conf: f32) {
+        self.bboxes.retain(|b| b.conf().is_none_or(|c| c >= min_conf));
+    }
+
+    /// Only keeps the `k` most confident detections. Ground truths are kept
+    /// and the order of the remaining bounding boxes is preserved.
+    pub fn keep_top_k(&mut self, k: usize) {
+        let mut confs: Vec<f32> = self.bboxes.iter().filter_map(BBox::conf).collect();
+
+        if confs.len() <= k {
+            return
+        }
+
+        confs.sort_by(|a, b| b.total_cmp(a));
+
+        // Detections tied with the k-th one are kept in order of appearance.
+        let min_conf = if k == 0 { f32::INFINITY } else { confs[k - 1] };
+        let mut nb_ties = confs[..k].iter().filter(|&&c| c == min_conf).count();
+
+        self.bboxes.retain(|b| match b.conf() {
+            None => true,
+            Some(c) if c > min_conf => true,
+            Some(c) if c == min_conf && nb_ties > 0 => { nb_ties -= 1; true },
+            Some(_) => false,
+        });
+    }
+
+    /// Only keeps the `k` most confident detections of each label. 
+    /// Ground truths are kept and the order of the remaining bounding 
+    /// boxes is preserved.
+    pub fn keep_top_k_per_label(&mut self, k: usize) {
+        let mut by_label = HashMap::<&str, Vec<usize>>::new();
+
+        for (i, bbox) in self.bboxes.iter().enumerate().filter(|(_, b)| b.is_detection()) {
+            by_label.entry(bbox.label.as_str()).or_default().push(i);
+        }
+
+        let mut dropped = vec![false; self.bboxes.len()];
+
+        for mut idxs in by_label.into_values() {
+            // Stable sort so that ties are kept in order of appearance.
+            idxs.sort_by(|&a, &b| {
+                self.bboxes[b].conf().unwrap().total_cmp(&self.bboxes[a].conf().unwrap())
+            });
+
+            for &i in idxs.iter().skip(k) {
+                dropped[i] = true;
+            }
+        }
+
+        let mut dropped = dropped.into_iter();
+        self.bboxes.retain(|_| !dropped.next().unwrap());
+    }
+
+    /// Only keeps the bounding boxes with an area, in pixels, in the range.
+    pub fn filter_area(&mut self, range: (f32, f32)) {
+        self.bboxes.retain(|b| in_range(b.area(), range));
+    }
+
+    /// Only keeps the bounding boxes with an area, relative to the image 
+    /// area, in the range. 
+    /// 
+    /// Does nothing if the image size is unknown or empty.
+    pub fn filter_rel_area(&mut self, range: (f32, f32)) {
+        if let Some(size) = self.img_size.filter(|s| s.width > 0 && s.height > 0) {
+            let img_area = size.width as f32 * size.height as f32;
+            self.bboxes.retain(|b| in_range(b.area() / img_area, range));
+        }
+    }
+
+    /// Only keeps the bounding boxes with a width and a height, in pixels,
+    /// in the ranges.
+    pub fn filter_size(&mut self, width: (f32, f32), height: (f32, f32)) {
+        self.bboxes.retain(|b| in_range(b.width(), width) && in_range(b.height(), height));
+    }
+
+    /// Only keeps the bounding boxes with a width over height ratio in 
+    /// the range.
+    pub fn filter_aspect_ratio(&mut self, range: (f32, f32)) {
+        self.bboxes.retain(|b| in_range(aspect_ratio(b), range));
+    }
+}
+
+// Annotations are kept even if all their bounding boxes are removed.
+impl AnnSet {
+    /// Only keeps the bounding boxes for which the predicate is true.
+    pub fn retain_bboxes<F: FnMut(&BBox) -> bool>(&mut self, mut f: F) {
+        self.anns_mut().for_each(|ann| ann.bboxes.retain(&mut f));
+    }
+
+    /// Removes the detections, only keeping the ground truths.
+    pub fn drop_detections(&mut self) {
+        self.anns_mut().for_each(Ann::drop_detections);
+    }
+
+    /// Removes the ground truths, only keeping the detections.
+    pub fn drop_ground_truths(&mut self) {
+        self.anns_mut().for_each(Ann::drop_ground_truths);
+    }
+
+    /// Removes the detections with a confidence below the threshold.
+    pub fn filter_conf(&mut self, min_conf: f32) {
+        self.anns_mut().for_each(|ann| ann.filter_conf(min_conf));
+    }
+
+    /// Only keeps the `k` most confident detections of each image.
+    pub fn keep_top_k(&mut self, k: usize) {
+        self.anns_mut().for_each(|ann| ann.keep_top_k(k));
+    }
+
+    /// Only keeps the `k` most confident detections of each label in 
+    /// each image.
+    pub fn keep_top_k_per_label(&mut self, k: usize) {
+        self.anns_mut().for_each(|ann| ann.keep_top_k_per_label(k));
+    }
+
+    /// Only keeps the bounding boxes with an area, in pixels, in the range.
+    pub fn filter_area(&mut self, range: (f32, f32)) {
+        self.anns_mut().for_each(|ann| ann.filter_area(range));
+    }
+
+    /// Only keeps the bounding boxes with an area, relative to the image 
+    /// area, in the range. 
+    /// 
+    /// Annotations without image size are left unchanged.
+    pub fn filter_rel_area(&mut self, range: (f32, f32)) {
+        self.anns_mut().for_each(|ann| ann.filter_rel_area(range));
+    }
+
+    /// Only keeps the bounding boxes with a width and a height, in pixels,
+    /// in the ranges.
+    pub fn filter_size(&mut self, width: (f32, f32), height: (f32, f32)) {
+        self.anns_mut().for_each(|ann| ann.filter_size(width, height));
+    }
+
+    /// Only keeps the bounding boxes with a width over height ratio in 
+    /// the range.
+    pub fn filter_aspect_ratio(&mut self, range: (f32, f32)) {
+        self.anns_mut().for_each(|ann| ann.filter_aspect_ratio(range));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        filters::*,
+        imgsize::ImgSize,
+    };
+
+    fn ann() -> Ann {
+        Ann::new("a.jpg", Some(ImgSize::new(100, 100)), vec![
+            BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.6)),
+            BBox::new("dog", 0.0, 0.0, 50.0, 10.0, Some(0.9)),
+            BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None),
+            BBox::new("cat", 0.0, 0.0, 20.0, 40.0, Some(0.3)),
+            BBox::new("dog", 0.0, 0.0, 10.0, 10.0, Some(0.6)),
+        ])
+    }
+
+    fn confs(ann: &Ann) -> Vec<Option<f32>> {
+        ann.bboxes.iter().map(|b| b.conf()).collect()
+    }
+
+    #[test]
+    fn conf_and_kind() {
+        let mut ann = ann();
+        ann.filter_conf(0.5);
+        assert_eq!(confs(&ann), [Some(0.6), Some(0.9), None, Some(0.6)]);
+
+        let mut gts = ann.clone();
+        gts.drop_detections();
+        assert_eq!(confs(&gts), [None]);
+
+        ann.drop_ground_truths();
+        assert_eq!(confs(&ann), [Some(0.6), Some(0.9), Some(0.6)]);
+    }
+
+    #[test]
+    fn top_k() {
+        let mut ann = ann();
+        ann.keep_top_k(2);
+        assert_eq!(confs(&ann), [Some(0.6), Some(0.9), None]);
+
+        let mut ann = self::ann();
+        ann.keep_top_k_per_label(1);
+        assert_eq!(confs(&ann), [Some(0.6), Some(0.9), None]);
+
+        let mut ann = self::ann();
+        ann.keep_top_k(0);
+        assert_eq!(confs(&ann), [None]);
+    }
+
+    #[test]
+    fn size() {
+        let mut ann = ann();
+        ann.filter_area((200.0, f32::INFINITY));
+        assert_eq!(confs(&ann), [Some(0.9), Some(0.3)]);
+
+        let mut ann = self::ann();
+        ann.filter_rel_area((0.0, 0.01));
+        assert_eq!(ann.bboxes.len(), 3);
+
+        let mut ann = self::ann();
+        ann.filter_size((0.0, 30.0), (15.0, 100.0));
+        assert_eq!(confs(&ann), [Some(0.3)]);
+
+        let mut ann = self::ann();
+        ann.filter_aspect_ratio((2.0, f32::INFINITY));
+        assert_eq!(confs(&ann), [Some(0.9)]);
+    }
+
+    #[test]
+    fn degenerate_sizes() {
+        let mut ann = Ann::new("a.jpg", Some(ImgSize::new(100_000, 100_000)), vec![
+            BBox::new("cat", 0.0, 0.0, 10.0, 0.0, Some(0.9)),
+            BBox::new("cat", 0.0, 0.0, 0.0, 0.0, Some(0.8)),
+            BBox::new("cat", 0.0, 0.0, 1000.0, 1000.0, Some(0.7)),
+        ]);
+
+        // Null heights give infinite ratios, even for null widths.
+        let mut ratios = ann.clone();
+        ratios.filter_aspect_ratio((1.0, f32::INFINITY));
+        assert_eq!(confs(&ratios), [Some(0.9), Some(0.8), Some(0.7)]);
+
+        // The image area does not fit in a `u32`.
+        ann.filter_rel_area((1e-5, 1.0));
+        assert_eq!(confs(&ann), [Some(0.7)]);
+
+        let mut empty = self::ann();
+        empty.img_size = Some(ImgSize::new(0, 100));
+        empty.filter_rel_area((0.0, 0.01));
+        assert_eq!(empty.bboxes.len(), 5);
+    }
+}
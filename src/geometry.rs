@@ -0,0 +1,156 @@
+use crate::{
+    annotation::Ann,
+    annotationset::AnnSet,
+    bbox::BBox,
+};
+
+impl Ann {
+    /// Clips the bounding boxes to the image bounds. 
+    /// 
+    /// Does nothing if the image size is unknown.
+    pub fn clip_bboxes(&mut self) {
+        if let Some(img_size) = self.img_size {
+            self.bboxes.iter_mut().for_each(|b| b.clip(img_size));
+        }
+    }
+
+    /// Removes the bounding boxes which are not entirely inside the image.
+    /// 
+    /// Does nothing if the image size is unknown.
+    pub fn drop_bboxes_outside(&mut self) {
+        if let Some(img_size) = self.img_size {
+            self.bboxes.retain(|b| b.is_inside(img_size));
+        }
+    }
+
+    /// Scales the bounding boxes and the image size, for instance after
+    /// an image resize, see `BBox::scale`. The image size is rounded to 
+    /// the nearest pixel.
+    pub fn scale_bboxes(&mut self, sx: f32, sy: f32) {
+        self.bboxes.iter_mut().for_each(|b| b.scale(sx, sy));
+
+        if let Some(img_size) = self.img_size.as_mut() {
+            img_size.width = (img_size.width as f32 * sx).round() as u32;
+            img_size.height = (img_size.height as f32 * sy).round() as u32;
+        }
+    }
+
+    /// Moves the bounding boxes, see `BBox::translate`.
+    pub fn translate_bboxes(&mut self, dx: f32, dy: f32) {
+        self.bboxes.iter_mut().for_each(|b| b.translate(dx, dy));
+    }
+
+    /// Pads the bounding boxes by a number of pixels, see `BBox::pad`.
+    pub fn pad_bboxes(&mut self, dx: f32, dy: f32) {
+        self.bboxes.iter_mut().for_each(|b| b.pad(dx, dy));
+    }
+
+    /// Pads the bounding boxes by a ratio of their size, see `BBox::pad_ratio`.
+    pub fn pad_bboxes_ratio(&mut self, ratio: f32) {
+        self.bboxes.iter_mut().for_each(|b| b.pad_ratio(ratio));
+    }
+
+    /// Converts the bounding boxes from the inclusive to the exclusive
+    /// pixel convention, see `BBox::inclusive_to_exclusive`.
+    pub fn inclusive_to_exclusive(&mut self) {
+        self.bboxes.iter_mut().for_each(BBox::inclusive_to_exclusive);
+    }
+
+    /// Converts the bounding boxes from the exclusive to the inclusive
+    /// pixel convention, see `BBox::exclusive_to_inclusive`.
+    pub fn exclusive_to_inclusive(&mut self) {
+        self.bboxes.iter_mut().for_each(BBox::exclusive_to_inclusive);
+    }
+}
+
+impl AnnSet {
+    /// Clips the bounding boxes to their image bounds. 
+    /// 
+    /// Annotations without image size are left unchanged.
+    pub fn clip_bboxes(&mut self) {
+        self.anns_mut().for_each(Ann::clip_bboxes);
+    }
+
+    /// Removes the bounding boxes which are not entirely inside their image.
+    /// 
+    /// Annotations without image size are left unchanged.
+    pub fn drop_bboxes_outside(&mut self) {
+        self.anns_mut().for_each(Ann::drop_bboxes_outside);
+    }
+
+    /// Scales all the bounding boxes and image sizes, see `Ann::scale_bboxes`.
+    pub fn scale_bboxes(&mut self, sx: f32, sy: f32) {
+        self.anns_mut().for_each(|ann| ann.scale_bboxes(sx, sy));
+    }
+
+    /// Moves all the bounding boxes, see `BBox::translate`.
+    pub fn translate_bboxes(&mut self, dx: f32, dy: f32) {
+        self.anns_mut().for_each(|ann| ann.translate_bboxes(dx, dy));
+    }
+
+    /// Pads all the bounding boxes by a number of pixels, see `BBox::pad`.
+    pub fn pad_bboxes(&mut self, dx: f32, dy: f32) {
+        self.anns_mut().for_each(|ann| ann.pad_bboxes(dx, dy));
+    }
+
+    /// Pads all the bounding boxes by a ratio of their size, see `BBox::pad_ratio`.
+    pub fn pad_bboxes_ratio(&mut self, ratio: f32) {
+        self.anns_mut().for_each(|ann| ann.pad_bboxes_ratio(ratio));
+    }
+
+    /// Converts all the bounding boxes from the inclusive to the exclusive
+    /// pixel convention, see `BBox::inclusive_to_exclusive`.
+    pub fn inclusive_to_exclusive(&mut self) {
+        self.anns_mut().for_each(Ann::inclusive_to_exclusive);
+    }
+
+    /// Converts all the bounding boxes from the exclusive to the inclusive
+    /// pixel convention, see `BBox::exclusive_to_inclusive`.
+    pub fn exclusive_to_inclusive(&mut self) {
+        self.anns_mut().for_each(Ann::exclusive_to_inclusive);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        geometry::*,
+        imgsize::ImgSize,
+    };
+
+    #[test]
+    fn clip_and_drop() {
+        let mut anns = AnnSet::from_iter([
+            Ann::new("a.jpg", Some(ImgSize::new(10, 10)), vec![
+                BBox::new("", -5.0, 0.0, 5.0, 5.0, None),
+                BBox::new("", 2.0, 2.0, 8.0, 8.0, None),
+            ]),
+            Ann::new("b.jpg", None, vec![
+                BBox::new("", -5.0, 0.0, 5.0, 5.0, None),
+            ]),
+        ]);
+
+        let mut dropped = anns.clone();
+        dropped.drop_bboxes_outside();
+        assert_eq!(dropped.get("a.jpg").unwrap().bboxes.len(), 1);
+        assert_eq!(dropped.get("b.jpg").unwrap().bboxes.len(), 1);
+
+        anns.clip_bboxes();
+        assert!(anns.get("a.jpg").unwrap().bboxes[0].ltrb() == (0.0, 0.0, 5.0, 5.0));
+        assert!(anns.get("b.jpg").unwrap().bboxes[0].ltrb() == (-5.0, 0.0, 5.0, 5.0));
+    }
+
+    #[test]
+    fn scale_img_size() {
+        let mut ann = Ann::new("a.jpg", Some(ImgSize::new(10, 15)), vec![
+            BBox::new("", 2.0, 2.0, 8.0, 14.0, None),
+        ]);
+
+        ann.scale_bboxes(2.0, 0.5);
+        assert_eq!(ann.img_size, Some(ImgSize::new(20, 8)));
+
+        // The box is still inside the resized image.
+        ann.clip_bboxes();
+        assert!(ann.bboxes[0].ltrb() == (4.0, 1.0, 16.0, 7.0));
+    }
+}
@@ -4,7 +4,7 @@ use std::path::Path;
 use imagesize::{size, ImageSize};
 
 /// An image size.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ImgSize {
     /// The image width.
     pub width: u32,
@@ -0,0 +1,384 @@
+use crate::{
+    annotation::Ann,
+    annotationset::AnnSet,
+    parsers::ParseError,
+    serde_records::openimage::OAHierarchy,
+};
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+/// A letter case to normalise labels to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelCase {
+    Lower,
+    Upper,
+}
+
+/// A mapping from old labels to new labels.
+/// 
+/// Mapping several labels to the same new label merges them.
+pub type LabelMap = HashMap<String, String>;
+
+/// Reads a label mapping from a CSV file without header where each row
+/// is `old_label,new_label`.
+pub fn read_label_map<P: AsRef<Path>>(path: P) -> Result<LabelMap, ParseError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .map_err(|_| ParseError {})?;
+
+    reader.deserialize::<(String, String)>()
+        .map(|r| r.map_err(|_| ParseError {}))
+        .collect()
+}
+
+/// The label given to every bounding box by `Ann::make_agnostic`.
+pub const AGNOSTIC_LABEL: &str = "object";
+
+/// A hierarchy of labels where each label can have several parents.
+#[derive(Debug, Clone, Default)]
+pub struct LabelHierarchy {
+    parents: HashMap<String, Vec<String>>,
+    children: HashMap<String, Vec<String>>,
+}
+
+impl LabelHierarchy {
+    pub fn new() -> Self {
+        LabelHierarchy::default()
+    }
+
+    /// Adds `parent` as a parent of `child`.
+    pub fn add<C: Into<String>, P: Into<String>>(&mut self, child: C, parent: P) {
+        let (child, parent) = (child.into(), parent.into());
+        let parents = self.parents.entry(child.clone()).or_default();
+
+        if !parents.contains(&parent) {
+            parents.push(parent.clone());
+            self.children.entry(parent).or_default().push(child);
+        }
+    }
+
+    /// The direct parents of the label.
+    pub fn parents(&self, label: &str) -> &[String] {
+        self.parents.get(label).map_or(&[], Vec::as_slice)
+    }
+
+    /// The direct children of the label.
+    pub fn children(&self, label: &str) -> &[String] {
+        self.children.get(label).map_or(&[], Vec::as_slice)
+    }
+
+    /// The ancestors of the label, closest first and without duplicates.
+    pub fn ancestors<'h>(&'h self, label: &str) -> Vec<&'h str> {
+        Self::closure(&self.parents, label)
+    }
+
+    /// The descendants of the label, closest first and without duplicates.
+    pub fn descendants<'h>(&'h self, label: &str) -> Vec<&'h str> {
+        Self::closure(&self.children, label)
+    }
+
+    /// The labels reachable from `label` in breadth-first order.
+    fn closure<'h>(edges: &'h HashMap<String, Vec<String>>, label: &str) -> Vec<&'h str> {
+        let next = |label: &str| edges.get(label).map_or(&[][..], Vec::as_slice);
+
+        let mut reached: Vec<&str> = next(label).iter().map(String::as_str).collect();
+        let mut i = 0;
+
+        while let Some(&current) = reached.get(i) {
+            for other in next(current) {
+                if other != label && !reached.contains(&other.as_str()) {
+                    reached.push(other);
+                }
+            }
+
+            i += 1;
+        }
+
+        reached
+    }
+
+    /// Reads the OpenImages class hierarchy JSON, where each node has a
+    /// `LabelName` and optional `Subcategory` children. The root, which 
+    /// only groups every class, is not part of the hierarchy and `Part`
+    /// relations are ignored, as in the OpenImages challenge metric.
+    pub fn from_openimages<P: AsRef<Path>>(path: P) -> Result<Self, ParseError> {
+        let content = fs::read_to_string(path)
+            .map_err(|_| ParseError {})?;
+
+        let root: OAHierarchy = serde_json::from_str(&content)
+            .map_err(|_| ParseError {})?;
+
+        let mut hierarchy = LabelHierarchy::new();
+        let mut nodes: Vec<&OAHierarchy> = root.children.iter().collect();
+
+        while let Some(node) = nodes.pop() {
+            for child in &node.children {
+                hierarchy.add(&child.label, &node.label);
+                nodes.push(child);
+            }
+        }
+
+        Ok(hierarchy)
+    }
+}
+
+impl Ann {
+    /// Renames every bounding box label to `AGNOSTIC_LABEL`, except the
+    /// labels in `except` such as those of ignore regions.
+    pub fn make_agnostic(&mut self, except: &HashSet<String>) {
+        for bbox in &mut self.bboxes {
+            if !except.contains(&bbox.label) {
+                bbox.label = AGNOSTIC_LABEL.to_string();
+            }
+        }
+    }
+
+    /// Adds a copy of each bounding box for each ancestor of its label.
+    pub fn expand_labels(&mut self, hierarchy: &LabelHierarchy) {
+        let expanded: Vec<_> = self.bboxes.iter()
+            .flat_map(|bbox| hierarchy.ancestors(&bbox.label).into_iter().map(|label| {
+                let mut bbox = bbox.clone();
+                bbox.label = label.to_string();
+                bbox
+            }))
+            .collect();
+
+        self.bboxes.extend(expanded);
+    }
+
+    /// Renames the bounding box labels present in the mapping. Other 
+    /// labels are left unchanged.
+    pub fn map_labels(&mut self, mapping: &LabelMap) {
+        for bbox in &mut self.bboxes {
+            if let Some(label) = mapping.get(&bbox.label) {
+                bbox.label.clone_from(label);
+            }
+        }
+    }
+
+    /// Removes the bounding boxes with one of the given labels.
+    pub fn drop_labels(&mut self, labels: &HashSet<String>) {
+        self.bboxes.retain(|b| !labels.contains(&b.label));
+    }
+
+    /// Only keeps the bounding boxes with one of the given labels.
+    pub fn keep_labels(&mut self, labels: &HashSet<String>) {
+        self.bboxes.retain(|b| labels.contains(&b.label));
+    }
+
+    /// Converts the bounding box labels to the given case.
+    pub fn normalize_label_case(&mut self, case: LabelCase) {
+        for bbox in &mut self.bboxes {
+            bbox.label = match case {
+                LabelCase::Lower => bbox.label.to_lowercase(),
+                LabelCase::Upper => bbox.label.to_uppercase(),
+            };
+        }
+    }
+}
+
+impl AnnSet {
+    /// Renames the bounding box labels present in the mapping. Other 
+    /// labels are left unchanged.
+    pub fn map_labels(&mut self, mapping: &LabelMap) {
+        self.anns_mut().for_each(|ann| ann.map_labels(mapping));
+    }
+
+    /// Removes the bounding boxes with one of the given labels. 
+    /// 
+    /// Annotations are kept even if all their bounding boxes are removed.
+    pub fn drop_labels(&mut self, labels: &HashSet<String>) {
+        self.anns_mut().for_each(|ann| ann.drop_labels(labels));
+    }
+
+    /// Only keeps the bounding boxes with one of the given labels.
+    /// 
+    /// Annotations are kept even if all their bounding boxes are removed.
+    pub fn keep_labels(&mut self, labels: &HashSet<String>) {
+        self.anns_mut().for_each(|ann| ann.keep_labels(labels));
+    }
+
+    /// Converts the bounding box labels to the given case.
+    pub fn normalize_label_case(&mut self, case: LabelCase) {
+        self.anns_mut().for_each(|ann| ann.normalize_label_case(case));
+    }
+
+    /// Renames every bounding box label to `AGNOSTIC_LABEL`, except the
+    /// labels in `except`.
+    pub fn make_agnostic(&mut self, except: &HashSet<String>) {
+        self.anns_mut().for_each(|ann| ann.make_agnostic(except));
+    }
+
+    /// Adds a copy of each bounding box for each ancestor of its label.
+    pub fn expand_labels(&mut self, hierarchy: &LabelHierarchy) {
+        self.anns_mut().for_each(|ann| ann.expand_labels(hierarchy));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        labels::*,
+        bbox::BBox,
+        evaluators::coco::COCOEval,
+        path::test_dir,
+    };
+
+    fn ann() -> Ann {
+        Ann::new("a.jpg", None, vec![
+            BBox::new("Cat", 0.0, 0.0, 1.0, 1.0, None),
+            BBox::new("kitten", 0.0, 0.0, 1.0, 1.0, None),
+            BBox::new("dog", 0.0, 0.0, 1.0, 1.0, None),
+        ])
+    }
+
+    fn labels(ann: &Ann) -> Vec<&str> {
+        ann.bboxes.iter().map(|b| b.label.as_str()).collect()
+    }
+
+    #[test]
+    fn map_and_filter() {
+        let mut ann = ann();
+        ann.normalize_label_case(LabelCase::Lower);
+        assert_eq!(labels(&ann), ["cat", "kitten", "dog"]);
+
+        let mapping = LabelMap::from([(String::from("kitten"), String::from("cat"))]);
+        ann.map_labels(&mapping);
+        assert_eq!(labels(&ann), ["cat", "cat", "dog"]);
+
+        let mut kept = ann.clone();
+        kept.keep_labels(&HashSet::from([String::from("dog")]));
+        assert_eq!(labels(&kept), ["dog"]);
+
+        ann.drop_labels(&HashSet::from([String::from("dog")]));
+        assert_eq!(labels(&ann), ["cat", "cat"]);
+    }
+
+    fn hierarchy() -> LabelHierarchy {
+        let mut hierarchy = LabelHierarchy::new();
+        hierarchy.add("kitten", "cat");
+        hierarchy.add("cat", "animal");
+        hierarchy.add("kitten", "pet");
+        hierarchy.add("pet", "animal");
+        hierarchy
+    }
+
+    #[test]
+    fn ancestors_and_descendants() {
+        let hierarchy = hierarchy();
+
+        assert_eq!(hierarchy.parents("kitten"), ["cat", "pet"]);
+        assert_eq!(hierarchy.children("animal"), ["cat", "pet"]);
+        assert_eq!(hierarchy.ancestors("kitten"), ["cat", "pet", "animal"]);
+        assert_eq!(hierarchy.descendants("animal"), ["cat", "pet", "kitten"]);
+
+        assert!(hierarchy.ancestors("animal").is_empty());
+        assert!(hierarchy.descendants("kitten").is_empty());
+        assert!(hierarchy.ancestors("dog").is_empty());
+    }
+
+    #[test]
+    fn duplicate_and_cyclic_edges() {
+        let mut hierarchy = hierarchy();
+        hierarchy.add("kitten", "cat");
+        assert_eq!(hierarchy.parents("kitten"), ["cat", "pet"]);
+        assert_eq!(hierarchy.children("cat"), ["kitten"]);
+
+        hierarchy.add("animal", "kitten");
+        assert_eq!(hierarchy.ancestors("kitten"), ["cat", "pet", "animal"]);
+        assert_eq!(hierarchy.ancestors("animal"), ["kitten", "cat", "pet"]);
+    }
+
+    #[test]
+    fn from_openimages() {
+        let path = test_dir("hierarchy").join("hierarchy.json");
+        std::fs::write(&path, r#"{
+            "LabelName": "/m/0bl9f",
+            "Subcategory": [
+                {"LabelName": "/m/animal", "Subcategory": [
+                    {"LabelName": "/m/cat", "Subcategory": [{"LabelName": "/m/kitten"}]},
+                    {"LabelName": "/m/dog", "Part": [{"LabelName": "/m/tail"}]}
+                ]},
+                {"LabelName": "/m/car"}
+            ]
+        }"#).unwrap();
+
+        let hierarchy = LabelHierarchy::from_openimages(&path).unwrap();
+        assert_eq!(hierarchy.ancestors("/m/kitten"), ["/m/cat", "/m/animal"]);
+        assert!(hierarchy.ancestors("/m/animal").is_empty());
+        assert!(hierarchy.ancestors("/m/car").is_empty());
+        assert!(hierarchy.ancestors("/m/tail").is_empty());
+        assert!(hierarchy.descendants("/m/0bl9f").is_empty());
+
+        std::fs::write(&path, r#"{"Subcategory": []}"#).unwrap();
+        assert!(LabelHierarchy::from_openimages(&path).is_err());
+    }
+
+    #[test]
+    fn expand_labels() {
+        let mut expanded = ann();
+        expanded.expand_labels(&hierarchy());
+        assert_eq!(labels(&expanded), ["Cat", "kitten", "dog", "cat", "pet", "animal"]);
+
+        let mut unchanged = ann();
+        unchanged.expand_labels(&LabelHierarchy::new());
+        assert_eq!(labels(&unchanged), labels(&ann()));
+
+        let mut empty = Ann::empty("a.jpg");
+        empty.expand_labels(&hierarchy());
+        assert!(empty.bboxes.is_empty());
+    }
+
+    #[test]
+    fn make_agnostic() {
+        let mut agnostic = ann();
+        agnostic.make_agnostic(&HashSet::from([String::from("dog")]));
+        assert_eq!(labels(&agnostic), [AGNOSTIC_LABEL, AGNOSTIC_LABEL, "dog"]);
+
+        let mut agnostic = ann();
+        agnostic.make_agnostic(&HashSet::new());
+        assert_eq!(labels(&agnostic), [AGNOSTIC_LABEL; 3]);
+    }
+
+    #[test]
+    fn agnostic_evaluation() {
+        let mut gts = AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None)]),
+        ]);
+
+        let mut dets = AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![BBox::new("dog", 0.0, 0.0, 10.0, 10.0, Some(0.9))]),
+        ]);
+
+        assert_eq!(COCOEval::new(&gts, &dets).eval_all().ap.ap, Some(0.0));
+
+        gts.make_agnostic(&HashSet::new());
+        dets.make_agnostic(&HashSet::new());
+        assert_eq!(COCOEval::new(&gts, &dets).eval_all().ap.ap, Some(1.0));
+    }
+
+    #[test]
+    fn hierarchical_evaluation() {
+        let hierarchy = hierarchy();
+
+        let mut gts = AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![BBox::new("kitten", 0.0, 0.0, 10.0, 10.0, None)]),
+        ]);
+
+        // A prediction of a parent label matches the expanded ground truth.
+        let dets = AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.9))]),
+        ]);
+
+        gts.expand_labels(&hierarchy);
+        let summary = COCOEval::new(&gts, &dets).eval_all();
+        assert_eq!(summary.ap.labels["cat"].ap, Some(1.0));
+        assert_eq!(summary.ap.labels["kitten"].ap, Some(0.0));
+    }
+}
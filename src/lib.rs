@@ -4,6 +4,9 @@ pub mod imgsize;
 pub mod bbox;
 pub mod annotation;
 pub mod annotationset;
+pub mod labels;
+pub mod geometry;
+pub mod filters;
 
 pub mod parsers;
 pub mod converters;
@@ -12,6 +15,11 @@ pub mod evaluators;
 pub mod cli;
 
 pub mod evaluation;
+pub mod stats;
+pub mod validation;
+pub mod split;
+pub mod postprocess;
 
 pub(crate) mod serde_records;
-pub(crate) mod path;
\ No newline at end of file
+pub(crate) mod path;
+pub(crate) mod rng;
\ No newline at end of file
@@ -0,0 +1,154 @@
+use crate::{
+    imgsize::ImgSize,
+    bbox::{BBox, BBoxFmt},
+    annotation::Ann,
+    annotationset::AnnSet,
+    parsers::ParseError,
+    serde_records::cityscapes::*,
+};
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use itertools::Itertools;
+use serde_json::from_str;
+
+fn ltwh(coords: &[f32]) -> Result<(f32, f32, f32, f32), ParseError> {
+    match coords[..] {
+        [l, t, w, h] if w >= 0.0 && h >= 0.0 => Ok((l, t, w, h)),
+        _ => Err(ParseError {}),
+    }
+}
+
+impl TryFrom<CSObj> for BBox {
+    type Error = ParseError;
+
+    fn try_from(obj: CSObj) -> Result<Self, Self::Error> {
+        let mut bbox = BBox::create(obj.label, ltwh(&obj.bbox)?, BBoxFmt::LTWH, None);
+
+        if let Some(id) = obj.instance_id {
+            bbox.set_attribute("instance_id", id.to_string());
+        }
+
+        if let Some(bbox_vis) = obj.bbox_vis {
+            ltwh(&bbox_vis)?;
+            bbox.set_attribute("bbox_vis", bbox_vis.iter().join(" "));
+        }
+
+        Ok(bbox)
+    }
+}
+
+impl Ann {
+    /// Parses a Cityscapes bounding box annotation file such as
+    /// `aachen_000000_000019_gtBboxCityPersons.json`.
+    /// 
+    /// The image identifier is the matching image name, e.g. 
+    /// `aachen_000000_000019_leftImg8bit.png`. The instance identifier
+    /// and the visible part of objects are kept as the `instance_id` and
+    /// `bbox_vis` attributes.
+    pub fn parse_cityscapes<P: AsRef<Path>>(path: P) -> Result<Ann, ParseError> {
+        let name = path.as_ref()
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or(ParseError {})?;
+
+        let stem = name.rfind(ANN_INFIX)
+            .map(|i| &name[..i])
+            .ok_or(ParseError {})?;
+
+        let img_id = format!("{stem}{IMG_SUFFIX}");
+
+        let content = fs::read_to_string(path)
+            .map_err(|_| ParseError {})?;
+
+        let ann: CSAnn = from_str(&content)
+            .map_err(|_| ParseError {})?;
+
+        let img_size = ImgSize::new(ann.img_width, ann.img_height);
+
+        let bboxes = ann.objects.into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Ann::new(img_id, Some(img_size), bboxes))
+    }
+}
+
+/// Recursively lists Cityscapes bounding box annotation files, which
+/// are stored in one sub-directory per city.
+fn find_ann_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), ParseError> {
+    // Sorted for a deterministic annotation order.
+    let mut entries = path.read_dir()
+        .map_err(|_| ParseError {})?
+        .map(|e| e.map(|e| e.path()).map_err(|_| ParseError {}))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    entries.sort();
+
+    for p in entries {
+        if p.is_dir() {
+            find_ann_files(&p, files)?;
+        } else if p.extension().is_some_and(|e| e == "json") && 
+            p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.contains(ANN_INFIX)) 
+        {
+            files.push(p);
+        }
+    }
+
+    Ok(())
+}
+
+impl AnnSet {
+    /// Parses a directory of Cityscapes bounding box annotations, 
+    /// including its sub-directories.
+    pub fn parse_cityscapes<P: AsRef<Path>>(path: P) -> Result<AnnSet, ParseError> {
+        let mut files = vec![];
+        find_ann_files(path.as_ref(), &mut files)?;
+
+        let mut annset = AnnSet::with_capacity(files.len());
+
+        for p in files {
+            annset.insert(Ann::parse_cityscapes(p)?);
+        }
+
+        Ok(annset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parsers::cityscapes::*, path::test_dir};
+
+    #[test]
+    fn city_directories() {
+        let dir = test_dir("cityscapes");
+        let city = dir.join("train").join("aachen");
+        fs::create_dir_all(&city).unwrap();
+
+        fs::write(city.join("aachen_000000_000019_gtBboxCityPersons.json"), r#"{
+            "imgWidth": 2048, "imgHeight": 1024,
+            "objects": [
+                {"label": "pedestrian", "instanceId": 24000, "bbox": [10, 20, 30, 60], "bboxVis": [12, 20, 20, 50]},
+                {"label": "ignore", "instanceId": 0, "bbox": [100, 200, 50, 50]}
+            ]
+        }"#).unwrap();
+
+        // Not a bounding box annotation file.
+        fs::write(city.join("aachen_000000_000019_gtFine_polygons.json"), "{}").unwrap();
+
+        let annset = AnnSet::parse_cityscapes(&dir).unwrap();
+        assert_eq!(annset.len(), 1);
+
+        let ann = annset.get("aachen_000000_000019_leftImg8bit.png").unwrap();
+        assert_eq!(ann.img_size, Some(ImgSize::new(2048, 1024)));
+        assert_eq!(ann.bboxes.len(), 2);
+
+        let pedestrian = &ann.bboxes[0];
+        assert_eq!(pedestrian.ltrb(), (10.0, 20.0, 40.0, 80.0));
+        assert_eq!(pedestrian.attribute("instance_id"), Some("24000"));
+        assert_eq!(pedestrian.attribute("bbox_vis"), Some("12 20 20 50"));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     imgsize::ImgSize,
-    bbox::{BBox, BBoxFmt},
+    bbox::{BBox, BBoxFmt, CROWD_ATTR},
     annotation::Ann,
     annotationset::AnnSet,
     parsers::ParseError,
@@ -64,14 +64,18 @@ impl AnnSet {
                 .ok_or(ParseError {})?;
             
             if let [l, t, w, h] = coco_ann.bbox[..] {
-                let bbox = BBox::create(
+                let mut bbox = BBox::create(
                     String::from(*label), 
                     (l, t, w, h),
                     BBoxFmt::LTWH,
                     coco_ann.conf,
                 );
 
-                let ann = annset.get_mut(*img_id)
+                if let Some(iscrowd) = coco_ann.iscrowd {
+                    bbox.set_attribute(CROWD_ATTR, iscrowd.to_string());
+                }
+
+                let ann = annset.get_mut(img_id)
                     .expect("Image id must be present.");
                 
                 ann.bboxes.push(bbox);
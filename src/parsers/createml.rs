@@ -0,0 +1,94 @@
+use crate::{
+    imgsize::ImgSize,
+    bbox::{BBox, BBoxFmt},
+    annotation::Ann,
+    annotationset::AnnSet,
+    parsers::ParseError,
+    serde_records::createml::*,
+};
+
+use std::{fs, path::Path};
+
+use serde_json::from_str;
+
+impl TryFrom<CMLObj> for BBox {
+    type Error = ParseError;
+
+    fn try_from(obj: CMLObj) -> Result<Self, Self::Error> {
+        if let Some(conf) = obj.confidence {
+            if !(0.0..=1.0).contains(&conf) {
+                return Err(ParseError {})
+            }
+        }
+
+        let CMLCoords { x, y, width, height } = obj.coordinates;
+
+        if width < 0.0 || height < 0.0 {
+            return Err(ParseError {})
+        }
+
+        Ok(BBox::create(obj.label, (x, y, width, height), BBoxFmt::XYWH, obj.confidence))
+    }
+}
+
+impl AnnSet {
+    /// Parses a CreateML JSON annotation file.
+    /// 
+    /// CreateML does not store the image size, it is read from the
+    /// image headers if `imgs_path` is provided.
+    pub fn parse_createml<P1: AsRef<Path>, P2: AsRef<Path>>(
+        path: P1,
+        imgs_path: Option<P2>,
+    ) -> Result<AnnSet, ParseError> {
+        let content = fs::read_to_string(path)
+            .map_err(|_| ParseError {})?;
+
+        let cml_anns = from_str::<Vec<CMLAnn>>(&content)
+            .map_err(|_| ParseError {})?;
+
+        let mut annset = AnnSet::with_capacity(cml_anns.len());
+
+        for cml_ann in cml_anns {
+            let img_size = match imgs_path.as_ref() {
+                Some(p) => Some(ImgSize::from_file(p.as_ref().join(&cml_ann.image))?),
+                None => None,
+            };
+
+            let bboxes = cml_ann.annotations.into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            annset.insert(Ann::new(cml_ann.image, img_size, bboxes));
+        }
+
+        Ok(annset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parsers::createml::*, path::test_dir};
+
+    #[test]
+    fn round_trip() {
+        let annset = AnnSet::from_iter([
+            Ann::new("a.jpg", None, vec![
+                BBox::new("cat", 10.0, 20.0, 50.0, 80.0, None),
+                BBox::new("dog", 0.0, 0.0, 100.0, 40.0, Some(0.75)),
+            ]),
+            Ann::new("b.jpg", None, vec![]),
+        ]);
+
+        let path = test_dir("createml-round-trip").join("anns.json");
+        annset.save_createml(&path).unwrap();
+
+        let parsed = AnnSet::parse_createml(&path, None::<&Path>).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert!(parsed.get("b.jpg").unwrap().bboxes.is_empty());
+
+        let bboxes = &parsed.get("a.jpg").unwrap().bboxes;
+        assert_eq!(bboxes[0].ltrb(), (10.0, 20.0, 50.0, 80.0));
+        assert_eq!(bboxes[1].label, "dog");
+        assert_eq!(bboxes[1].conf(), Some(0.75));
+    }
+}
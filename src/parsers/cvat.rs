@@ -130,13 +130,11 @@ fn parse_cvat<P: AsRef<Path>>(path: P) -> Result<AnnSet, ParseError> {
                 }
             },
 
-            Ok(Event::End(data)) => {
-                if data.name().as_ref() == b"image" {
-                    if let Some(a) = ann.take() {
-                        anns.insert(a);
-                    } else {
-                        panic!("Ann should not be None at this point.")
-                    }
+            Ok(Event::End(data)) if data.name().as_ref() == b"image" => {
+                if let Some(a) = ann.take() {
+                    anns.insert(a);
+                } else {
+                    panic!("Ann should not be None at this point.")
                 }
             },
 
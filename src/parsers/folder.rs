@@ -8,8 +8,10 @@ use std::{
     path::{Path, PathBuf},
 };
 
+/// Lists the files with the given extension, sorted by path since the
+/// directory listing order is platform-dependent.
 fn read_dir<P: AsRef<Path>>(path: P, file_ext: &str) -> Result<Vec<PathBuf>, ParseError> {
-    path.as_ref()
+    let mut files = path.as_ref()
         .read_dir()
         .map_err(|_| ParseError {})?
         .filter_map(|result| {
@@ -28,7 +30,11 @@ fn read_dir<P: AsRef<Path>>(path: P, file_ext: &str) -> Result<Vec<PathBuf>, Par
                 }
             }
         })
-        .collect::<Result<Vec<_>, _>>()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    files.sort();
+
+    Ok(files)
 }
 
 impl AnnSet {
@@ -1,6 +1,6 @@
 use crate::{
     imgsize::ImgSize, 
-    bbox::BBox, 
+    bbox::{BBox, DIFFICULT_ATTR},
     annotation::Ann, 
     annotationset::AnnSet,
     parsers::ParseError,
@@ -18,14 +18,20 @@ impl From<InetObj> for BBox {
     fn from(obj: InetObj) -> Self {
         let bndbox = obj.bndbox;
 
-        BBox::new(
+        let mut bbox = BBox::new(
             obj.name,
             bndbox.xmin,
             bndbox.ymin,
             bndbox.xmax,
             bndbox.ymax,
             None,
-        )
+        );
+
+        if let Some(difficult) = obj.difficult {
+            bbox.set_attribute(DIFFICULT_ATTR, difficult.to_string());
+        }
+
+        bbox
     }
 }
 
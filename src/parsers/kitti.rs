@@ -0,0 +1,182 @@
+use crate::{
+    imgsize::ImgSize,
+    bbox::BBox,
+    annotation::Ann,
+    annotationset::AnnSet,
+    parsers::{ParseError, path_to_img_id},
+    serde_records::kitti::*,
+};
+
+use std::{fs, path::Path};
+
+fn parse_kitti_line(line: &str) -> Result<BBox, ParseError> {
+    let fields = line.split_whitespace().collect::<Vec<_>>();
+
+    let score = match fields.len() {
+        NB_FIELDS_GT => None,
+        NB_FIELDS_DET => {
+            let score = fields[NB_FIELDS_GT].parse::<f32>()
+                .map_err(|_| ParseError {})?;
+
+            if score.is_nan() {
+                return Err(ParseError {})
+            }
+
+            Some(score)
+        },
+        _ => return Err(ParseError {}),
+    };
+
+    let label = fields[0];
+
+    let coords = fields[4..8].iter()
+        .map(|c| c.parse::<f32>().map_err(|_| ParseError {}))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (xmin, ymin, xmax, ymax) = (coords[0], coords[1], coords[2], coords[3]);
+
+    // Also rejects NaN coordinates.
+    if !(xmin <= xmax && ymin <= ymax) {
+        return Err(ParseError {})
+    }
+
+    let mut bbox = BBox::new(label, xmin, ymin, xmax, ymax, score.map(score_to_conf));
+
+    if score.is_some() {
+        bbox.set_attribute(SCORE_ATTR, fields[NB_FIELDS_GT]);
+    }
+
+    for (name, value) in PRE_BOX_ATTRS.iter().zip(&fields[1..4]) {
+        bbox.set_attribute(*name, *value);
+    }
+
+    for (name, value) in POST_BOX_ATTRS.iter().zip(&fields[8..NB_FIELDS_GT]) {
+        bbox.set_attribute(*name, *value);
+    }
+
+    Ok(bbox)
+}
+
+fn parse_kitti_str(content: &str, keep_dont_care: bool) -> Result<Vec<BBox>, ParseError> {
+    content.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(parse_kitti_line)
+        .filter(|b| {
+            keep_dont_care || !matches!(b, Ok(b) if b.label == DONT_CARE)
+        })
+        .collect()
+}
+
+impl Ann {
+    /// Parses a KITTI object detection label file.
+    /// 
+    /// The 2D box is used as the bounding box while the truncation,
+    /// occlusion, observation angle and 3D fields are kept as attributes.
+    /// `DontCare` regions are discarded unless `keep_dont_care` is set.
+    /// 
+    /// KITTI detection scores are unbounded, they are mapped to `0..=1` 
+    /// with the logistic function and the raw score is kept as the 
+    /// `score` attribute.
+    pub fn parse_kitti<P: AsRef<Path>>(
+        path: P,
+        img_size: Option<ImgSize>,
+        img_ext: &str,
+        keep_dont_care: bool,
+    ) -> Result<Ann, ParseError> {
+        let img_id = path_to_img_id(path.as_ref(), img_ext)?;
+
+        // Label files are small, faster to read them at once.
+        let content = fs::read_to_string(path)
+            .map_err(|_| ParseError {})?;
+
+        let bboxes = parse_kitti_str(&content, keep_dont_care)?;
+
+        Ok(Ann::new(img_id, img_size, bboxes))
+    }
+}
+
+impl AnnSet {
+    /// Parses a folder of KITTI object detection label files.
+    /// 
+    /// KITTI labels do not store the image size, it is read from the
+    /// image headers if `imgs_path` is provided.
+    pub fn parse_kitti<P1: AsRef<Path>, P2: AsRef<Path>>(
+        path: P1,
+        imgs_path: Option<P2>,
+        img_ext: &str,
+        keep_dont_care: bool,
+    ) -> Result<AnnSet, ParseError> {
+        let imgs_path = imgs_path
+            .map(|p| p.as_ref().to_path_buf());
+
+        AnnSet::parse_folder(path, "txt", |p| {
+            let img_size = if let Some(imgs_path) = imgs_path.as_ref() {
+                let img_id = path_to_img_id(p, img_ext)?;
+                let mut img_path = imgs_path.clone();
+                img_path.push(&img_id);
+
+                Some(ImgSize::from_file(img_path)?)
+            } else { None };
+
+            Ann::parse_kitti(p, img_size, img_ext, keep_dont_care)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::kitti::*;
+
+    const LINES: &str = "\
+Pedestrian 0.00 0 -0.20 712.40 143.00 810.73 307.92 1.89 0.48 1.20 1.84 1.47 8.41 0.01
+Car 0.88 3 -0.69 0.00 192.37 402.31 374.00 1.60 1.57 3.23 -2.70 1.74 3.68 -1.29
+DontCare -1 -1 -10 800.38 163.67 825.45 184.07 -1 -1 -1 -1000 -1000 -1000 -10";
+
+    #[test]
+    fn round_trip() {
+        let bboxes = parse_kitti_str(LINES, true).unwrap();
+        assert_eq!(bboxes.len(), 3);
+
+        let car = &bboxes[1];
+        assert_eq!(car.ltrb(), (0.0, 192.37, 402.31, 374.0));
+        assert_eq!(car.attribute("truncated"), Some("0.88"));
+        assert_eq!(car.attribute("occluded"), Some("3"));
+        assert_eq!(car.attribute("rotation_y"), Some("-1.29"));
+
+        let ann = Ann::new("000000", None, bboxes);
+        let saved = ann.to_kitti().unwrap();
+        assert_eq!(saved, LINES);
+
+        let bboxes = parse_kitti_str(&saved, false).unwrap();
+        assert_eq!(bboxes.len(), 2);
+        assert!(bboxes.iter().all(|b| b.label != DONT_CARE));
+    }
+
+    #[test]
+    fn unbounded_scores() {
+        let lines = "\
+Car -1 -1 -10 10.00 10.00 50.00 50.00 -1 -1 -1 -1000 -1000 -1000 -10 -1.5
+Car -1 -1 -10 20.00 20.00 60.00 60.00 -1 -1 -1 -1000 -1000 -1000 -10 7.25";
+
+        let bboxes = parse_kitti_str(lines, false).unwrap();
+        let (low, high) = (bboxes[0].conf().unwrap(), bboxes[1].conf().unwrap());
+
+        assert!(0.0 < low && low < 0.5);
+        assert!(0.5 < high && high <= 1.0);
+        assert_eq!(bboxes[0].attribute(SCORE_ATTR), Some("-1.5"));
+
+        let ann = Ann::new("000000", None, bboxes);
+        assert_eq!(ann.to_kitti().unwrap(), lines);
+    }
+
+    #[test]
+    fn invalid_lines() {
+        let inverted = "Car -1 -1 -10 50.00 10.00 10.00 50.00 -1 -1 -1 -1000 -1000 -1000 -10";
+        assert!(parse_kitti_line(inverted).is_err());
+
+        let nan_score = "Car -1 -1 -10 10.00 10.00 50.00 50.00 -1 -1 -1 -1000 -1000 -1000 -10 NaN";
+        assert!(parse_kitti_line(nan_score).is_err());
+
+        assert!(parse_kitti_line("Car 0 0 0 1 2 3 4").is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{
+    imgsize::ImgSize,
+    bbox::{BBox, BBoxFmt},
+    annotation::Ann,
+    annotationset::AnnSet,
+    parsers::ParseError,
+    serde_records::labelstudio::*,
+};
+
+use std::{fs, path::Path};
+
+use serde_json::{from_str, from_value, Value};
+
+impl LSTask {
+    /// The image identifier is the file name of the task image URL.
+    fn img_id(&self) -> Result<String, ParseError> {
+        let url = match self.data.get("image") {
+            Some(Value::String(url)) => url,
+            _ => self.data.values()
+                .find_map(|v| v.as_str())
+                .ok_or(ParseError {})?,
+        };
+
+        let name = url.rsplit('/')
+            .next()
+            .filter(|n| !n.is_empty())
+            .ok_or(ParseError {})?;
+
+        Ok(String::from(name))
+    }
+}
+
+impl LSResult {
+    fn img_size(&self) -> Option<ImgSize> {
+        Some(ImgSize::new(self.original_width?, self.original_height?))
+    }
+
+    /// Returns `None` if the result is not a rectangle.
+    fn to_bbox(&self, conf: Option<f32>) -> Result<Option<BBox>, ParseError> {
+        if self.kind != RECT_LABELS {
+            return Ok(None)
+        }
+
+        let value: LSValue = from_value(self.value.clone())
+            .map_err(|_| ParseError {})?;
+
+        // Rotated rectangles cannot be represented by a `BBox`.
+        if value.rotation != 0.0 {
+            return Err(ParseError {})
+        }
+
+        if let Some(conf) = conf {
+            if !(0.0..=1.0).contains(&conf) {
+                return Err(ParseError {})
+            }
+        }
+
+        let label = value.labels.first().ok_or(ParseError {})?;
+        let img_size = self.img_size().ok_or(ParseError {})?;
+
+        let coords = (
+            value.x / 100.0, 
+            value.y / 100.0, 
+            value.width / 100.0, 
+            value.height / 100.0,
+        );
+
+        let bbox = BBox::create_rel(label.as_str(), coords, BBoxFmt::LTWH, conf, img_size);
+
+        Ok(Some(bbox))
+    }
+}
+
+fn parse_labelstudio<P: AsRef<Path>>(path: P, predictions: bool) -> Result<AnnSet, ParseError> {
+    let content = fs::read_to_string(path)
+        .map_err(|_| ParseError {})?;
+
+    let tasks = from_str::<Vec<LSTask>>(&content)
+        .map_err(|_| ParseError {})?;
+
+    let mut annset = AnnSet::with_capacity(tasks.len());
+
+    for task in &tasks {
+        let img_id = task.img_id()?;
+
+        // Results with their confidence score. Only the first annotation
+        // not cancelled is used when a task has been annotated several times.
+        let results = if predictions {
+            task.predictions.iter()
+                .flat_map(|p| p.result.iter().map(|r| (r, r.score.or(p.score))))
+                .collect::<Vec<_>>()
+        } else {
+            task.annotations.iter()
+                .find(|a| !a.was_cancelled)
+                .map(|a| a.result.iter().map(|r| (r, None)).collect())
+                .unwrap_or_default()
+        };
+
+        let img_size = results.iter()
+            .find_map(|(r, _)| r.img_size());
+
+        let mut bboxes = Vec::with_capacity(results.len());
+
+        for (result, conf) in results {
+            if let Some(bbox) = result.to_bbox(conf)? {
+                bboxes.push(bbox);
+            }
+        }
+
+        // Tasks of the same image are merged.
+        match annset.get_mut(&img_id) {
+            Some(ann) => {
+                ann.img_size = ann.img_size.or(img_size);
+                ann.bboxes.extend(bboxes);
+            },
+            None => { annset.insert(Ann::new(img_id, img_size, bboxes)); },
+        }
+    }
+
+    Ok(annset)
+}
+
+impl AnnSet {
+    /// Parses the annotations of a Label Studio JSON export.
+    /// 
+    /// Only rectangle results are kept. The image identifier is the 
+    /// file name of the task image and the image size is read from 
+    /// the results, thus tasks without any result have no image size.
+    /// The bounding boxes of tasks with the same image are merged.
+    pub fn parse_labelstudio<P: AsRef<Path>>(path: P) -> Result<AnnSet, ParseError> {
+        parse_labelstudio(path, false)
+    }
+
+    /// Parses the predictions of a Label Studio JSON file.
+    /// 
+    /// The confidence score of a bounding box is the result score if any, 
+    /// else the prediction score. Results without score are parsed as 
+    /// ground truths.
+    pub fn parse_labelstudio_predictions<P: AsRef<Path>>(path: P) -> Result<AnnSet, ParseError> {
+        parse_labelstudio(path, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{coords::Coords, parsers::labelstudio::*, path::test_dir};
+
+    const CAR: &str = r#"{
+        "id": "r1", "type": "rectanglelabels",
+        "original_width": 200, "original_height": 100,
+        "value": {"x": 10, "y": 20, "width": 50, "height": 30, "rectanglelabels": ["car"]}
+    }"#;
+
+    const PERSON: &str = r#"{
+        "id": "r2", "type": "rectanglelabels",
+        "original_width": 200, "original_height": 100,
+        "value": {"x": 0, "y": 0, "width": 25, "height": 50, "rectanglelabels": ["person"]}
+    }"#;
+
+    fn task(results: &[&str]) -> String {
+        format!(
+            r#"{{"data": {{"image": "/data/upload/1/a.jpg"}}, "annotations": [{{"result": [{}]}}]}}"#,
+            results.join(","),
+        )
+    }
+
+    fn parse(name: &str, tasks: &[String]) -> AnnSet {
+        let path = test_dir(name).join("export.json");
+        fs::write(&path, format!("[{}]", tasks.join(","))).unwrap();
+
+        AnnSet::parse_labelstudio(&path).unwrap()
+    }
+
+    fn bboxes(ann: &Ann) -> Vec<(&str, Coords)> {
+        ann.bboxes.iter().map(|b| (b.label.as_str(), b.ltrb())).collect()
+    }
+
+    #[test]
+    fn relation_results() {
+        let relation = r#"{"type": "relation", "from_id": "r1", "to_id": "r2", "direction": "right"}"#;
+        let annset = parse("labelstudio-relations", &[task(&[CAR, relation, PERSON])]);
+
+        let ann = annset.get("a.jpg").unwrap();
+        assert_eq!(ann.img_size, Some(ImgSize::new(200, 100)));
+        assert_eq!(bboxes(ann), [("car", (20.0, 20.0, 120.0, 50.0)), ("person", (0.0, 0.0, 50.0, 50.0))]);
+    }
+
+    #[test]
+    fn duplicate_tasks() {
+        let annset = parse("labelstudio-duplicates", &[task(&[CAR]), task(&[PERSON])]);
+        assert_eq!(annset.len(), 1);
+
+        let ann = annset.get("a.jpg").unwrap();
+        assert_eq!(bboxes(ann), [("car", (20.0, 20.0, 120.0, 50.0)), ("person", (0.0, 0.0, 50.0, 50.0))]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let annset = parse("labelstudio-round-trip", &[task(&[CAR, PERSON])]);
+
+        let path = test_dir("labelstudio-round-trip-saved").join("tasks.json");
+        annset.save_labelstudio(&path, "/data/upload/1/").unwrap();
+
+        let tasks: Vec<LSTask> = from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let result = &tasks[0].annotations[0].result[0];
+        let value: LSValue = from_value(result.value.clone()).unwrap();
+
+        assert_eq!((result.original_width, result.original_height), (Some(200), Some(100)));
+        let percents = [value.x, value.y, value.width, value.height];
+        assert!(percents.iter().zip([10.0, 20.0, 50.0, 30.0]).all(|(p, e)| (p - e).abs() < 1e-4));
+
+        let parsed = AnnSet::parse_labelstudio(&path).unwrap();
+        let (ann, saved) = (annset.get("a.jpg").unwrap(), parsed.get("a.jpg").unwrap());
+
+        assert_eq!(saved.img_size, ann.img_size);
+        assert_eq!(bboxes(saved), bboxes(ann));
+    }
+}
@@ -7,10 +7,7 @@ use crate::{
     serde_records::openimage::*,
 };
 
-use std::{
-    path::{Path},
-    collections::hash_map::Entry,
-};
+use std::path::Path;
 
 use csv;
 
@@ -42,38 +39,37 @@ impl AnnSet {
             let line: OALine = raw_record.deserialize(Some(&headers))
                 .map_err(|_| ParseError {})?;
 
-            let img_id = String::from(line.img_id);
             let coords = (line.xmin, line.ymin, line.xmax, line.ymax);
 
-            // TODO: Could avoid to String.clone() when key is present.
-            match annset.items.entry(img_id.clone()) {
-                Entry::Occupied(mut oe) => {
-                    let ann = oe.get_mut();
-
-                    let img_size = ann.img_size
-                        .expect("Image size should have been populated during Ann init.");
-                    
-                    let bbox = BBox::create_rel(
-                        line.label, 
-                        coords,
-                        BBoxFmt::LTRB, 
-                        line.conf, 
-                        img_size
-                    );
+            if !annset.contains(line.img_id) {
+                let mut img_path = imgs_path.to_path_buf();
+                img_path.push(line.img_id);
+                let img_size = ImgSize::from_file(&img_path)?;
 
-                    ann.bboxes.push(bbox);
-                },
+                annset.insert(Ann::new(line.img_id, Some(img_size), vec![]));
+            }
 
-                Entry::Vacant(ve) => {
-                    let mut img_path = imgs_path.to_path_buf();
-                    img_path.push(&img_id);
-                    let img_size = ImgSize::from_file(&img_path)?;
+            let ann = annset.get_mut(line.img_id)
+                .expect("Image id must be present.");
 
-                    let ann = Ann::new(img_id, Some(img_size), vec![]);
+            let img_size = ann.img_size
+                .expect("Image size should have been populated during Ann init.");
+            
+            let mut bbox = BBox::create_rel(
+                line.label, 
+                coords,
+                BBoxFmt::LTRB, 
+                line.conf, 
+                img_size
+            );
 
-                    ve.insert(ann);
-                },
+            for (name, value) in line.flags() {
+                if let Some(value) = value {
+                    bbox.set_attribute(name, if value { "1" } else { "0" });
+                }
             }
+
+            ann.bboxes.push(bbox);
         }
 
         Ok(annset)
@@ -0,0 +1,197 @@
+use crate::{
+    imgsize::ImgSize,
+    bbox::BBox,
+    annotation::Ann,
+    annotationset::AnnSet,
+    parsers::ParseError,
+    serde_records::supervisely::*,
+};
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::Path,
+};
+
+use serde_json::from_str;
+
+impl TryFrom<SlyObj> for BBox {
+    type Error = ParseError;
+
+    fn try_from(obj: SlyObj) -> Result<Self, Self::Error> {
+        let (lt, rb) = match &obj.points.exterior[..] {
+            [lt, rb] => (lt, rb),
+            _ => return Err(ParseError {}),
+        };
+
+        let (x1, y1, x2, y2) = match (&lt[..], &rb[..]) {
+            ([x1, y1], [x2, y2]) => (*x1, *y1, *x2, *y2),
+            _ => return Err(ParseError {}),
+        };
+
+        // Confidence scores are stored as a `confidence` tag.
+        let conf = obj.tags.iter()
+            .find(|t| t["name"] == "confidence")
+            .and_then(|t| t["value"].as_f64())
+            .map(|c| c as f32);
+
+        if let Some(conf) = conf {
+            if !(0.0..=1.0).contains(&conf) {
+                return Err(ParseError {})
+            }
+        }
+
+        // Points may be in any order.
+        Ok(BBox::new(obj.class_title, x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2), conf))
+    }
+}
+
+impl Ann {
+    /// Parses a Supervisely image annotation file.
+    /// 
+    /// The image identifier is the file name without the `.json`
+    /// extension, e.g. `image.jpg` for `image.jpg.json`. Objects which
+    /// are not rectangles are ignored.
+    pub fn parse_supervisely<P: AsRef<Path>>(path: P) -> Result<Ann, ParseError> {
+        let img_id = path.as_ref()
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or(ParseError {})?;
+        let img_id = String::from(img_id);
+
+        let content = fs::read_to_string(path)
+            .map_err(|_| ParseError {})?;
+
+        let ann: SlyAnn = from_str(&content)
+            .map_err(|_| ParseError {})?;
+
+        let img_size = ImgSize::new(ann.size.width, ann.size.height);
+
+        let bboxes = ann.objects.into_iter()
+            .filter(|o| o.geometry_type == RECTANGLE)
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Ann::new(img_id, Some(img_size), bboxes))
+    }
+}
+
+impl AnnSet {
+    /// Parses a Supervisely project.
+    /// 
+    /// The project directory must contain the `meta.json` class definitions
+    /// and one directory per dataset with an `ann` sub-directory. Only
+    /// rectangle classes declared in `meta.json` are accepted.
+    pub fn parse_supervisely<P: AsRef<Path>>(path: P) -> Result<AnnSet, ParseError> {
+        let path = path.as_ref();
+
+        let content = fs::read_to_string(path.join("meta.json"))
+            .map_err(|_| ParseError {})?;
+
+        let meta: SlyMeta = from_str(&content)
+            .map_err(|_| ParseError {})?;
+
+        let classes = meta.classes.iter()
+            .filter(|c| c.shape == RECTANGLE)
+            .map(|c| c.title.as_str())
+            .collect::<HashSet<_>>();
+
+        let mut annset = AnnSet::new();
+
+        // Sorted for a deterministic annotation order.
+        let mut datasets = path.read_dir()
+            .map_err(|_| ParseError {})?
+            .map(|e| e.map(|e| e.path()).map_err(|_| ParseError {}))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        datasets.sort();
+
+        for dataset in datasets {
+            let ann_dir = dataset.join("ann");
+
+            if !ann_dir.is_dir() {
+                continue
+            }
+
+            for ann in AnnSet::parse_folder(ann_dir, "json", |p| Ann::parse_supervisely(p))? {
+                if ann.bboxes.iter().any(|b| !classes.contains(b.label.as_str())) {
+                    return Err(ParseError {})
+                }
+
+                annset.insert(ann);
+            }
+        }
+
+        Ok(annset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parsers::supervisely::*, path::test_dir};
+
+    #[test]
+    fn non_rectangle_objects() {
+        let dir = test_dir("supervisely-bitmap");
+        let ann_dir = dir.join("ds").join("ann");
+        fs::create_dir_all(&ann_dir).unwrap();
+
+        fs::write(dir.join("meta.json"), r##"{"classes": [
+            {"title": "car", "shape": "rectangle", "color": "#E6194B"},
+            {"title": "road", "shape": "bitmap", "color": "#3CB44B"},
+            {"title": "wheel", "shape": "point", "color": "#FFE119"}
+        ]}"##).unwrap();
+
+        fs::write(ann_dir.join("a.jpg.json"), r#"{
+            "size": {"width": 640, "height": 480},
+            "objects": [
+                {
+                    "classTitle": "road", "geometryType": "bitmap",
+                    "bitmap": {"data": "eJwBAQD+/wAAAQABAA==", "origin": [0, 240]}
+                },
+                {
+                    "classTitle": "car", "geometryType": "rectangle",
+                    "points": {"exterior": [[300, 200], [100, 50]], "interior": []}
+                },
+                {
+                    "classTitle": "wheel", "geometryType": "point",
+                    "points": {"exterior": [[120, 190]], "interior": []}
+                }
+            ]
+        }"#).unwrap();
+
+        let annset = AnnSet::parse_supervisely(&dir).unwrap();
+        let ann = annset.get("a.jpg").unwrap();
+
+        assert_eq!(ann.img_size, Some(ImgSize::new(640, 480)));
+        assert_eq!(ann.bboxes.len(), 1);
+        assert_eq!(ann.bboxes[0].label, "car");
+        assert_eq!(ann.bboxes[0].ltrb(), (100.0, 50.0, 300.0, 200.0));
+    }
+
+    #[test]
+    fn round_trip() {
+        let annset = AnnSet::from_iter([
+            Ann::new("a.jpg", Some(ImgSize::new(640, 480)), vec![
+                BBox::new("car", 100.0, 50.0, 300.0, 200.0, None),
+                BBox::new("person", 10.0, 20.0, 30.0, 90.0, Some(0.5)),
+            ]),
+        ]);
+
+        let dir = test_dir("supervisely-round-trip");
+        annset.save_supervisely(&dir, "ds").unwrap();
+
+        let parsed = AnnSet::parse_supervisely(&dir).unwrap();
+        let ann = parsed.get("a.jpg").unwrap();
+        assert_eq!(ann.img_size, Some(ImgSize::new(640, 480)));
+
+        let bboxes: Vec<_> = ann.bboxes.iter()
+            .map(|b| (b.label.as_str(), b.ltrb(), b.conf()))
+            .collect();
+
+        assert_eq!(bboxes, [
+            ("car", (100.0, 50.0, 300.0, 200.0), None),
+            ("person", (10.0, 20.0, 30.0, 90.0), Some(0.5)),
+        ]);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{
+    imgsize::ImgSize,
+    bbox::{BBox, BBoxFmt},
+    annotation::Ann,
+    annotationset::AnnSet,
+    parsers::ParseError,
+    serde_records::tfrecord::*,
+};
+
+use std::{
+    fs::File,
+    io::BufReader,
+    path::Path,
+};
+
+fn utf8(bytes: &[u8]) -> Result<String, ParseError> {
+    String::from_utf8(bytes.to_vec()).map_err(|_| ParseError {})
+}
+
+impl TryFrom<TFExample> for Ann {
+    type Error = ParseError;
+
+    fn try_from(example: TFExample) -> Result<Self, Self::Error> {
+        let img_id = example.bytes("image/filename")
+            .or_else(|| example.bytes("image/source_id"))
+            .and_then(|v| v.first())
+            .ok_or(ParseError {})?;
+        let img_id = utf8(img_id)?;
+
+        let width = example.ints("image/width").and_then(|v| v.first());
+        let height = example.ints("image/height").and_then(|v| v.first());
+
+        let img_size = match (width, height) {
+            (Some(&w), Some(&h)) => {
+                let w = u32::try_from(w).map_err(|_| ParseError {})?;
+                let h = u32::try_from(h).map_err(|_| ParseError {})?;
+                ImgSize::new(w, h)
+            },
+            _ => return Err(ParseError {}),
+        };
+
+        let coords = |key: &str| example.floats(key).unwrap_or_default();
+
+        let xmins = coords("image/object/bbox/xmin");
+        let ymins = coords("image/object/bbox/ymin");
+        let xmaxs = coords("image/object/bbox/xmax");
+        let ymaxs = coords("image/object/bbox/ymax");
+
+        let nb_boxes = xmins.len();
+
+        if [ymins.len(), xmaxs.len(), ymaxs.len()].iter().any(|&n| n != nb_boxes) {
+            return Err(ParseError {})
+        }
+
+        // Labels are the class names if present, else the class ids.
+        let labels = match example.bytes("image/object/class/text") {
+            Some(texts) => texts.iter()
+                .map(|t| utf8(t))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => example.ints("image/object/class/label")
+                .unwrap_or_default()
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+        };
+
+        let scores = example.floats("image/object/score");
+
+        if labels.len() != nb_boxes || scores.is_some_and(|s| s.len() != nb_boxes) {
+            return Err(ParseError {})
+        }
+
+        let mut bboxes = Vec::with_capacity(nb_boxes);
+
+        for (i, label) in labels.into_iter().enumerate() {
+            let conf = scores.map(|s| s[i]);
+
+            if let Some(conf) = conf {
+                if !(0.0..=1.0).contains(&conf) {
+                    return Err(ParseError {})
+                }
+            }
+
+            let coords = (xmins[i], ymins[i], xmaxs[i], ymaxs[i]);
+            let (xmin, ymin, xmax, ymax) = coords;
+
+            // Images of null size are saved with NaN coordinates.
+            let is_finite = [xmin, ymin, xmax, ymax].iter().all(|c| c.is_finite());
+
+            if !is_finite || xmin > xmax || ymin > ymax {
+                return Err(ParseError {})
+            }
+
+            bboxes.push(BBox::create_rel(label, coords, BBoxFmt::LTRB, conf, img_size));
+        }
+
+        Ok(Ann::new(img_id, Some(img_size), bboxes))
+    }
+}
+
+impl AnnSet {
+    /// Parses a TFRecord file of `tf.train.Example` using the
+    /// TensorFlow Object Detection API feature layout.
+    /// 
+    /// The image identifier is `image/filename`, or `image/source_id`
+    /// if the former is missing. Labels are read from
+    /// `image/object/class/text`, or from `image/object/class/label`
+    /// if the class names are missing. Confidence scores are read
+    /// from `image/object/score` if present.
+    pub fn parse_tfrecord<P: AsRef<Path>>(path: P) -> Result<AnnSet, ParseError> {
+        let file = File::open(path).map_err(|_| ParseError {})?;
+        let mut reader = BufReader::new(file);
+
+        let mut annset = AnnSet::new();
+
+        while let Some(record) = read_record(&mut reader).map_err(|_| ParseError {})? {
+            let example = TFExample::decode(&record).ok_or(ParseError {})?;
+            annset.insert(example.try_into()?);
+        }
+
+        Ok(annset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parsers::tfrecord::*, path::test_dir};
+
+    fn write_examples(path: &Path, examples: &[TFExample]) {
+        let mut file = File::create(path).unwrap();
+        examples.iter().for_each(|e| write_record(&mut file, &e.encode()).unwrap());
+    }
+
+    fn read_examples(path: &Path) -> Vec<TFExample> {
+        let mut reader = BufReader::new(File::open(path).unwrap());
+        std::iter::from_fn(|| read_record(&mut reader).unwrap())
+            .map(|record| TFExample::decode(&record).unwrap())
+            .collect()
+    }
+
+    /// An example with a 100x50 image and a single box.
+    fn example(key: &str, id: &str) -> TFExample {
+        let mut example = TFExample::default();
+        example.insert(key, TFFeature::Bytes(vec![id.as_bytes().to_vec()]));
+        example.insert("image/width", TFFeature::Int64(vec![100]));
+        example.insert("image/height", TFFeature::Int64(vec![50]));
+        example.insert("image/object/bbox/xmin", TFFeature::Float(vec![0.1]));
+        example.insert("image/object/bbox/ymin", TFFeature::Float(vec![0.2]));
+        example.insert("image/object/bbox/xmax", TFFeature::Float(vec![0.5]));
+        example.insert("image/object/bbox/ymax", TFFeature::Float(vec![1.0]));
+        example
+    }
+
+    #[test]
+    fn round_trip() {
+        let annset = AnnSet::from_iter([
+            Ann::new("a.jpg", Some(ImgSize::new(100, 50)), vec![
+                BBox::new("dog", 10.0, 10.0, 50.0, 50.0, Some(0.75)),
+                BBox::new("cat", 0.0, 0.0, 20.0, 25.0, Some(0.5)),
+            ]),
+            Ann::new("b.jpg", Some(ImgSize::new(10, 10)), vec![]),
+        ]);
+
+        let path = test_dir("tfrecord-round-trip").join("anns.tfrecord");
+        annset.save_tfrecord(&path, None::<&Path>).unwrap();
+
+        // Normalized coordinates and class ids in lexicographic order.
+        let examples = read_examples(&path);
+        assert_eq!(examples[0].floats("image/object/bbox/xmin"), Some(&[0.1, 0.0][..]));
+        assert_eq!(examples[0].floats("image/object/bbox/ymax"), Some(&[1.0, 0.5][..]));
+        assert_eq!(examples[0].ints("image/object/class/label"), Some(&[2, 1][..]));
+        assert_eq!(examples[0].floats("image/object/score"), Some(&[0.75, 0.5][..]));
+        assert_eq!(examples[1].floats("image/object/score"), None);
+
+        let parsed = AnnSet::parse_tfrecord(&path).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed.get("b.jpg").unwrap().img_size, Some(ImgSize::new(10, 10)));
+        assert!(parsed.get("b.jpg").unwrap().bboxes.is_empty());
+
+        let bboxes = &parsed.get("a.jpg").unwrap().bboxes;
+        assert_eq!(bboxes[0].label, "dog");
+        assert_eq!(bboxes[0].ltrb(), (10.0, 10.0, 50.0, 50.0));
+        assert_eq!(bboxes[0].conf(), Some(0.75));
+        assert_eq!(bboxes[1].label, "cat");
+        assert_eq!(bboxes[1].ltrb(), (0.0, 0.0, 20.0, 25.0));
+    }
+
+    #[test]
+    fn fallback_features() {
+        // Neither `image/filename` nor `image/object/class/text`.
+        let mut example = example("image/source_id", "a.jpg");
+        example.insert("image/object/class/label", TFFeature::Int64(vec![3]));
+
+        let path = test_dir("tfrecord-fallback").join("anns.tfrecord");
+        write_examples(&path, &[example]);
+
+        let parsed = AnnSet::parse_tfrecord(&path).unwrap();
+        let ann = parsed.get("a.jpg").unwrap();
+        assert_eq!(ann.bboxes[0].label, "3");
+        assert_eq!(ann.bboxes[0].ltrb(), (10.0, 10.0, 50.0, 50.0));
+        assert_eq!(ann.bboxes[0].conf(), None);
+    }
+
+    #[test]
+    fn invalid_examples() {
+        let dir = test_dir("tfrecord-invalid");
+        let path = dir.join("anns.tfrecord");
+
+        let labelled = |mut example: TFExample| {
+            example.insert("image/object/class/text", TFFeature::Bytes(vec![b"cat".to_vec()]));
+            example
+        };
+
+        // No image identifier.
+        write_examples(&path, &[labelled(example("image/format", "jpeg"))]);
+        assert!(AnnSet::parse_tfrecord(&path).is_err());
+
+        // As many scores as boxes are required.
+        let mut scores = labelled(example("image/filename", "a.jpg"));
+        scores.insert("image/object/score", TFFeature::Float(vec![0.5, 0.5]));
+        write_examples(&path, &[scores]);
+        assert!(AnnSet::parse_tfrecord(&path).is_err());
+
+        // Images of null width are saved with NaN coordinates.
+        let null_width = AnnSet::from_iter([
+            Ann::new("a.jpg", Some(ImgSize::new(0, 50)), vec![BBox::new("cat", 0.0, 0.0, 0.0, 10.0, None)]),
+        ]);
+
+        null_width.save_tfrecord(&path, None::<&Path>).unwrap();
+        assert!(AnnSet::parse_tfrecord(&path).is_err());
+
+        // Scored and unscored boxes cannot be mixed when saving.
+        let mixed = AnnSet::from_iter([
+            Ann::new("a.jpg", Some(ImgSize::new(100, 50)), vec![
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.5)),
+                BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None),
+            ]),
+        ]);
+
+        assert!(mixed.save_tfrecord(dir.join("mixed.tfrecord"), None::<&Path>).is_err());
+    }
+}
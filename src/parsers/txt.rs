@@ -111,7 +111,7 @@ impl AnnSet {
         img_ext: &str,
     ) -> Result<AnnSet, ParseError> { 
         let imgs_path = imgs_path
-            .and_then(|p| Some(p.as_ref().to_path_buf()));
+            .map(|p| p.as_ref().to_path_buf());
 
         AnnSet::parse_folder(path, "txt", |p| {
             let img_id = path_to_img_id(p, img_ext)?;
@@ -0,0 +1,141 @@
+use crate::{
+    imgsize::ImgSize,
+    bbox::{BBox, BBoxFmt},
+    annotation::Ann,
+    annotationset::AnnSet,
+    parsers::ParseError,
+    serde_records::via::*,
+};
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::Path,
+};
+
+use serde_json::{from_str, from_value, Value};
+
+impl VIARegion {
+    /// Returns `None` if the region is not a rectangle.
+    fn to_bbox(&self, label_attr: &str) -> Result<Option<BBox>, ParseError> {
+        if self.shape_attributes["name"] != RECT {
+            return Ok(None)
+        }
+
+        let rect: VIARect = from_value(self.shape_attributes.clone())
+            .map_err(|_| ParseError {})?;
+
+        if rect.width < 0.0 || rect.height < 0.0 {
+            return Err(ParseError {})
+        }
+
+        let label = self.region_attributes.get(label_attr)
+            .and_then(Value::as_str)
+            .ok_or(ParseError {})?;
+
+        let coords = (rect.x, rect.y, rect.width, rect.height);
+        let mut bbox = BBox::create(label, coords, BBoxFmt::LTWH, None);
+
+        // Other textual region attributes are preserved.
+        for (name, value) in &self.region_attributes {
+            if let (false, Some(value)) = (name == label_attr, value.as_str()) {
+                bbox.set_attribute(name.as_str(), value);
+            }
+        }
+
+        Ok(Some(bbox))
+    }
+}
+
+impl AnnSet {
+    /// Parses a VGG Image Annotator (VIA) project or annotation export.
+    /// 
+    /// Only rectangle regions are kept. The label is read from the
+    /// region attribute `label_attr` and other textual region attributes
+    /// are kept as bounding box attributes.
+    /// 
+    /// VIA does not store the image size, it is read from the image 
+    /// headers if `imgs_path` is provided.
+    pub fn parse_via<P1: AsRef<Path>, P2: AsRef<Path>>(
+        path: P1,
+        imgs_path: Option<P2>,
+        label_attr: &str,
+    ) -> Result<AnnSet, ParseError> {
+        let content = fs::read_to_string(path)
+            .map_err(|_| ParseError {})?;
+
+        let mut json = from_str::<Value>(&content)
+            .map_err(|_| ParseError {})?;
+
+        // Projects wrap the image metadata while exports do not.
+        let metadata = match json.get_mut("_via_img_metadata") {
+            Some(metadata) => metadata.take(),
+            None => json,
+        };
+
+        let imgs = from_value::<BTreeMap<String, VIAImg>>(metadata)
+            .map_err(|_| ParseError {})?;
+
+        let mut annset = AnnSet::with_capacity(imgs.len());
+
+        for img in imgs.into_values() {
+            let mut bboxes = Vec::with_capacity(img.regions.len());
+
+            for region in &img.regions {
+                if let Some(bbox) = region.to_bbox(label_attr)? {
+                    bboxes.push(bbox);
+                }
+            }
+
+            let img_size = match imgs_path.as_ref() {
+                Some(p) => Some(ImgSize::from_file(p.as_ref().join(&img.filename))?),
+                None => None,
+            };
+
+            annset.insert(Ann::new(img.filename, img_size, bboxes));
+        }
+
+        Ok(annset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parsers::via::*, path::test_dir};
+
+    const IMG: &str = r#"{
+        "filename": "a.jpg", "size": 1024,
+        "regions": [
+            {
+                "shape_attributes": {"name": "rect", "x": 10, "y": 20, "width": 30, "height": 40},
+                "region_attributes": {"label": "cat", "color": "black"}
+            },
+            {
+                "shape_attributes": {"name": "circle", "cx": 5, "cy": 5, "r": 2},
+                "region_attributes": {"label": "ball"}
+            }
+        ],
+        "file_attributes": {}
+    }"#;
+
+    #[test]
+    fn project_and_export() {
+        let dir = test_dir("via");
+
+        let export = dir.join("export.json");
+        fs::write(&export, format!(r#"{{"a.jpg1024": {IMG}}}"#)).unwrap();
+
+        let project = dir.join("project.json");
+        fs::write(&project, format!(r#"{{"_via_settings": {{}}, "_via_img_metadata": {{"a.jpg1024": {IMG}}}}}"#)).unwrap();
+
+        for path in [export, project] {
+            let annset = AnnSet::parse_via(&path, None::<&Path>, "label").unwrap();
+            let bboxes = &annset.get("a.jpg").unwrap().bboxes;
+
+            assert_eq!(bboxes.len(), 1);
+            assert_eq!(bboxes[0].label, "cat");
+            assert_eq!(bboxes[0].ltrb(), (10.0, 20.0, 40.0, 60.0));
+            assert_eq!(bboxes[0].attribute("color"), Some("black"));
+        }
+    }
+}
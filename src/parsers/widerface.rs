@@ -0,0 +1,172 @@
+use crate::{
+    imgsize::ImgSize,
+    bbox::{BBox, BBoxFmt},
+    annotation::Ann,
+    annotationset::AnnSet,
+    parsers::ParseError,
+    serde_records::widerface::*,
+};
+
+use std::{fs, path::Path};
+
+fn parse_widerface_line(line: &str) -> Result<Option<BBox>, ParseError> {
+    let fields = line.split_whitespace().collect::<Vec<_>>();
+
+    let conf = match fields.len() {
+        NB_FIELDS_GT => None,
+        NB_FIELDS_DET => {
+            let conf = fields[4].parse::<f32>().map_err(|_| ParseError {})?;
+            Some(conf)
+        },
+        _ => return Err(ParseError {}),
+    };
+
+    let coords = fields[..4].iter()
+        .map(|c| c.parse::<f32>().map_err(|_| ParseError {}))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Images without face have a single placeholder line of zeros.
+    if coords.iter().all(|&c| c == 0.0) {
+        return Ok(None)
+    }
+
+    if coords[2] < 0.0 || coords[3] < 0.0 || conf.is_some_and(|c| !(0.0..=1.0).contains(&c)) {
+        return Err(ParseError {})
+    }
+
+    let coords = (coords[0], coords[1], coords[2], coords[3]);
+    let mut bbox = BBox::create(FACE, coords, BBoxFmt::LTWH, conf);
+
+    if conf.is_none() {
+        for (name, value) in ATTRS.iter().zip(&fields[4..]) {
+            bbox.set_attribute(*name, *value);
+        }
+    }
+
+    Ok(Some(bbox))
+}
+
+impl AnnSet {
+    /// Parses a WIDER FACE annotation file.
+    /// 
+    /// Each image is described by its relative path, the number of faces and 
+    /// one line per face. Ground truth lines are `x1 y1 w h blur expression 
+    /// illumination invalid occlusion pose`, the flags being kept as 
+    /// attributes, and detection lines are `x1 y1 w h score`. The image 
+    /// identifier is the relative image path.
+    /// 
+    /// WIDER FACE does not store the image size, it is read from the image 
+    /// headers if `imgs_path` is provided.
+    pub fn parse_widerface<P1: AsRef<Path>, P2: AsRef<Path>>(
+        path: P1,
+        imgs_path: Option<P2>,
+    ) -> Result<AnnSet, ParseError> {
+        let content = fs::read_to_string(path)
+            .map_err(|_| ParseError {})?;
+
+        let mut lines = content.lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .peekable();
+
+        let mut annset = AnnSet::new();
+
+        while let Some(img_id) = lines.next() {
+            let count = lines.next()
+                .ok_or(ParseError {})?
+                .parse::<usize>()
+                .map_err(|_| ParseError {})?;
+
+            let mut bboxes = Vec::with_capacity(count);
+
+            // Ground truth files have a placeholder line when there are no 
+            // faces, prediction files do not.
+            let is_placeholder = |l: &&str| {
+                l.split_whitespace().all(|f| f.parse::<f32>().is_ok())
+            };
+
+            let nb_lines = match count {
+                0 if lines.peek().is_some_and(is_placeholder) => 1,
+                _ => count,
+            };
+
+            for _ in 0..nb_lines {
+                let line = lines.next().ok_or(ParseError {})?;
+
+                if let Some(bbox) = parse_widerface_line(line)? {
+                    bboxes.push(bbox);
+                }
+            }
+
+            let img_size = match imgs_path.as_ref() {
+                Some(p) => Some(ImgSize::from_file(p.as_ref().join(img_id))?),
+                None => None,
+            };
+
+            annset.insert(Ann::new(img_id, img_size, bboxes));
+        }
+
+        Ok(annset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parsers::widerface::*, path::test_dir};
+
+    fn parse(name: &str, content: &str) -> AnnSet {
+        let path = test_dir(name).join("anns.txt");
+        fs::write(&path, content).unwrap();
+
+        AnnSet::parse_widerface(&path, None::<&Path>).unwrap()
+    }
+
+    #[test]
+    fn no_faces() {
+        let gts = parse("widerface-no-faces-gts", "\
+0--Parade/a.jpg
+0
+0 0 0 0 0 0 0 0 0 0
+0--Parade/b.jpg
+1
+10 20 30 40 0 0 0 0 0 0
+");
+
+        assert!(gts.get("0--Parade/a.jpg").unwrap().bboxes.is_empty());
+        assert_eq!(gts.get("0--Parade/b.jpg").unwrap().bboxes.len(), 1);
+
+        // Prediction files have no placeholder line.
+        let dets = parse("widerface-no-faces-dets", "\
+0--Parade/a.jpg
+0
+0--Parade/b.jpg
+1
+10 20 30 40 0.9
+");
+
+        assert!(dets.get("0--Parade/a.jpg").unwrap().bboxes.is_empty());
+        assert_eq!(dets.get("0--Parade/b.jpg").unwrap().bboxes[0].conf(), Some(0.9));
+    }
+
+    #[test]
+    fn flags() {
+        let content = "\
+0--Parade/a.jpg
+2
+78 221 7 8 2 0 0 0 0 0
+449 330 122 149 0 0 0 0 1 0
+";
+
+        let annset = parse("widerface-flags", content);
+        let bboxes = &annset.get("0--Parade/a.jpg").unwrap().bboxes;
+
+        assert_eq!(bboxes[0].label, FACE);
+        assert_eq!(bboxes[0].ltrb(), (78.0, 221.0, 85.0, 229.0));
+        assert_eq!(bboxes[0].attribute("blur"), Some("2"));
+        assert_eq!(bboxes[1].attribute("occlusion"), Some("1"));
+
+        let path = test_dir("widerface-flags-saved").join("anns.txt");
+        annset.save_widerface(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+    }
+}
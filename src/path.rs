@@ -5,5 +5,17 @@ use shellexpand::tilde;
 pub(crate) fn expand_user<S: AsRef<str>>(path: S) -> PathBuf {
     let t = tilde(path.as_ref());
     Path::new(t.as_ref()).to_path_buf()
+}
 
-}
\ No newline at end of file
+/// Creates an empty directory in the system temporary directory for
+/// tests reading and writing files.
+#[cfg(test)]
+pub(crate) fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("globox-{}-{name}", std::process::id()));
+
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("failed to create the test directory");
+
+    dir
+}
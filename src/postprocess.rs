@@ -0,0 +1,372 @@
+use crate::{
+    annotation::Ann,
+    annotationset::AnnSet,
+    bbox::BBox,
+    evaluation::iou,
+};
+
+use std::collections::HashSet;
+
+/// The confidence decay of Soft-NMS.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SoftNmsDecay {
+    /// Confidences of boxes overlapping by more than the IoU threshold are
+    /// multiplied by `1 - IoU`.
+    Linear,
+
+    /// Confidences of all overlapping boxes are multiplied by 
+    /// `exp(-IoU² / sigma)`.
+    Gaussian { sigma: f32 },
+}
+
+/// Whether a detection may suppress or decay another one.
+fn interacts(lhs: &BBox, rhs: &BBox, agnostic: bool) -> bool {
+    agnostic || lhs.label == rhs.label
+}
+
+impl Ann {
+    /// The indices of the detections, sorted by decreasing confidence.
+    fn detections_by_conf(&self) -> Vec<usize> {
+        let mut idxs: Vec<usize> = self.bboxes.iter()
+            .enumerate()
+            .filter(|(_, b)| b.is_detection())
+            .map(|(i, _)| i)
+            .collect();
+
+        idxs.sort_by(|&a, &b| self.bboxes[b].conf().unwrap().total_cmp(&self.bboxes[a].conf().unwrap()));
+        idxs
+    }
+
+    /// Removes the detections overlapping a more confident detection with 
+    /// an IoU above the threshold. 
+    /// 
+    /// Only detections with the same label suppress each other, unless 
+    /// `agnostic` is true. Ground truths are left untouched and the order 
+    /// of the remaining bounding boxes is preserved.
+    pub fn nms(&mut self, iou_thresh: f32, agnostic: bool) {
+        let mut kept: Vec<usize> = vec![];
+        let mut suppressed = vec![false; self.bboxes.len()];
+
+        for i in self.detections_by_conf() {
+            let bbox = &self.bboxes[i];
+
+            let overlaps = kept.iter().any(|&k| {
+                let other = &self.bboxes[k];
+                interacts(bbox, other, agnostic) && iou(bbox, other) > iou_thresh
+            });
+
+            if overlaps {
+                suppressed[i] = true;
+            } else {
+                kept.push(i);
+            }
+        }
+
+        let mut suppressed = suppressed.into_iter();
+        self.bboxes.retain(|_| !suppressed.next().unwrap());
+    }
+
+    /// Decays the confidence of the detections overlapping a more confident
+    /// detection instead of removing them, then removes the detections 
+    /// whose confidence falls below `score_thresh`.
+    /// 
+    /// `iou_thresh` is only used by the linear decay. Labels, ground truths
+    /// and order are handled as in `Ann::nms`.
+    pub fn soft_nms(&mut self, decay: SoftNmsDecay, iou_thresh: f32, score_thresh: f32, agnostic: bool) {
+        let mut scores: Vec<f32> = self.bboxes.iter()
+            .map(|b| b.conf().unwrap_or(0.0))
+            .collect();
+        let mut remaining = self.detections_by_conf();
+        let mut suppressed = vec![false; self.bboxes.len()];
+
+        while !remaining.is_empty() {
+            // The first most confident detection, ties are broken by the 
+            // initial order.
+            let pos = (0..remaining.len())
+                .rev()
+                .max_by(|&a, &b| scores[remaining[a]].total_cmp(&scores[remaining[b]]))
+                .unwrap();
+            let best = remaining.remove(pos);
+
+            remaining.retain(|&i| {
+                let (bbox, other) = (&self.bboxes[i], &self.bboxes[best]);
+
+                if interacts(bbox, other, agnostic) {
+                    let iou = iou(bbox, other);
+
+                    scores[i] *= match decay {
+                        SoftNmsDecay::Linear if iou > iou_thresh => 1.0 - iou,
+                        SoftNmsDecay::Linear => 1.0,
+                        SoftNmsDecay::Gaussian { sigma } => (-iou * iou / sigma).exp(),
+                    };
+                }
+
+                suppressed[i] = scores[i] < score_thresh;
+                !suppressed[i]
+            });
+        }
+
+        for (bbox, &score) in self.bboxes.iter_mut().zip(&scores) {
+            if bbox.is_detection() {
+                bbox.set_conf(score);
+            }
+        }
+
+        let mut suppressed = suppressed.into_iter();
+        self.bboxes.retain(|_| !suppressed.next().unwrap());
+    }
+}
+
+impl AnnSet {
+    /// Applies `Ann::nms` to every annotation.
+    pub fn nms(&mut self, iou_thresh: f32, agnostic: bool) {
+        self.anns_mut().for_each(|ann| ann.nms(iou_thresh, agnostic));
+    }
+
+    /// Applies `Ann::soft_nms` to every annotation.
+    pub fn soft_nms(&mut self, decay: SoftNmsDecay, iou_thresh: f32, score_thresh: f32, agnostic: bool) {
+        self.anns_mut().for_each(|ann| ann.soft_nms(decay, iou_thresh, score_thresh, agnostic));
+    }
+}
+
+/// Fuses the detections of several models on the same image with Weighted 
+/// Boxes Fusion (WBF).
+/// 
+/// Detections with a confidence below `skip_thresh` are discarded and the 
+/// others are weighted by the weight of their model. Detections are then 
+/// visited by decreasing weighted confidence and added to the cluster whose 
+/// fused box overlaps them the most with an IoU above `iou_thresh`, if any. 
+/// Fused boxes are the confidence-weighted means of their cluster and their 
+/// confidence is the mean confidence of the cluster, scaled down when fewer 
+/// models than available contributed.
+/// 
+/// Ground truths are ignored. The image identifier is the one of the first
+/// annotation and the image size is the first known one.
+/// 
+/// # Panics
+/// Will panic if `anns` is empty, if there is not one weight per annotation
+/// or if a weight is negative.
+pub fn weighted_boxes_fusion(anns: &[&Ann], weights: &[f32], iou_thresh: f32, skip_thresh: f32) -> Ann {
+    assert!(!anns.is_empty(), "at least one annotation is required");
+    assert_eq!(anns.len(), weights.len(), "there should be one weight per annotation");
+    assert!(weights.iter().all(|&w| w >= 0.0), "weights should not be negative");
+
+    let mut by_label: Vec<(&str, Vec<(f32, &BBox)>)> = vec![];
+
+    for (ann, &weight) in anns.iter().zip(weights) {
+        for bbox in ann.bboxes.iter().filter(|b| b.is_detection()) {
+            let conf = bbox.conf().unwrap();
+
+            if conf < skip_thresh {
+                continue
+            }
+
+            match by_label.iter_mut().find(|(l, _)| *l == bbox.label) {
+                Some((_, dets)) => dets.push((conf * weight, bbox)),
+                None => by_label.push((&bbox.label, vec![(conf * weight, bbox)])),
+            }
+        }
+    }
+
+    let total_weight: f32 = weights.iter().sum();
+    let mut fused = vec![];
+
+    for (label, mut dets) in by_label {
+        dets.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut clusters: Vec<Vec<(f32, &BBox)>> = vec![];
+        let mut boxes: Vec<BBox> = vec![];
+
+        for (score, bbox) in dets {
+            let best = boxes.iter()
+                .map(|f| iou(f, bbox))
+                .enumerate()
+                .filter(|&(_, iou)| iou > iou_thresh)
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| i);
+
+            match best {
+                Some(i) => {
+                    clusters[i].push((score, bbox));
+                    boxes[i] = fuse_cluster(label, &clusters[i]);
+                },
+                None => {
+                    clusters.push(vec![(score, bbox)]);
+                    boxes.push(fuse_cluster(label, &clusters[clusters.len() - 1]));
+                },
+            }
+        }
+
+        for (mut bbox, cluster) in boxes.into_iter().zip(clusters) {
+            let nb_models = cluster.len().min(weights.len()) as f32;
+            let mean = cluster.iter().map(|(s, _)| s).sum::<f32>() / cluster.len() as f32;
+
+            // Only the final confidence is clamped, weights above 1 can give
+            // weighted means above 1.
+            bbox.set_conf((mean * nb_models / total_weight).clamp(0.0, 1.0));
+            fused.push(bbox);
+        }
+    }
+
+    fused.sort_by(|a, b| b.conf().unwrap().total_cmp(&a.conf().unwrap()));
+
+    Ann::new(
+        anns[0].img_id.as_str(), 
+        anns.iter().find_map(|a| a.img_size), 
+        fused,
+    )
+}
+
+/// The confidence-weighted mean box of a cluster, without confidence.
+fn fuse_cluster(label: &str, cluster: &[(f32, &BBox)]) -> BBox {
+    let total: f32 = cluster.iter().map(|(s, _)| s).sum();
+
+    // Fallback to uniform weights for clusters of null confidences.
+    let weight = |s: f32| if total > 0.0 { s / total } else { 1.0 / cluster.len() as f32 };
+
+    let (mut l, mut t, mut r, mut b) = (0.0, 0.0, 0.0, 0.0);
+    for &(score, bbox) in cluster {
+        let w = weight(score);
+        l += w * bbox.xmin();
+        t += w * bbox.ymin();
+        r += w * bbox.xmax();
+        b += w * bbox.ymax();
+    }
+
+    BBox::new(label, l, t, r.max(l), b.max(t), None)
+}
+
+impl AnnSet {
+    /// Fuses the detections of several models with `weighted_boxes_fusion`, 
+    /// image by image. 
+    /// 
+    /// Images are ordered by first appearance. Images missing from a set
+    /// are considered to have no detections from that model.
+    /// 
+    /// # Panics
+    /// Will panic if there is not one weight per set or if a weight is negative.
+    pub fn weighted_boxes_fusion(sets: &[&AnnSet], weights: &[f32], iou_thresh: f32, skip_thresh: f32) -> AnnSet {
+        assert_eq!(sets.len(), weights.len(), "there should be one weight per annotation set");
+        assert!(weights.iter().all(|&w| w >= 0.0), "weights should not be negative");
+
+        let mut img_ids: Vec<&str> = vec![];
+        let mut seen = HashSet::new();
+
+        for ann in sets.iter().flat_map(|s| s.iter()) {
+            if seen.insert(ann.img_id.as_str()) {
+                img_ids.push(ann.img_id.as_str());
+            }
+        }
+
+        img_ids.into_iter()
+            .map(|img_id| {
+                // Missing models still count in the confidence normalisation.
+                let empty = Ann::empty(img_id);
+                let anns: Vec<&Ann> = sets.iter()
+                    .map(|s| s.get(img_id).unwrap_or(&empty))
+                    .collect();
+
+                let mut fused = weighted_boxes_fusion(&anns, weights, iou_thresh, skip_thresh);
+                fused.img_id = img_id.to_string();
+                fused
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::postprocess::*;
+
+    fn ann() -> Ann {
+        Ann::new("a.jpg", None, vec![
+            BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.6)),
+            BBox::new("cat", 1.0, 0.0, 11.0, 10.0, Some(0.9)),
+            BBox::new("dog", 0.0, 0.0, 10.0, 10.0, Some(0.8)),
+            BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None),
+            BBox::new("cat", 50.0, 50.0, 60.0, 60.0, Some(0.1)),
+        ])
+    }
+
+    fn confs(ann: &Ann) -> Vec<Option<f32>> {
+        ann.bboxes.iter().map(|b| b.conf()).collect()
+    }
+
+    #[test]
+    fn nms() {
+        let mut aware = ann();
+        aware.nms(0.5, false);
+        assert_eq!(confs(&aware), [Some(0.9), Some(0.8), None, Some(0.1)]);
+
+        let mut agnostic = ann();
+        agnostic.nms(0.5, true);
+        assert_eq!(confs(&agnostic), [Some(0.9), None, Some(0.1)]);
+    }
+
+    #[test]
+    fn soft_nms() {
+        let mut linear = ann();
+        linear.soft_nms(SoftNmsDecay::Linear, 0.5, 0.05, false);
+        let iou = 90.0 / 110.0;
+        assert_eq!(confs(&linear), [Some(0.6 * (1.0 - iou)), Some(0.9), Some(0.8), None, Some(0.1)]);
+
+        let mut gaussian = ann();
+        gaussian.soft_nms(SoftNmsDecay::Gaussian { sigma: 0.5 }, 0.5, 0.05, true);
+        assert_eq!(gaussian.bboxes.len(), 4);
+        assert!(gaussian.bboxes[1].conf().unwrap() < 0.8);
+    }
+
+    #[test]
+    fn wbf() {
+        let a = Ann::new("a.jpg", None, vec![
+            BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.8)),
+            BBox::new("dog", 0.0, 0.0, 10.0, 10.0, Some(0.4)),
+        ]);
+        let b = Ann::new("a.jpg", None, vec![
+            BBox::new("cat", 2.0, 0.0, 12.0, 10.0, Some(0.4)),
+        ]);
+
+        let fused = weighted_boxes_fusion(&[&a, &b], &[1.0, 1.0], 0.55, 0.0);
+        assert_eq!(fused.bboxes.len(), 2);
+
+        let cat = &fused.bboxes[0];
+        assert!((cat.xmin() - 2.0 / 3.0).abs() < 1e-5);
+        assert!((cat.conf().unwrap() - 0.6).abs() < 1e-5);
+
+        // Only one of the two models predicted a dog.
+        assert!((fused.bboxes[1].conf().unwrap() - 0.2).abs() < 1e-5);
+
+        let sets = [AnnSet::from_iter([a]), AnnSet::from_iter([b, Ann::empty("b.jpg")])];
+        let fused = AnnSet::weighted_boxes_fusion(&[&sets[0], &sets[1]], &[1.0, 1.0], 0.55, 0.0);
+        assert_eq!(fused.len(), 2);
+        assert_eq!(fused.get("a.jpg").unwrap().bboxes.len(), 2);
+    }
+
+    #[test]
+    fn wbf_weights() {
+        let a = Ann::new("a.jpg", None, vec![
+            BBox::new("cat", 0.0, 0.0, 10.0, 10.0, Some(0.9)),
+            BBox::new("dog", 0.0, 0.0, 10.0, 10.0, Some(0.6)),
+        ]);
+        let b = Ann::new("a.jpg", None, vec![
+            BBox::new("dog", 0.0, 0.0, 10.0, 10.0, Some(0.3)),
+            BBox::new("cow", 0.0, 0.0, 10.0, 10.0, Some(0.8)),
+        ]);
+
+        let fused = weighted_boxes_fusion(&[&a, &b], &[2.0, 1.0], 0.55, 0.0);
+        let conf = |label: &str| fused.bboxes.iter().find(|b| b.label == label).unwrap().conf().unwrap();
+
+        // Weighted means above 1 are not clamped before the normalisation.
+        assert!((conf("cat") - 1.8 / 3.0).abs() < 1e-5);
+        assert!((conf("dog") - (1.2 + 0.3) / 3.0).abs() < 1e-5);
+        assert!((conf("cow") - 0.8 / 3.0).abs() < 1e-5);
+        assert_eq!(fused.bboxes[0].label, "cat");
+    }
+
+    #[test]
+    #[should_panic(expected = "weights should not be negative")]
+    fn wbf_negative_weight() {
+        let ann = ann();
+        weighted_boxes_fusion(&[&ann, &ann], &[1.0, -1.0], 0.55, 0.0);
+    }
+}
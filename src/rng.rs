@@ -0,0 +1,68 @@
+/// A small seedable pseudo-random number generator (SplitMix64).
+/// 
+/// Used where results must be reproducible across runs and platforms,
+/// it is not suitable for cryptographic use.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..n`.
+    /// 
+    /// # Panics
+    /// Will panic if `n` is zero.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "`n` should be greater than zero");
+
+        // Lemire's multiply-shift, the bias is negligible for our use.
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    /// Shuffles the slice in-place (Fisher-Yates).
+    pub(crate) fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rng::*;
+
+    #[test]
+    fn deterministic() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+
+        let xs = (0..8).map(|_| a.next_u64()).collect::<Vec<_>>();
+        let ys = (0..8).map(|_| b.next_u64()).collect::<Vec<_>>();
+        assert_eq!(xs, ys);
+
+        let mut items = (0..10).collect::<Vec<_>>();
+        Rng::new(0).shuffle(&mut items);
+        let mut sorted = items.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn below() {
+        let mut rng = Rng::new(7);
+        assert!((0..1000).all(|_| rng.below(3) < 3));
+    }
+}
@@ -2,4 +2,12 @@ pub(crate) mod labelme;
 pub(crate) mod imagenet;
 pub(crate) mod txt;
 pub(crate) mod openimage;
-pub(crate) mod coco;
\ No newline at end of file
+pub(crate) mod coco;
+pub(crate) mod kitti;
+pub(crate) mod labelstudio;
+pub(crate) mod tfrecord;
+pub(crate) mod createml;
+pub(crate) mod supervisely;
+pub(crate) mod via;
+pub(crate) mod cityscapes;
+pub(crate) mod widerface;
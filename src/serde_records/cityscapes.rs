@@ -0,0 +1,34 @@
+use serde::{Serialize, Deserialize};
+
+/// The suffix of Cityscapes images, e.g. `aachen_000000_000019_leftImg8bit.png`.
+pub(crate) const IMG_SUFFIX: &str = "_leftImg8bit.png";
+
+/// The infix of Cityscapes bounding box annotation files, 
+/// e.g. `aachen_000000_000019_gtBboxCityPersons.json`.
+pub(crate) const ANN_INFIX: &str = "_gtBbox";
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CSObj {
+    pub(crate) label: String,
+
+    #[serde(rename = "instanceId", skip_serializing_if = "Option::is_none")]
+    pub(crate) instance_id: Option<i64>,
+
+    /// `[x, y, width, height]` of the full object extent.
+    pub(crate) bbox: Vec<f32>,
+
+    /// `[x, y, width, height]` of the visible part of the object.
+    #[serde(rename = "bboxVis", skip_serializing_if = "Option::is_none")]
+    pub(crate) bbox_vis: Option<Vec<f32>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CSAnn {
+    #[serde(rename = "imgWidth")]
+    pub(crate) img_width: u32,
+
+    #[serde(rename = "imgHeight")]
+    pub(crate) img_height: u32,
+
+    pub(crate) objects: Vec<CSObj>,
+}
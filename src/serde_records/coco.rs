@@ -31,6 +31,9 @@ pub(crate) struct COCOAnn {
 
     #[serde(rename = "score")]
     pub(crate) conf: Option<f32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) iscrowd: Option<u8>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -0,0 +1,25 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CMLCoords {
+    /// Center coordinates.
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) width: f32,
+    pub(crate) height: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CMLObj {
+    pub(crate) label: String,
+    pub(crate) coordinates: CMLCoords,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) confidence: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CMLAnn {
+    pub(crate) image: String,
+    pub(crate) annotations: Vec<CMLObj>,
+}
@@ -31,6 +31,10 @@ impl From<InetSize> for ImgSize {
 #[derive(Serialize, Deserialize)]
 pub(crate) struct InetObj {
     pub(crate) name: String, 
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) difficult: Option<u8>,
+
     pub(crate) bndbox: INetBndBox,
 }
 
@@ -0,0 +1,40 @@
+/// The label KITTI uses for regions containing objects that were
+/// not annotated (too far away, too small, etc.).
+pub(crate) const DONT_CARE: &str = "DontCare";
+
+/// The names of the KITTI fields preserved as bounding box attributes,
+/// in file order. `left top right bottom` are stored as the box 
+/// coordinates and sit between `alpha` and `height_3d`.
+pub(crate) const PRE_BOX_ATTRS: [&str; 3] = ["truncated", "occluded", "alpha"];
+
+pub(crate) const POST_BOX_ATTRS: [&str; 7] = [
+    "height_3d", "width_3d", "length_3d",
+    "x_3d", "y_3d", "z_3d",
+    "rotation_y",
+];
+
+/// Values written when an attribute is missing, which are the ones used
+/// by KITTI for `DontCare` regions.
+pub(crate) const PRE_BOX_DEFAULTS: [&str; 3] = ["-1", "-1", "-10"];
+
+pub(crate) const POST_BOX_DEFAULTS: [&str; 7] = [
+    "-1", "-1", "-1",
+    "-1000", "-1000", "-1000",
+    "-10",
+];
+
+/// The number of fields of a ground truth line.
+pub(crate) const NB_FIELDS_GT: usize = 15;
+
+/// The number of fields of a detection line (trailing score).
+pub(crate) const NB_FIELDS_DET: usize = 16;
+
+/// The attribute storing the raw score of a detection, KITTI scores
+/// are not bounded to `0..=1`.
+pub(crate) const SCORE_ATTR: &str = "score";
+
+/// Maps a raw KITTI score to a confidence in `0..=1` with the logistic
+/// function, which preserves the ranking of detections.
+pub(crate) fn score_to_conf(score: f32) -> f32 {
+    1.0 / (1.0 + (-score).exp())
+}
@@ -0,0 +1,86 @@
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
+
+/// The result type of rectangle annotations.
+pub(crate) const RECT_LABELS: &str = "rectanglelabels";
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct LSValue {
+    /// Coordinates are percentages of the image size.
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) width: f32,
+    pub(crate) height: f32,
+
+    #[serde(default)]
+    pub(crate) rotation: f32,
+
+    #[serde(rename = "rectanglelabels", default)]
+    pub(crate) labels: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct LSResult {
+    #[serde(rename = "type")]
+    pub(crate) kind: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) original_width: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) original_height: Option<u32>,
+
+    #[serde(default = "default_from_name")]
+    pub(crate) from_name: String,
+
+    #[serde(default = "default_to_name")]
+    pub(crate) to_name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) score: Option<f32>,
+
+    /// Only deserialized for rectangles, other result types
+    /// have a different layout and relations have no value.
+    #[serde(default)]
+    pub(crate) value: Value,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct LSAnnotation {
+    #[serde(default)]
+    pub(crate) was_cancelled: bool,
+
+    #[serde(default)]
+    pub(crate) result: Vec<LSResult>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct LSPrediction {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) model_version: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) score: Option<f32>,
+
+    #[serde(default)]
+    pub(crate) result: Vec<LSResult>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct LSTask {
+    pub(crate) data: Map<String, Value>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) annotations: Vec<LSAnnotation>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) predictions: Vec<LSPrediction>,
+}
+
+fn default_from_name() -> String {
+    String::from("label")
+}
+
+fn default_to_name() -> String {
+    String::from("image")
+}
@@ -1,5 +1,28 @@
+use crate::bbox::GROUP_OF_ATTR;
+
 use serde::{Serialize, Deserialize};
 
+/// The `0`/`1` boolean columns of OpenImages, empty when unknown.
+mod flag {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub(crate) fn serialize<S: Serializer>(value: &Option<bool>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.serialize_u8(*value as u8),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<bool>, D::Error> {
+        match Option::<String>::deserialize(deserializer)?.as_deref() {
+            None | Some("") => Ok(None),
+            Some("1" | "true") => Ok(Some(true)),
+            Some("0" | "false") => Ok(Some(false)),
+            Some(value) => Err(D::Error::custom(format!("invalid flag `{value}`"))),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub(crate) struct OALine<'l> {
     #[serde(rename = "ImageID")]
@@ -27,18 +50,23 @@ pub(crate) struct OALine<'l> {
     pub(crate) ymax: f32,
 
     #[serde(rename = "IsOccluded")]
+    #[serde(default, with = "flag")]
     pub(crate) is_occulded: Option<bool>,
 
     #[serde(rename = "IsTruncated")]
+    #[serde(default, with = "flag")]
     pub(crate) is_trucated: Option<bool>,
 
     #[serde(rename = "IsGroupOf")]
+    #[serde(default, with = "flag")]
     pub(crate) is_group_of: Option<bool>,
 
     #[serde(rename = "IsDepiction")]
+    #[serde(default, with = "flag")]
     pub(crate) is_depiction: Option<bool>,
 
     #[serde(rename = "IsInside")]
+    #[serde(default, with = "flag")]
     pub(crate) is_inside: Option<bool>,
 }
 
@@ -65,4 +93,53 @@ impl<'l> OALine<'l> {
             is_inside: None
         }
     }
-}
\ No newline at end of file
+
+    /// The boolean columns with their name, kept as bounding box attributes.
+    pub(crate) fn flags(&self) -> [(&'static str, Option<bool>); 5] {
+        [
+            ("IsOccluded", self.is_occulded),
+            ("IsTruncated", self.is_trucated),
+            (GROUP_OF_ATTR, self.is_group_of),
+            ("IsDepiction", self.is_depiction),
+            ("IsInside", self.is_inside),
+        ]
+    }
+
+    /// Sets the boolean columns from their name.
+    pub(crate) fn set_flag(&mut self, name: &str, value: bool) {
+        let flag = match name {
+            "IsOccluded" => &mut self.is_occulded,
+            "IsTruncated" => &mut self.is_trucated,
+            GROUP_OF_ATTR => &mut self.is_group_of,
+            "IsDepiction" => &mut self.is_depiction,
+            "IsInside" => &mut self.is_inside,
+            _ => return,
+        };
+
+        *flag = Some(value);
+    }
+}
+
+/// A node of the OpenImages class hierarchy JSON.
+#[derive(Deserialize)]
+pub(crate) struct OAHierarchy {
+    #[serde(rename = "LabelName")]
+    pub(crate) label: String,
+
+    #[serde(rename = "Subcategory", default)]
+    pub(crate) children: Vec<OAHierarchy>,
+}
+
+/// A line of OpenImages image-level labels, a confidence of 1 meaning
+/// verified present and 0 verified absent.
+#[derive(Deserialize)]
+pub(crate) struct OAImageLabel {
+    #[serde(rename = "ImageID")]
+    pub(crate) img_id: String,
+
+    #[serde(rename = "LabelName")]
+    pub(crate) label: String,
+
+    #[serde(rename = "Confidence")]
+    pub(crate) conf: f32,
+}
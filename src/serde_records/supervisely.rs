@@ -0,0 +1,57 @@
+use serde::{Serialize, Deserialize};
+
+pub(crate) const RECTANGLE: &str = "rectangle";
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SlyClass {
+    pub(crate) title: String,
+    pub(crate) shape: String,
+    pub(crate) color: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SlyMeta {
+    pub(crate) classes: Vec<SlyClass>,
+
+    #[serde(default)]
+    pub(crate) tags: Vec<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SlySize {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct SlyPoints {
+    pub(crate) exterior: Vec<Vec<f32>>,
+
+    #[serde(default)]
+    pub(crate) interior: Vec<Vec<Vec<f32>>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SlyObj {
+    #[serde(rename = "classTitle")]
+    pub(crate) class_title: String,
+
+    #[serde(rename = "geometryType")]
+    pub(crate) geometry_type: String,
+
+    /// Missing for geometries such as bitmaps.
+    #[serde(default)]
+    pub(crate) points: SlyPoints,
+
+    #[serde(default)]
+    pub(crate) tags: Vec<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SlyAnn {
+    pub(crate) size: SlySize,
+    pub(crate) objects: Vec<SlyObj>,
+
+    #[serde(default)]
+    pub(crate) tags: Vec<serde_json::Value>,
+}
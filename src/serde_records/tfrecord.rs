@@ -0,0 +1,407 @@
+//! Minimal reader and writer of TFRecord files containing
+//! `tf.train.Example` protocol buffers.
+//! 
+//! Only the subset of the protocol buffer wire format needed by
+//! `tf.train.Example` is implemented, which avoids depending on
+//! TensorFlow or a protobuf compiler.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Read, Write},
+};
+
+/// A `tf.train.Feature`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TFFeature {
+    Bytes(Vec<Vec<u8>>),
+    Float(Vec<f32>),
+    Int64(Vec<i64>),
+}
+
+/// A `tf.train.Example`, i.e. a map of named features.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct TFExample {
+    pub(crate) features: BTreeMap<String, TFFeature>,
+}
+
+impl TFExample {
+    pub(crate) fn bytes(&self, key: &str) -> Option<&[Vec<u8>]> {
+        match self.features.get(key)? {
+            TFFeature::Bytes(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn floats(&self, key: &str) -> Option<&[f32]> {
+        match self.features.get(key)? {
+            TFFeature::Float(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn ints(&self, key: &str) -> Option<&[i64]> {
+        match self.features.get(key)? {
+            TFFeature::Int64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn insert(&mut self, key: &str, feature: TFFeature) {
+        self.features.insert(String::from(key), feature);
+    }
+}
+
+// Wire format
+
+const VARINT: u64 = 0;
+const FIXED64: u64 = 1;
+const LEN: u64 = 2;
+const FIXED32: u64 = 5;
+
+fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn put_len_field(buf: &mut Vec<u8>, field: u64, data: &[u8]) {
+    put_varint(buf, (field << 3) | LEN);
+    put_varint(buf, data.len() as u64);
+    buf.extend_from_slice(data);
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn varint(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = self.data.split_first()?;
+            self.data = rest;
+            value |= ((byte & 0x7f) as u64) << shift;
+
+            if byte & 0x80 == 0 {
+                return Some(value)
+            }
+        }
+
+        None
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.data.len() {
+            return None
+        }
+
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(head)
+    }
+
+    fn len_delimited(&mut self) -> Option<&'a [u8]> {
+        let len = self.varint()? as usize;
+        self.take(len)
+    }
+
+    /// Returns the field number and wire type of the next field.
+    fn key(&mut self) -> Option<(u64, u64)> {
+        let key = self.varint()?;
+        Some((key >> 3, key & 0x7))
+    }
+
+    fn skip(&mut self, wire_type: u64) -> Option<()> {
+        match wire_type {
+            VARINT => { self.varint()?; },
+            FIXED64 => { self.take(8)?; },
+            LEN => { self.len_delimited()?; },
+            FIXED32 => { self.take(4)?; },
+            _ => return None,
+        }
+
+        Some(())
+    }
+}
+
+impl TFFeature {
+    fn encode(&self) -> Vec<u8> {
+        let mut list = vec![];
+
+        let field = match self {
+            TFFeature::Bytes(values) => {
+                for v in values {
+                    put_len_field(&mut list, 1, v);
+                }
+                1
+            },
+            TFFeature::Float(values) => {
+                let packed = values.iter()
+                    .flat_map(|v| v.to_le_bytes())
+                    .collect::<Vec<_>>();
+                put_len_field(&mut list, 1, &packed);
+                2
+            },
+            TFFeature::Int64(values) => {
+                let mut packed = vec![];
+                for &v in values {
+                    put_varint(&mut packed, v as u64);
+                }
+                put_len_field(&mut list, 1, &packed);
+                3
+            },
+        };
+
+        let mut buf = vec![];
+        put_len_field(&mut buf, field, &list);
+        buf
+    }
+
+    fn decode(data: &[u8]) -> Option<TFFeature> {
+        let mut feature = None;
+        let mut decoder = Decoder { data };
+
+        while !decoder.is_empty() {
+            let (field, wire_type) = decoder.key()?;
+
+            if wire_type != LEN || !(1..=3).contains(&field) {
+                decoder.skip(wire_type)?;
+                continue
+            }
+
+            let mut list = Decoder { data: decoder.len_delimited()? };
+            
+            let mut bytes = vec![];
+            let mut floats = vec![];
+            let mut ints = vec![];
+
+            while !list.is_empty() {
+                let (value_field, value_type) = list.key()?;
+
+                if value_field != 1 {
+                    list.skip(value_type)?;
+                    continue
+                }
+
+                // Repeated scalars may be either packed or not.
+                match (field, value_type) {
+                    (1, LEN) => bytes.push(list.len_delimited()?.to_vec()),
+                    (2, FIXED32) => floats.push(f32::from_le_bytes(list.take(4)?.try_into().ok()?)),
+                    (2, LEN) => {
+                        let packed = list.len_delimited()?;
+                        for chunk in packed.chunks(4) {
+                            floats.push(f32::from_le_bytes(chunk.try_into().ok()?));
+                        }
+                    },
+                    (3, VARINT) => ints.push(list.varint()? as i64),
+                    (3, LEN) => {
+                        let mut packed = Decoder { data: list.len_delimited()? };
+                        while !packed.is_empty() {
+                            ints.push(packed.varint()? as i64);
+                        }
+                    },
+                    _ => return None,
+                }
+            }
+
+            feature = Some(match field {
+                1 => TFFeature::Bytes(bytes),
+                2 => TFFeature::Float(floats),
+                _ => TFFeature::Int64(ints),
+            });
+        }
+
+        feature
+    }
+}
+
+impl TFExample {
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut features = vec![];
+
+        for (key, feature) in &self.features {
+            let mut entry = vec![];
+            put_len_field(&mut entry, 1, key.as_bytes());
+            put_len_field(&mut entry, 2, &feature.encode());
+
+            put_len_field(&mut features, 1, &entry);
+        }
+
+        let mut buf = vec![];
+        put_len_field(&mut buf, 1, &features);
+        buf
+    }
+
+    pub(crate) fn decode(data: &[u8]) -> Option<TFExample> {
+        let mut example = TFExample::default();
+        let mut decoder = Decoder { data };
+
+        while !decoder.is_empty() {
+            let (field, wire_type) = decoder.key()?;
+
+            if (field, wire_type) != (1, LEN) {
+                decoder.skip(wire_type)?;
+                continue
+            }
+
+            let mut features = Decoder { data: decoder.len_delimited()? };
+
+            while !features.is_empty() {
+                let (field, wire_type) = features.key()?;
+
+                if (field, wire_type) != (1, LEN) {
+                    features.skip(wire_type)?;
+                    continue
+                }
+
+                let mut entry = Decoder { data: features.len_delimited()? };
+                let mut key = None;
+                let mut value = None;
+
+                while !entry.is_empty() {
+                    match entry.key()? {
+                        (1, LEN) => {
+                            let k = entry.len_delimited()?;
+                            key = Some(String::from_utf8(k.to_vec()).ok()?);
+                        },
+                        (2, LEN) => value = TFFeature::decode(entry.len_delimited()?),
+                        (_, wire_type) => entry.skip(wire_type)?,
+                    }
+                }
+
+                if let (Some(key), Some(value)) = (key, value) {
+                    example.features.insert(key, value);
+                }
+            }
+        }
+
+        Some(example)
+    }
+}
+
+// Record framing
+
+/// CRC-32C (Castagnoli) lookup table.
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+
+        while j < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0x82f63b78 } else { crc >> 1 };
+            j += 1;
+        }
+
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+};
+
+pub(crate) fn crc32c(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &b| {
+        CRC_TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn masked_crc(data: &[u8]) -> u32 {
+    let crc = crc32c(data);
+    crc.rotate_right(15).wrapping_add(0xa282ead8)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn corrupted() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "corrupted TFRecord")
+}
+
+/// Reads the next record, returns `None` at the end of the stream.
+/// 
+/// Fails if the stream is truncated or if a checksum does not match.
+pub(crate) fn read_record<R: Read>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut len_buf = [0u8; 8];
+
+    // Distinguish a clean end of stream from a truncated header.
+    let read = reader.read(&mut len_buf)?;
+    if read == 0 {
+        return Ok(None)
+    }
+    reader.read_exact(&mut len_buf[read..])?;
+
+    if read_u32(reader)? != masked_crc(&len_buf) {
+        return Err(corrupted())
+    }
+
+    let len = u64::from_le_bytes(len_buf) as usize;
+    let mut data = vec![0u8; len];
+    reader.read_exact(&mut data)?;
+
+    if read_u32(reader)? != masked_crc(&data) {
+        return Err(corrupted())
+    }
+
+    Ok(Some(data))
+}
+
+pub(crate) fn write_record<W: Write>(writer: &mut W, data: &[u8]) -> io::Result<()> {
+    let len = (data.len() as u64).to_le_bytes();
+
+    writer.write_all(&len)?;
+    writer.write_all(&masked_crc(&len).to_le_bytes())?;
+    writer.write_all(data)?;
+    writer.write_all(&masked_crc(data).to_le_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::serde_records::tfrecord::*;
+
+    #[test]
+    fn crc32c_check_value() {
+        assert_eq!(crc32c(b"123456789"), 0xe3069283);
+    }
+
+    #[test]
+    fn example_round_trip() {
+        let mut example = TFExample::default();
+        example.insert("image/filename", TFFeature::Bytes(vec![b"img.jpg".to_vec()]));
+        example.insert("image/width", TFFeature::Int64(vec![640, -1]));
+        example.insert("image/object/bbox/xmin", TFFeature::Float(vec![0.25, 0.5]));
+
+        let mut buf = vec![];
+        write_record(&mut buf, &example.encode()).unwrap();
+        write_record(&mut buf, &TFExample::default().encode()).unwrap();
+
+        let mut reader = buf.as_slice();
+        let first = read_record(&mut reader).unwrap().unwrap();
+        let second = read_record(&mut reader).unwrap().unwrap();
+
+        assert_eq!(TFExample::decode(&first), Some(example));
+        assert_eq!(TFExample::decode(&second), Some(TFExample::default()));
+        assert!(read_record(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn corrupted_record() {
+        let mut buf = vec![];
+        write_record(&mut buf, b"data").unwrap();
+        buf[13] ^= 0xff;
+
+        assert!(read_record(&mut buf.as_slice()).is_err());
+    }
+}
@@ -0,0 +1,44 @@
+use std::collections::BTreeMap;
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+pub(crate) const RECT: &str = "rect";
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct VIARegion {
+    /// The region geometry, only rectangles are deserialized
+    /// to `VIARect`.
+    pub(crate) shape_attributes: Value,
+
+    #[serde(default)]
+    pub(crate) region_attributes: BTreeMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct VIARect {
+    pub(crate) name: String,
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) width: f32,
+    pub(crate) height: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct VIAImg {
+    pub(crate) filename: String,
+
+    #[serde(default = "unknown_size")]
+    pub(crate) size: i64,
+
+    #[serde(default)]
+    pub(crate) regions: Vec<VIARegion>,
+
+    #[serde(default)]
+    pub(crate) file_attributes: BTreeMap<String, Value>,
+}
+
+/// VIA uses -1 for the file size of images it could not stat.
+fn unknown_size() -> i64 {
+    -1
+}
@@ -0,0 +1,14 @@
+/// WIDER FACE only annotates faces, all boxes share this label.
+pub(crate) const FACE: &str = "face";
+
+/// The names of the WIDER FACE flags preserved as bounding box 
+/// attributes, in file order after `x1 y1 w h`.
+pub(crate) const ATTRS: [&str; 6] = [
+    "blur", "expression", "illumination", "invalid", "occlusion", "pose",
+];
+
+/// The number of fields of a ground truth line.
+pub(crate) const NB_FIELDS_GT: usize = 10;
+
+/// The number of fields of a detection line, `x1 y1 w h score`.
+pub(crate) const NB_FIELDS_DET: usize = 5;
@@ -0,0 +1,241 @@
+use crate::{
+    annotation::Ann,
+    annotationset::AnnSet,
+    rng::Rng,
+};
+
+use std::collections::{HashMap, HashSet};
+
+/// Assigns each annotation to a split, returning the annotation indices 
+/// of each split.
+fn assign(anns: &[&Ann], weights: &[f32], stratify: bool) -> Vec<Vec<usize>> {
+    let n = anns.len();
+    let mut splits = vec![vec![]; weights.len()];
+
+    if !stratify {
+        let mut start = 0;
+        let mut cum = 0.0;
+
+        for (split, w) in splits.iter_mut().zip(weights) {
+            cum += w;
+            let end = ((cum * n as f32).round() as usize).min(n);
+            split.extend(start..end);
+            start = end;
+        }
+
+        // Rounding errors.
+        splits.last_mut().unwrap().extend(start..n);
+
+        return splits
+    }
+
+    // Iterative stratification: images with the rarest labels are 
+    // assigned first, to the split lacking or needing their rarest 
+    // label the most.
+    let img_labels = anns.iter()
+        .map(|ann| {
+            ann.bboxes.iter()
+                .map(|b| b.label.as_str())
+                .collect::<HashSet<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut label_counts = HashMap::<&str, usize>::new();
+    for labels in &img_labels {
+        for &label in labels {
+            *label_counts.entry(label).or_insert(0) += 1;
+        }
+    }
+
+    // Ties are resolved with the label name for determinism.
+    let rarest = img_labels.iter()
+        .map(|labels| labels.iter().copied().min_by_key(|l| (label_counts[l], *l)))
+        .collect::<Vec<_>>();
+
+    let mut order = (0..n).collect::<Vec<_>>();
+    order.sort_by_key(|&i| rarest[i].map_or(usize::MAX, |l| label_counts[l]));
+
+    let mut total_demand = weights.iter()
+        .map(|w| w * n as f32)
+        .collect::<Vec<_>>();
+
+    let mut label_demand = weights.iter()
+        .map(|w| {
+            label_counts.iter()
+                .map(|(&l, &c)| (l, w * c as f32))
+                .collect::<HashMap<_, _>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut has_label = vec![HashSet::new(); weights.len()];
+
+    for i in order {
+        let key = |s: usize| match rarest[i] {
+            Some(l) => (!has_label[s].contains(l), label_demand[s][l], total_demand[s]),
+            None => (false, 0.0, total_demand[s]),
+        };
+
+        // Lowest split index on ties.
+        let s = (0..weights.len())
+            .filter(|&s| weights[s] > 0.0)
+            .rev()
+            .max_by(|&a, &b| key(a).partial_cmp(&key(b)).unwrap())
+            .unwrap();
+
+        splits[s].push(i);
+        total_demand[s] -= 1.0;
+
+        for &label in &img_labels[i] {
+            *label_demand[s].get_mut(label).unwrap() -= 1.0;
+            has_label[s].insert(label);
+        }
+    }
+
+    splits
+}
+
+impl AnnSet {
+    /// Returns the annotations sorted by image identifier then shuffled.
+    fn shuffled(&self, seed: u64) -> Vec<&Ann> {
+        let mut anns = self.iter().collect::<Vec<_>>();
+        anns.sort_by(|a, b| a.img_id.cmp(&b.img_id));
+        Rng::new(seed).shuffle(&mut anns);
+        anns
+    }
+
+    /// Randomly partitions the annotations into named splits with the 
+    /// given ratios. Ratios are normalized to sum to one.
+    /// 
+    /// If `stratify` is set, images are assigned so that each split 
+    /// follows the label distribution of the whole set and each label
+    /// is present in as many splits as possible. Split sizes may then
+    /// slightly differ from the requested ratios.
+    /// 
+    /// The result only depends on the annotations and the seed.
+    /// 
+    /// # Panics
+    /// Will panic if there are no splits, if a ratio is negative or 
+    /// if ratios sum to zero.
+    pub fn split<S: AsRef<str>>(
+        &self, 
+        ratios: &[(S, f32)], 
+        seed: u64, 
+        stratify: bool,
+    ) -> Vec<(String, AnnSet)> {
+        assert!(!ratios.is_empty(), "there should be at least one split");
+        assert!(ratios.iter().all(|(_, r)| *r >= 0.0), "ratios should be positive");
+
+        let sum = ratios.iter().map(|(_, r)| r).sum::<f32>();
+        assert!(sum > 0.0, "ratios should not sum to zero");
+
+        let weights = ratios.iter()
+            .map(|(_, r)| r / sum)
+            .collect::<Vec<_>>();
+
+        let anns = self.shuffled(seed);
+
+        assign(&anns, &weights, stratify).into_iter()
+            .zip(ratios)
+            .map(|(indices, (name, _))| {
+                let annset = indices.into_iter()
+                    .map(|i| anns[i].clone())
+                    .collect();
+
+                (String::from(name.as_ref()), annset)
+            })
+            .collect()
+    }
+
+    /// Partitions the annotations into `k` folds and returns the 
+    /// `(train, validation)` pairs of each fold, where the validation
+    /// set is the fold and the train set the other folds.
+    /// 
+    /// See `AnnSet::split` for `seed` and `stratify`.
+    /// 
+    /// # Panics
+    /// Will panic if `k` is zero.
+    pub fn kfold(&self, k: usize, seed: u64, stratify: bool) -> Vec<(AnnSet, AnnSet)> {
+        assert!(k > 0, "`k` should be greater than zero");
+
+        let ratios = vec![("", 1.0); k];
+        let folds = self.split(&ratios, seed, stratify);
+
+        (0..k)
+            .map(|i| {
+                let train = folds.iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .flat_map(|(_, (_, fold))| fold.iter().cloned())
+                    .collect();
+
+                (train, folds[i].1.clone())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        split::*,
+        bbox::BBox,
+    };
+
+    fn annset() -> AnnSet {
+        (0..100)
+            .map(|i| {
+                // 3 images with a rare label.
+                let label = if i % 33 == 0 { "rare" } else { "common" };
+                let bbox = BBox::new(label, 0.0, 0.0, 1.0, 1.0, None);
+                Ann::new(format!("{i}.jpg"), None, vec![bbox])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_sizes() {
+        let splits = annset().split(&[("train", 0.8), ("val", 0.1), ("test", 0.1)], 0, false);
+
+        let sizes = splits.iter().map(|(_, s)| s.len()).collect::<Vec<_>>();
+        assert_eq!(sizes, [80, 10, 10]);
+        assert_eq!(splits[0].0, "train");
+    }
+
+    #[test]
+    fn split_deterministic() {
+        let annset = annset();
+        let ids = |splits: Vec<(String, AnnSet)>| {
+            let mut ids = splits[1].1.iter().map(|a| a.img_id.clone()).collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+
+        let a = ids(annset.split(&[("train", 0.5), ("val", 0.5)], 7, false));
+        let b = ids(annset.clone().split(&[("train", 0.5), ("val", 0.5)], 7, false));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn split_stratified() {
+        let splits = annset().split(&[("train", 0.8), ("val", 0.1), ("test", 0.1)], 0, true);
+        
+        let total = splits.iter().map(|(_, s)| s.len()).sum::<usize>();
+        assert_eq!(total, 100);
+
+        for (_, split) in &splits {
+            assert!(split.iter().any(|a| a.bboxes[0].label == "rare"));
+            assert!(split.len() >= 9);
+        }
+    }
+
+    #[test]
+    fn kfold() {
+        let folds = annset().kfold(5, 0, true);
+        assert_eq!(folds.len(), 5);
+
+        for (train, val) in &folds {
+            assert_eq!(train.len() + val.len(), 100);
+            assert!(val.iter().all(|a| !train.contains(&a.img_id)));
+        }
+    }
+}
@@ -0,0 +1,363 @@
+use crate::annotationset::AnnSet;
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::{self, Display},
+};
+
+use serde::Serialize;
+
+/// Summary statistics of a set of values.
+#[derive(Debug, Clone, Serialize)]
+pub struct Distribution {
+    /// The number of values.
+    pub count: usize,
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    /// The population standard deviation.
+    pub std: f32,
+    /// The first quartile.
+    pub q1: f32,
+    pub median: f32,
+    /// The third quartile.
+    pub q3: f32,
+}
+
+impl Distribution {
+    /// Computes the distribution of the given values.
+    /// 
+    /// Returns `None` if there are no values.
+    pub fn new(mut values: Vec<f32>) -> Option<Self> {
+        if values.is_empty() {
+            return None
+        }
+
+        values.sort_by(f32::total_cmp);
+
+        let count = values.len();
+        let mean = values.iter().sum::<f32>() / count as f32;
+        let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / count as f32;
+
+        // Linear interpolation between closest ranks.
+        let quantile = |q: f32| {
+            let pos = q * (count - 1) as f32;
+            let (low, high) = (pos.floor() as usize, pos.ceil() as usize);
+            values[low] + (values[high] - values[low]) * (pos - low as f32)
+        };
+
+        Some(Distribution {
+            count,
+            min: values[0],
+            max: values[count - 1],
+            mean,
+            std: var.sqrt(),
+            q1: quantile(0.25),
+            median: quantile(0.5),
+            q3: quantile(0.75),
+        })
+    }
+}
+
+/// Statistics of a single label.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LabelStats {
+    /// The number of bounding boxes with this label.
+    pub nb_boxes: usize,
+    /// The number of images with at least one bounding box with this label.
+    pub nb_images: usize,
+}
+
+/// Distributions of the bounding box dimensions.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BoxStats {
+    pub width: Option<Distribution>,
+    pub height: Option<Distribution>,
+    pub area: Option<Distribution>,
+    /// Width over height, boxes with a null height are ignored.
+    pub aspect_ratio: Option<Distribution>,
+}
+
+/// Statistics of an annotation set.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnnSetStats {
+    pub nb_images: usize,
+    pub nb_boxes: usize,
+    /// Statistics per label.
+    pub labels: BTreeMap<String, LabelStats>,
+    /// The number of images for each number of bounding boxes.
+    pub boxes_per_image: BTreeMap<usize, usize>,
+    /// Bounding box dimensions in pixels.
+    pub boxes: BoxStats,
+    /// Bounding box dimensions relative to the image size. Only images 
+    /// with a known size are considered and the aspect ratio is 
+    /// the ratio of relative dimensions.
+    pub boxes_rel: BoxStats,
+    /// Identifiers of images without bounding boxes, sorted.
+    pub imgs_without_boxes: Vec<String>,
+    /// The number of images for each image size, formatted as `{width}x{height}`.
+    pub img_sizes: BTreeMap<String, usize>,
+    /// The number of images without image size.
+    pub nb_images_without_size: usize,
+}
+
+fn box_stats(dims: Vec<(f32, f32)>) -> BoxStats {
+    let aspect_ratios = dims.iter()
+        .filter(|(_, h)| *h > 0.0)
+        .map(|(w, h)| w / h)
+        .collect();
+
+    BoxStats {
+        width: Distribution::new(dims.iter().map(|d| d.0).collect()),
+        height: Distribution::new(dims.iter().map(|d| d.1).collect()),
+        area: Distribution::new(dims.iter().map(|d| d.0 * d.1).collect()),
+        aspect_ratio: Distribution::new(aspect_ratios),
+    }
+}
+
+impl AnnSet {
+    /// Computes statistics of the annotation set.
+    pub fn stats(&self) -> AnnSetStats {
+        let mut labels = BTreeMap::<String, LabelStats>::new();
+        let mut boxes_per_image = BTreeMap::new();
+        let mut img_sizes = BTreeMap::new();
+        let mut imgs_without_boxes = vec![];
+        let mut nb_images_without_size = 0;
+        let mut nb_boxes = 0;
+
+        let mut dims = vec![];
+        let mut dims_rel = vec![];
+
+        for ann in self {
+            nb_boxes += ann.bboxes.len();
+            *boxes_per_image.entry(ann.bboxes.len()).or_insert(0) += 1;
+
+            if ann.bboxes.is_empty() {
+                imgs_without_boxes.push(ann.img_id.clone());
+            }
+
+            match ann.img_size {
+                Some(size) => {
+                    let key = format!("{}x{}", size.width, size.height);
+                    *img_sizes.entry(key).or_insert(0) += 1;
+                },
+                None => nb_images_without_size += 1,
+            }
+
+            let mut img_labels = HashSet::new();
+
+            for bbox in &ann.bboxes {
+                let stats = labels.entry(bbox.label.clone()).or_default();
+                stats.nb_boxes += 1;
+
+                if img_labels.insert(bbox.label.as_str()) {
+                    stats.nb_images += 1;
+                }
+
+                dims.push((bbox.width(), bbox.height()));
+
+                if let Some(size) = ann.img_size {
+                    let (w, h) = (size.width as f32, size.height as f32);
+                    dims_rel.push((bbox.width() / w, bbox.height() / h));
+                }
+            }
+        }
+
+        imgs_without_boxes.sort();
+
+        AnnSetStats {
+            nb_images: self.len(),
+            nb_boxes,
+            labels,
+            boxes_per_image,
+            boxes: box_stats(dims),
+            boxes_rel: box_stats(dims_rel),
+            imgs_without_boxes,
+            img_sizes,
+            nb_images_without_size,
+        }
+    }
+}
+
+impl BoxStats {
+    fn rows(&self) -> [(&'static str, Option<&Distribution>); 4] {
+        [
+            ("width", self.width.as_ref()),
+            ("height", self.height.as_ref()),
+            ("area", self.area.as_ref()),
+            ("aspect_ratio", self.aspect_ratio.as_ref()),
+        ]
+    }
+}
+
+const DIST_FIELDS: [&str; 8] = ["count", "min", "max", "mean", "std", "q1", "median", "q3"];
+
+impl Distribution {
+    fn values(&self) -> [f32; 8] {
+        [
+            self.count as f32, self.min, self.max, self.mean, 
+            self.std, self.q1, self.median, self.q3,
+        ]
+    }
+}
+
+impl AnnSetStats {
+    /// Serializes the statistics to CSV with the columns 
+    /// `section,key,field,value`.
+    pub fn to_csv(&self) -> String {
+        let mut rows: Vec<[String; 4]> = vec![];
+
+        let mut push = |section: &str, key: &str, field: &str, value: String| {
+            rows.push([section.into(), key.into(), field.into(), value]);
+        };
+
+        push("dataset", "", "nb_images", self.nb_images.to_string());
+        push("dataset", "", "nb_boxes", self.nb_boxes.to_string());
+        push("dataset", "", "nb_images_without_boxes", self.imgs_without_boxes.len().to_string());
+        push("dataset", "", "nb_images_without_size", self.nb_images_without_size.to_string());
+
+        for (label, stats) in &self.labels {
+            push("label", label, "nb_boxes", stats.nb_boxes.to_string());
+            push("label", label, "nb_images", stats.nb_images.to_string());
+        }
+
+        for (nb_boxes, nb_images) in &self.boxes_per_image {
+            push("boxes_per_image", &nb_boxes.to_string(), "nb_images", nb_images.to_string());
+        }
+
+        for (section, box_stats) in [("boxes", &self.boxes), ("boxes_rel", &self.boxes_rel)] {
+            for (key, dist) in box_stats.rows() {
+                if let Some(dist) = dist {
+                    for (field, value) in DIST_FIELDS.iter().zip(dist.values()) {
+                        push(section, key, field, value.to_string());
+                    }
+                }
+            }
+        }
+
+        for (size, nb_images) in &self.img_sizes {
+            push("img_size", size, "nb_images", nb_images.to_string());
+        }
+
+        for img_id in &self.imgs_without_boxes {
+            push("img_without_boxes", img_id, "", String::new());
+        }
+
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        writer.write_record(["section", "key", "field", "value"])
+            .expect("writing to memory should not fail");
+
+        for row in rows {
+            writer.write_record(&row)
+                .expect("writing to memory should not fail");
+        }
+
+        let bytes = writer.into_inner()
+            .expect("writing to memory should not fail");
+
+        String::from_utf8(bytes).expect("CSV should be valid UTF-8")
+    }
+}
+
+impl Display for AnnSetStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Images: {}", self.nb_images)?;
+        writeln!(f, "Boxes: {}", self.nb_boxes)?;
+        writeln!(f, "Images without boxes: {}", self.imgs_without_boxes.len())?;
+        writeln!(f, "Images without size: {}", self.nb_images_without_size)?;
+
+        writeln!(f, "\n{:<24} {:>10} {:>10}", "Label", "Boxes", "Images")?;
+        for (label, stats) in &self.labels {
+            writeln!(f, "{:<24} {:>10} {:>10}", label, stats.nb_boxes, stats.nb_images)?;
+        }
+
+        writeln!(f, "\n{:<24} {:>10}", "Boxes per image", "Images")?;
+        for (nb_boxes, nb_images) in &self.boxes_per_image {
+            writeln!(f, "{:<24} {:>10}", nb_boxes, nb_images)?;
+        }
+
+        for (title, box_stats) in [("Box (px)", &self.boxes), ("Box (relative)", &self.boxes_rel)] {
+            if box_stats.width.is_none() {
+                continue
+            }
+
+            write!(f, "\n{:<24}", title)?;
+            for field in DIST_FIELDS {
+                write!(f, " {:>10}", field)?;
+            }
+            writeln!(f)?;
+
+            for (key, dist) in box_stats.rows() {
+                if let Some(dist) = dist {
+                    write!(f, "{:<24} {:>10}", key, dist.count)?;
+                    for value in &dist.values()[1..] {
+                        write!(f, " {:>10.4}", value)?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+        }
+
+        writeln!(f, "\n{:<24} {:>10}", "Image size", "Images")?;
+        for (size, nb_images) in &self.img_sizes {
+            writeln!(f, "{:<24} {:>10}", size, nb_images)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        stats::*,
+        imgsize::ImgSize,
+        bbox::BBox,
+        annotation::Ann,
+    };
+
+    #[test]
+    fn distribution() {
+        let dist = Distribution::new(vec![4.0, 1.0, 3.0, 2.0]).unwrap();
+
+        assert_eq!(dist.count, 4);
+        assert_eq!(dist.min, 1.0);
+        assert_eq!(dist.max, 4.0);
+        assert_eq!(dist.mean, 2.5);
+        assert_eq!(dist.median, 2.5);
+        assert_eq!(dist.q1, 1.75);
+
+        assert!(Distribution::new(vec![]).is_none());
+    }
+
+    #[test]
+    fn annset_stats() {
+        let mut annset = AnnSet::new();
+
+        annset.insert(Ann::new("a.jpg", Some(ImgSize::new(100, 100)), vec![
+            BBox::new("cat", 0.0, 0.0, 10.0, 20.0, None),
+            BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None),
+            BBox::new("dog", 0.0, 0.0, 50.0, 50.0, None),
+        ]));
+        annset.insert(Ann::new("b.jpg", None, vec![
+            BBox::new("cat", 0.0, 0.0, 10.0, 10.0, None),
+        ]));
+        annset.insert(Ann::empty("c.jpg"));
+
+        let stats = annset.stats();
+
+        assert_eq!(stats.nb_images, 3);
+        assert_eq!(stats.nb_boxes, 4);
+        assert_eq!(stats.labels["cat"].nb_boxes, 3);
+        assert_eq!(stats.labels["cat"].nb_images, 2);
+        assert_eq!(stats.labels["dog"].nb_images, 1);
+        assert_eq!(stats.boxes_per_image[&0], 1);
+        assert_eq!(stats.boxes_per_image[&3], 1);
+        assert_eq!(stats.imgs_without_boxes, vec!["c.jpg"]);
+        assert_eq!(stats.nb_images_without_size, 2);
+        assert_eq!(stats.boxes.width.as_ref().unwrap().count, 4);
+        assert_eq!(stats.boxes_rel.width.as_ref().unwrap().count, 3);
+        assert_eq!(stats.boxes_rel.area.as_ref().unwrap().max, 0.25);
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{
+    imgsize::ImgSize,
+    annotation::Ann,
+    annotationset::AnnSet,
+};
+
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+    path::Path,
+};
+
+/// The severity of a validation issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Suspicious but usable annotations.
+    Warning,
+    /// Annotations that are invalid or that some formats cannot represent.
+    Error,
+}
+
+/// A problem found in an annotation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IssueKind {
+    /// The bounding box extends beyond the image.
+    OutOfBounds,
+    /// The bounding box has a null area.
+    EmptyBox,
+    /// The bounding box has the same label and coordinates as a previous one.
+    DuplicateBox,
+    /// The label is empty or contains whitespace, which breaks
+    /// whitespace-separated formats such as YOLO or KITTI.
+    LabelWhitespace,
+    /// The label is not in the list of allowed labels.
+    UnknownLabel,
+    /// The annotation has no image size.
+    MissingImgSize,
+    /// The image file does not exist or is unreadable.
+    MissingImgFile,
+    /// The annotation image size differs from the image file size.
+    ImgSizeMismatch { expected: ImgSize, actual: ImgSize },
+}
+
+impl IssueKind {
+    /// The default severity of the issue.
+    pub fn severity(&self) -> Severity {
+        match self {
+            IssueKind::OutOfBounds
+            | IssueKind::DuplicateBox
+            | IssueKind::MissingImgSize => Severity::Warning,
+
+            IssueKind::EmptyBox
+            | IssueKind::LabelWhitespace
+            | IssueKind::UnknownLabel
+            | IssueKind::MissingImgFile
+            | IssueKind::ImgSizeMismatch { .. } => Severity::Error,
+        }
+    }
+}
+
+/// A validation issue of an annotation or of one of its bounding boxes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    /// The image identifier of the annotation.
+    pub img_id: String,
+    /// The index of the bounding box in the annotation, if the issue
+    /// concerns a bounding box.
+    pub bbox_index: Option<usize>,
+    pub kind: IssueKind,
+}
+
+impl Issue {
+    pub fn severity(&self) -> Severity {
+        self.kind.severity()
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IssueKind::OutOfBounds => write!(f, "bounding box outside of the image"),
+            IssueKind::EmptyBox => write!(f, "bounding box with a null area"),
+            IssueKind::DuplicateBox => write!(f, "duplicate bounding box"),
+            IssueKind::LabelWhitespace => write!(f, "empty label or label with whitespace"),
+            IssueKind::UnknownLabel => write!(f, "unknown label"),
+            IssueKind::MissingImgSize => write!(f, "missing image size"),
+            IssueKind::MissingImgFile => write!(f, "missing or unreadable image file"),
+            IssueKind::ImgSizeMismatch { expected, actual } => write!(
+                f, "image size is {}x{} but the image file is {}x{}",
+                expected.width, expected.height, actual.width, actual.height,
+            ),
+        }
+    }
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity(), self.img_id)?;
+
+        if let Some(index) = self.bbox_index {
+            write!(f, " (box {index})")?;
+        }
+
+        write!(f, ": {}", self.kind)
+    }
+}
+
+impl Ann {
+    /// Checks the annotation for problems.
+    /// 
+    /// See `AnnSet::validate` for details.
+    pub fn validate(
+        &self,
+        img_path: Option<&Path>,
+        allowed_labels: Option<&HashSet<String>>,
+    ) -> Vec<Issue> {
+        let mut issues = vec![];
+
+        let mut push = |bbox_index, kind| {
+            issues.push(Issue { img_id: self.img_id.clone(), bbox_index, kind });
+        };
+
+        if self.img_size.is_none() {
+            push(None, IssueKind::MissingImgSize);
+        }
+
+        if let Some(img_path) = img_path {
+            match (ImgSize::from_file(img_path), self.img_size) {
+                (Err(_), _) => push(None, IssueKind::MissingImgFile),
+                (Ok(actual), Some(expected)) if actual != expected => {
+                    push(None, IssueKind::ImgSizeMismatch { expected, actual })
+                },
+                _ => (),
+            }
+        }
+
+        for (i, bbox) in self.bboxes.iter().enumerate() {
+            if let Some(ImgSize { width, height }) = self.img_size {
+                let (xmin, ymin, xmax, ymax) = bbox.ltrb();
+
+                if xmin < 0.0 || ymin < 0.0 || xmax > width as f32 || ymax > height as f32 {
+                    push(Some(i), IssueKind::OutOfBounds);
+                }
+            }
+
+            if bbox.area() <= 0.0 {
+                push(Some(i), IssueKind::EmptyBox);
+            }
+
+            if self.bboxes[..i].iter().any(|b| b.label == bbox.label && b.ltrb() == bbox.ltrb()) {
+                push(Some(i), IssueKind::DuplicateBox);
+            }
+
+            if bbox.label.is_empty() || bbox.label.contains(char::is_whitespace) {
+                push(Some(i), IssueKind::LabelWhitespace);
+            }
+
+            if allowed_labels.is_some_and(|labels| !labels.contains(&bbox.label)) {
+                push(Some(i), IssueKind::UnknownLabel);
+            }
+        }
+
+        issues
+    }
+}
+
+impl AnnSet {
+    /// Checks the annotations for problems such as bounding boxes outside 
+    /// of the image, null area or duplicate bounding boxes, labels with 
+    /// whitespace or missing image sizes.
+    /// 
+    /// If `imgs_path` is provided, the image files are checked to exist
+    /// and to have the annotated size. If `allowed_labels` is provided, 
+    /// labels not in this set are reported.
+    /// 
+    /// Issues are sorted by image identifier.
+    pub fn validate<P: AsRef<Path>>(
+        &self,
+        imgs_path: Option<P>,
+        allowed_labels: Option<&HashSet<String>>,
+    ) -> Vec<Issue> {
+        let mut anns = self.iter().collect::<Vec<_>>();
+        anns.sort_by(|a, b| a.img_id.cmp(&b.img_id));
+
+        anns.into_iter()
+            .flat_map(|ann| {
+                let img_path = imgs_path.as_ref().map(|p| p.as_ref().join(&ann.img_id));
+                ann.validate(img_path.as_deref(), allowed_labels)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        validation::*,
+        bbox::BBox,
+    };
+
+    #[test]
+    fn validate() {
+        let labels = HashSet::from([String::from("cat"), String::from("dog")]);
+
+        let ann = Ann::new("a.jpg", Some(ImgSize::new(100, 100)), vec![
+            BBox::new("cat", 10.0, 10.0, 20.0, 20.0, None),
+            BBox::new("cat", 10.0, 10.0, 20.0, 20.0, None),
+            BBox::new("dog", 90.0, 90.0, 110.0, 100.0, None),
+            BBox::new("big dog", 10.0, 10.0, 10.0, 20.0, None),
+        ]);
+
+        let kinds = ann.validate(None, Some(&labels)).into_iter()
+            .map(|i| (i.bbox_index, i.kind))
+            .collect::<Vec<_>>();
+
+        assert_eq!(kinds, vec![
+            (Some(1), IssueKind::DuplicateBox),
+            (Some(2), IssueKind::OutOfBounds),
+            (Some(3), IssueKind::EmptyBox),
+            (Some(3), IssueKind::LabelWhitespace),
+            (Some(3), IssueKind::UnknownLabel),
+        ]);
+    }
+
+    #[test]
+    fn validate_missing_img() {
+        let ann = Ann::empty("a.jpg");
+        let issues = ann.validate(Some(Path::new("does/not/exist.jpg")), None);
+
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].kind, IssueKind::MissingImgSize);
+        assert_eq!(issues[1].kind, IssueKind::MissingImgFile);
+        assert_eq!(issues[1].severity(), Severity::Error);
+    }
+}